@@ -1,25 +1,35 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{net::TcpStream, thread, time::Duration};
 
-use anyhow::Result;
-use ed25519_dalek::SigningKey;
-use hmf_core::envelope::sign::sign_envelope_ed25519;
+use anyhow::{Context, Result, bail};
 
+use hmf_core::crypto::keyfile::{KeyFile, KeyProtection};
+use hmf_core::endpoint::{EndpointContext, Routing};
 use hmf_core::envelope::*;
-use hmf_core::ids::{DeviceId, IdempotencyKey, InstanceId, TransactionId};
-use hmf_transport::transport::tcp::write_record;
+use hmf_core::ids::DeviceId;
+use hmf_transport::transport::Connection;
 
-// dev/test only
-const DEVICE1_SK_BYTES: [u8; 32] = [7u8; 32];
+const USAGE: &str = "usage: hmf-device <sender_id> <key-file>";
 
 fn main() -> Result<()> {
-    let signing_key = SigningKey::from_bytes(&DEVICE1_SK_BYTES);
-    let verifying_key = signing_key.verifying_key();
-    println!("device pubkey = {:?}", verifying_key.to_bytes());
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [sender_id, path] = args.as_slice() else {
+        bail!(USAGE);
+    };
+    let key_file = KeyFile::read(path, &KeyProtection::from_env()?)
+        .with_context(|| format!("loading {path}"))?;
+    println!("device key_id = {}", key_file.key_id());
+
+    let mut endpoint =
+        EndpointContext::from_signer(DeviceId::new(sender_id.as_str()), key_file.into_signer());
+    println!("device sender_instance = {}", endpoint.sender_instance());
 
     let mut stream = TcpStream::connect("127.0.0.1:7878")?;
-    let mut counter: u64 = 1;
+    run(&mut stream, &mut endpoint)
+}
 
+/// Sends a lifecycle heartbeat every two seconds.
+fn run<C: Connection>(conn: &mut C, endpoint: &mut EndpointContext) -> Result<()> {
     loop {
         let uptime_ms = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -33,34 +43,15 @@ fn main() -> Result<()> {
             })),
         };
 
-        let mut env = Envelope {
-            proto_ver: EXPECTED_PROTO_VER,
-            msg_class: MsgClass::Telemetry,
-            sender_id: DeviceId::new("device-1"),
-            sender_instance: InstanceId::new("device-1:boot-1"),
-            counter,
-            ttl_ms: 5_000,
-            transaction_id: TransactionId::new(format!("txn-{counter}")),
-            idempotency_key: IdempotencyKey::new(format!("idem-{counter}")),
-            delivery_profile: DeliveryProfile::BestEffort,
-
-            topic: "zone:demo".to_string(),
-            target: "site-warden".to_string(),
-            scope: "hmf/telemetry/lifecycle_heartbeat".to_string(),
-
-            payload: Some(Payload::Telemetry(telemetry)),
-
-            sig_alg: SigAlg::Unspecified,
-            signature: Vec::new(),
-            key_id: String::new(),
-            auth_context: Vec::new(),
-        };
-
-        sign_envelope_ed25519(&mut env, "device-1:ed25519:v1", &signing_key);
-        write_record(&mut stream, &env)?;
-        println!("sent heartbeat #{counter}");
+        let routing = Routing::new(
+            "zone:demo",
+            "site-warden",
+            "hmf/telemetry/lifecycle_heartbeat",
+        );
+        let env = endpoint.send(Payload::Telemetry(telemetry), routing)?;
+        conn.send(&env)?;
+        println!("sent heartbeat #{}", env.counter);
 
-        counter += 1;
         thread::sleep(Duration::from_secs(2));
     }
 }
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result, bail};
+
+use hmf_core::crypto::keyfile::suggested_key_id;
+use hmf_core::crypto::keyfile::{KEK_ENV, KeyFile, KeyProtection, PASSPHRASE_ENV};
+use hmf_core::hex;
+use hmf_core::ids::{DeviceId, new_sender_instance};
+use hmf_core::trust::attestation::{AttestationEvidence, EnrollmentRequest};
+use hmf_core::trust::enrollment::sign_request;
+
+const USAGE: &str = "usage: hmf-keygen <sender_id> <key-file> [key-version]\n       \
+     hmf-keygen enroll <sender_id> <key-file> <request-file> \
+     [<device certificate.der> [<intermediate.der>...]]";
+
+/// Generates a signing key, seals it to a new key file under the passphrase
+/// or KEK from the environment, and prints what enrollment needs. With
+/// `enroll`, writes an enrollment request signed by an existing key file.
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let [command, sender_id, key_path, request_path, chain @ ..] = args.as_slice()
+        && command == "enroll"
+    {
+        return enroll(sender_id, key_path, request_path, chain);
+    }
+    let (sender_id, path, version) = match args.as_slice() {
+        [sender_id, path] => (sender_id, path, 1),
+        [sender_id, path, version] => (sender_id, path, version.parse().context(USAGE)?),
+        _ => bail!(USAGE),
+    };
+    let protection = protection()?;
+
+    let sender_id = DeviceId::new(sender_id.as_str());
+    let key_file = KeyFile::generate(&suggested_key_id(&sender_id, version));
+    key_file
+        .write(path, &protection)
+        .with_context(|| format!("writing {path}"))?;
+
+    let public_key = hex::encode(key_file.verifying_key().as_bytes());
+    println!("key_file:   {path}");
+    println!("sender_id:  {sender_id}");
+    println!("key_id:     {}", key_file.key_id());
+    println!("public_key: {public_key}");
+    Ok(())
+}
+
+/// Writes an enrollment request for the key in `key_path`, proposing a fresh
+/// sender_instance and carrying the manufacturer certificates in `chain`.
+fn enroll(sender_id: &str, key_path: &str, request_path: &str, chain: &[String]) -> Result<()> {
+    let key_file =
+        KeyFile::read(key_path, &protection()?).with_context(|| format!("loading {key_path}"))?;
+    let request = EnrollmentRequest {
+        sender_id: DeviceId::new(sender_id),
+        sender_instance: new_sender_instance(),
+        key_id: key_file.key_id().to_string(),
+        verifying_key: key_file.verifying_key(),
+        attestation: match chain {
+            [] => None,
+            paths => Some(AttestationEvidence::X509Chain(
+                paths.iter().map(std::fs::read).collect::<Result<_, _>>()?,
+            )),
+        },
+    };
+    let bytes = sign_request(&request, &key_file.into_signer())?;
+    std::fs::write(request_path, bytes).with_context(|| format!("writing {request_path}"))?;
+
+    println!("request_file:    {request_path}");
+    println!("sender_id:       {}", request.sender_id);
+    println!("sender_instance: {}", request.sender_instance);
+    println!("key_id:          {}", request.key_id);
+    Ok(())
+}
+
+fn protection() -> Result<KeyProtection> {
+    KeyProtection::from_env()
+        .with_context(|| format!("set {PASSPHRASE_ENV} or {KEK_ENV} to protect the key file"))
+}
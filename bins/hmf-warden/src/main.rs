@@ -1,47 +1,166 @@
-use std::net::TcpListener;
-
-use anyhow::Result;
-use ed25519_dalek::SigningKey;
-
-use hmf_core::envelope::sign::verify_envelope_ed25519;
-use hmf_transport::transport::tcp::read_record;
-
-const DEVICE1_SK_BYTES: [u8; 32] = [7u8; 32];
-
-fn main() -> Result<()> {
-    let verifying_key = SigningKey::from_bytes(&DEVICE1_SK_BYTES).verifying_key();
-    println!("warden expects pubkey = {:?}", verifying_key.to_bytes());
-
-    let listener = TcpListener::bind("127.0.0.1:7878")?;
-    loop {
-        println!("warden listening on 127.0.0.1:7878");
-        let (mut stream, addr) = listener.accept()?;
-        println!("hmf-warden: accepted connection from {addr}");
-
-        loop {
-            let maybe_env = read_record(&mut stream)?;
-            let Some(env) = maybe_env else {
-                println!("hmf-warden: connection closed (EOF)");
-                break;
-            };
-            if !verify_envelope_ed25519(&env, &verifying_key) {
-                println!("signature invalid — dropping envelope");
-                continue;
-            }
+use anyhow::{Context, Result, bail};
+
+use hmf_core::crypto::keyfile::{KeyFile, KeyProtection};
+use hmf_core::endpoint::EndpointContext;
+use hmf_core::envelope::{Payload, TelemetryPayload};
+use hmf_core::ids::DeviceId;
+use hmf_core::runtime::Runtime;
+use hmf_core::runtime::handler::{Authorizer, TelemetrySink, VerifiedContext};
+use hmf_core::trust::TrustRegistry;
+use hmf_core::trust::attestation::x509::X509ChainVerifier;
+use hmf_core::trust::attestation::{
+    AttestationResult, AttestationVerifier, EnrollmentRequest, attest,
+};
+use hmf_core::trust::enrollment::verify_request;
+use hmf_transport::transport::server::{AsyncListener, Incoming};
+use tokio::sync::mpsc;
+
+const USAGE: &str = "usage: hmf-warden <warden key-file> [<enrollment request>...]";
+
+/// Colon-separated DER manufacturer root certificates for attestation.
+const ATTESTATION_ROOTS_ENV: &str = "HMF_ATTESTATION_ROOTS";
+
+/// Envelopes queued from connection tasks ahead of the runtime.
+const INCOMING_QUEUE_DEPTH: usize = 256;
+
+/// The warden only consumes telemetry on the data plane.
+struct TelemetryOnly;
+
+impl Authorizer for TelemetryOnly {
+    fn authorize(&self, _ctx: &VerifiedContext, payload: &Payload) -> bool {
+        matches!(payload, Payload::Telemetry(_))
+    }
+}
+
+struct PrintSink;
+
+impl TelemetrySink for PrintSink {
+    fn on_telemetry(&mut self, ctx: &VerifiedContext, telemetry: &TelemetryPayload) {
+        println!("hmf-warden: received telemetry:");
+        println!("  sender_id: {}", ctx.sender_id());
+        println!("  sender_instance: {}", ctx.sender_instance());
+        println!("  counter: {}", ctx.counter());
+        println!("  transaction_id: {}", ctx.transaction_id());
+        println!("  idempotency_key: {}", ctx.idempotency_key());
+        println!("  scope: {}", ctx.scope());
+        println!("  target: {}", ctx.target());
+        println!("  topic: {}", ctx.topic());
+        println!("  payload: {telemetry:?}");
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Enrollment requests written by `hmf-keygen enroll`; naming one here is
+    // the operator's explicit approval of it.
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [key_path, requests @ ..] = args.as_slice() else {
+        bail!(USAGE);
+    };
+    // The warden signs responses with a persistent key, so devices can keep
+    // verifying them across restarts.
+    let warden_key = KeyFile::read(key_path, &KeyProtection::from_env()?)
+        .with_context(|| format!("loading {key_path}"))?;
+    println!("warden key_id = {}", warden_key.key_id());
+
+    let x509 = x509_verifier()?;
+    let verifiers = x509
+        .iter()
+        .map(|v| v as &dyn AttestationVerifier)
+        .collect::<Vec<_>>();
+    let mut registry = TrustRegistry::new();
+    for path in requests {
+        let bytes = std::fs::read(path).with_context(|| format!("reading {path}"))?;
+        let request =
+            verify_request(&bytes).with_context(|| format!("enrollment request {path}"))?;
+        enroll(&mut registry, &request, &verifiers)?;
+    }
 
-            println!("hmf-warden: received envelope:");
-            println!("  proto_ver: {}", env.proto_ver);
-            println!("  msg_class: {:?}", env.msg_class);
-            println!("  sender_id: {}", env.sender_id);
-            println!("  sender_instance: {}", env.sender_instance);
-            println!("  counter: {}", env.counter);
-            println!("  ttl_ms: {}", env.ttl_ms);
-            println!("  transaction_id: {}", env.transaction_id);
-            println!("  idempotency_key: {}", env.idempotency_key);
-            println!("  delivery_profile: {:?}", env.delivery_profile);
-            println!("  scope: {}", env.scope);
-            println!("  target: {}", env.target);
-            println!("  topic: {}", env.topic);
+    let endpoint =
+        EndpointContext::from_signer(DeviceId::new("site-warden"), warden_key.into_signer());
+    let mut runtime =
+        Runtime::new(endpoint, registry, TelemetryOnly).with_telemetry_sink(PrintSink);
+
+    let listener = AsyncListener::bind("127.0.0.1:7878".parse()?).await?;
+    println!("warden listening on {}", listener.local_addr()?);
+
+    // Connections are served concurrently; the runtime consumes them in order
+    // of arrival so replay and config state have a single writer.
+    let (tx, mut rx) = mpsc::channel(INCOMING_QUEUE_DEPTH);
+    let accept = tokio::spawn(listener.run(tx));
+    while let Some(incoming) = rx.recv().await {
+        process(&mut runtime, incoming).await;
+    }
+    accept.await??;
+    Ok(())
+}
+
+/// The X.509 chain verifier, if manufacturer roots are configured.
+fn x509_verifier() -> Result<Option<X509ChainVerifier>> {
+    let roots = std::env::var_os(ATTESTATION_ROOTS_ENV)
+        .map(|paths| std::env::split_paths(&paths).map(std::fs::read).collect())
+        .transpose()?
+        .unwrap_or_else(Vec::new);
+    Ok(match roots.as_slice() {
+        [] => None,
+        roots => Some(X509ChainVerifier::new(roots)?),
+    })
+}
+
+/// Approves a signed enrollment request, recording manufacturer attestation
+/// as approval metadata. A failed attestation is reported but does not block
+/// the operator's explicit approval, and a verified one is never a
+/// substitute for it.
+fn enroll(
+    registry: &mut TrustRegistry,
+    request: &EnrollmentRequest,
+    verifiers: &[&dyn AttestationVerifier],
+) -> Result<()> {
+    let attestation = attest(request, verifiers);
+    match &attestation {
+        AttestationResult::NotProvided => {}
+        AttestationResult::Unsupported { format } => {
+            println!("warden: no verifier for {format} attestation; set {ATTESTATION_ROOTS_ENV}")
+        }
+        AttestationResult::Verified { verifier, detail } => {
+            println!("warden: attestation verified by {verifier}: {detail}")
+        }
+        AttestationResult::Failed { verifier, reason } => {
+            println!("warden: attestation FAILED in {verifier}: {reason}")
+        }
+    }
+    registry.approve_enrollment(request, attestation)?;
+    println!(
+        "warden approved {} for {} (proposed sender_instance {})",
+        request.key_id, request.sender_id, request.sender_instance
+    );
+    Ok(())
+}
+
+async fn process(runtime: &mut Runtime, incoming: Incoming) {
+    let replies = incoming.replies().clone();
+    let inbound = incoming.inbound;
+    let responses = match runtime.process(inbound.envelope, inbound.first_observed) {
+        Ok(accepted) => {
+            for e in &accepted.unsigned {
+                println!(
+                    "hmf-warden: could not sign response to {}: {e}",
+                    inbound.peer
+                );
+            }
+            accepted.responses
+        }
+        Err(rejected) => {
+            println!(
+                "hmf-warden: rejected envelope from {}: {}",
+                inbound.peer, rejected.reason
+            );
+            rejected.response.map(|r| vec![*r]).unwrap_or_default()
+        }
+    };
+    for response in responses {
+        if let Err(e) = replies.send(response).await {
+            println!("hmf-warden: dropped response to {}: {e}", inbound.peer);
         }
     }
 }
@@ -0,0 +1,90 @@
+//! Compares batch signature verification with looped per-envelope verification.
+//!
+//! ```text
+//! cargo bench -p hmf-core --bench batch_verify
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+use hmf_core::Envelope;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::sign::{verify_envelope_ed25519, verify_envelopes_ed25519};
+use hmf_core::envelope::{
+    Health, LifecycleHeartbeat, Payload, Quality, StateItem, StateUpdate, StateValue, Telemetry,
+    TelemetryPayload,
+};
+use hmf_core::ids::DeviceId;
+
+const ROUNDS: u32 = 20;
+
+fn telemetry(n: usize) -> Vec<Envelope> {
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let mut endpoint = EndpointContext::new(DeviceId::new("device-1"), "device-1:ed25519:v1", key);
+    (0..n)
+        .map(|i| {
+            let body = if i % 2 == 0 {
+                TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+                    uptime_ms: i as u64,
+                    health: Health::Ok,
+                })
+            } else {
+                TelemetryPayload::State(StateUpdate {
+                    local_seq: i as u64,
+                    items: vec![StateItem {
+                        key: "temp".to_string(),
+                        value: Some(StateValue::F64(21.5)),
+                        unit: "C".to_string(),
+                        quality: Quality::Good,
+                    }],
+                })
+            };
+            let payload = Payload::Telemetry(Telemetry {
+                payload: Some(body),
+            });
+            endpoint
+                .send(payload, Routing::new("telemetry", "warden", "site"))
+                .unwrap()
+        })
+        .collect()
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let key = SigningKey::from_bytes(&[1; 32]).verifying_key();
+    println!(
+        "{:>6} {:>14} {:>14} {:>8}",
+        "batch", "looped/env", "batched/env", "speedup"
+    );
+    for n in [16, 64, 256, 1024] {
+        let envs = telemetry(n);
+        let batch = envs.iter().map(|env| (env, &key)).collect::<Vec<_>>();
+
+        let looped = time(|| {
+            for env in &envs {
+                black_box(verify_envelope_ed25519(black_box(env), &key)).unwrap();
+            }
+        });
+        let batched = time(|| {
+            for result in black_box(verify_envelopes_ed25519(black_box(&batch))) {
+                result.unwrap();
+            }
+        });
+
+        println!(
+            "{n:>6} {:>14?} {:>14?} {:>7.2}x",
+            looped / n as u32,
+            batched / n as u32,
+            looped.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+}
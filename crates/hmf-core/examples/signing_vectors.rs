@@ -0,0 +1,15 @@
+//! Regenerates the golden signing-bytes vectors.
+//!
+//! ```text
+//! cargo run -p hmf-core --example signing_vectors > crates/hmf-core/tests/vectors/signing-v1.json
+//! ```
+//!
+//! Third-party implementations can validate against the committed file; see
+//! `docs/06-testing/conformance.md` for the format.
+
+#[path = "../tests/support/vectors.rs"]
+mod vectors;
+
+fn main() {
+    print!("{}", vectors::render(&vectors::generate()));
+}
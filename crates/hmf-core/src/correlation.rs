@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::envelope::*;
+use crate::error::CorrelationError;
+use crate::ids::{DeviceId, TransactionId};
+
+/// Final outcome of a tracked request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// `OpResult` for a `CommandRequest` or `ConfigUpdate`.
+    Completed(OpResult),
+    /// `ConfigSnapshot` answering a `ConfigQuery`.
+    Snapshot(ConfigSnapshot),
+    /// `EngineeringResult` for an `EngineeringRequest`.
+    Engineering(EngineeringResult),
+    /// The receiver rejected the request before execution.
+    Rejected {
+        reason: RejectReason,
+        detail: String,
+    },
+    /// No final response arrived before the deadline. Requests rejected
+    /// before signature verification are never answered and end here.
+    TimedOut { acknowledged: bool },
+}
+
+/// Progress reported for a tracked request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The receiver acknowledged receipt; the final response is still pending.
+    Acknowledged {
+        transaction_id: TransactionId,
+        request_id: String,
+    },
+    /// The request finished and is no longer tracked.
+    Resolved(Resolution),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resolution {
+    pub transaction_id: TransactionId,
+    pub request_id: String,
+    pub outcome: Outcome,
+}
+
+#[derive(Clone, Debug)]
+struct Pending {
+    request_id: String,
+    msg_class: MsgClass,
+    responder: DeviceId,
+    deadline: Instant,
+    acknowledged: bool,
+}
+
+/// Client-side correlation of outbound requests with their responses.
+///
+/// Requests are keyed on `transaction_id`, which responders echo back. Time is
+/// supplied by the caller, so timeouts are deterministic.
+///
+/// [`PendingRequests::on_response`] only takes a [`VerifiedEnvelope`], such as
+/// [`crate::runtime::Accepted::envelope`]: correlation trusts the sender
+/// identity and does not verify signatures itself.
+#[derive(Debug, Default)]
+pub struct PendingRequests {
+    pending: HashMap<TransactionId, Pending>,
+}
+
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn is_pending(&self, transaction_id: &TransactionId) -> bool {
+        self.pending.contains_key(transaction_id)
+    }
+
+    /// Starts tracking a signed outbound `request` sent at `now`, to be
+    /// answered by `responder`.
+    ///
+    /// `responder` is the endpoint the request was sent to; the envelope's
+    /// `target` is only a routing hint and is not used to match responses.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CorrelationError`] if `request` does not carry a request
+    /// payload or its `transaction_id` is already outstanding.
+    pub fn track(
+        &mut self,
+        request: &Envelope,
+        responder: DeviceId,
+        now: Instant,
+        timeout: Duration,
+    ) -> Result<(), CorrelationError> {
+        let request_id = request_id_of(request).ok_or(CorrelationError::NotARequest)?;
+        if self.pending.contains_key(&request.transaction_id) {
+            return Err(CorrelationError::DuplicateTransaction {
+                transaction_id: request.transaction_id.clone(),
+            });
+        }
+
+        self.pending.insert(
+            request.transaction_id.clone(),
+            Pending {
+                request_id,
+                msg_class: request.msg_class.clone(),
+                responder,
+                deadline: now + timeout,
+                acknowledged: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// Matches an accepted inbound envelope against outstanding requests.
+    ///
+    /// Returns `None` if the envelope answers no tracked request, comes from
+    /// an endpoint other than its responder, or is not a response.
+    pub fn on_response(&mut self, response: &VerifiedEnvelope) -> Option<Event> {
+        let pending = self.pending.get_mut(&response.transaction_id)?;
+        if pending.responder != response.sender_id || pending.msg_class != response.msg_class {
+            return None;
+        }
+
+        let outcome = match response.payload() {
+            Payload::Command(Command {
+                payload: Some(CommandPayload::Ack(ack)),
+            })
+            | Payload::Config(Config {
+                payload: Some(ConfigPayload::Ack(ack)),
+            })
+            | Payload::Engineering(Engineering {
+                payload: Some(EngineeringPayload::Ack(ack)),
+            }) => match ack.status {
+                AckStatus::Received => {
+                    pending.acknowledged = true;
+                    return Some(Event::Acknowledged {
+                        transaction_id: response.transaction_id.clone(),
+                        request_id: pending.request_id.clone(),
+                    });
+                }
+                _ => Outcome::Rejected {
+                    reason: ack.reason,
+                    detail: ack.detail.clone(),
+                },
+            },
+            Payload::Command(Command {
+                payload: Some(CommandPayload::Result(r)),
+            })
+            | Payload::Config(Config {
+                payload: Some(ConfigPayload::Result(r)),
+            }) => Outcome::Completed(r.clone()),
+            Payload::Config(Config {
+                payload: Some(ConfigPayload::Snapshot(s)),
+            }) => Outcome::Snapshot(s.clone()),
+            Payload::Engineering(Engineering {
+                payload: Some(EngineeringPayload::Result(r)),
+            }) => Outcome::Engineering(r.clone()),
+            _ => return None,
+        };
+
+        let pending = self.pending.remove(&response.transaction_id)?;
+        Some(Event::Resolved(Resolution {
+            transaction_id: response.transaction_id.clone(),
+            request_id: pending.request_id,
+            outcome,
+        }))
+    }
+
+    /// Resolves every request whose deadline is at or before `now` as timed out.
+    ///
+    /// Resolutions are returned ordered by deadline.
+    pub fn expire(&mut self, now: Instant) -> Vec<Resolution> {
+        let mut expired: Vec<(TransactionId, Pending)> = Vec::new();
+        self.pending.retain(|txn, p| {
+            if p.deadline <= now {
+                expired.push((txn.clone(), p.clone()));
+                false
+            } else {
+                true
+            }
+        });
+        expired.sort_by(|a, b| a.1.deadline.cmp(&b.1.deadline).then(a.0.cmp(&b.0)));
+
+        expired
+            .into_iter()
+            .map(|(transaction_id, p)| Resolution {
+                transaction_id,
+                request_id: p.request_id,
+                outcome: Outcome::TimedOut {
+                    acknowledged: p.acknowledged,
+                },
+            })
+            .collect()
+    }
+
+    /// The earliest outstanding deadline, for scheduling the next [`Self::expire`].
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|p| p.deadline).min()
+    }
+}
+
+/// Request identifier carried inside a request payload, or `None` for
+/// envelopes that are not requests. `ConfigQuery` has no identifier.
+fn request_id_of(env: &Envelope) -> Option<String> {
+    match env.payload.as_ref()? {
+        Payload::Command(Command {
+            payload: Some(CommandPayload::Request(r)),
+        }) => Some(r.request_id.clone()),
+        Payload::Config(Config {
+            payload: Some(ConfigPayload::Update(u)),
+        }) => Some(u.update_id.clone()),
+        Payload::Config(Config {
+            payload: Some(ConfigPayload::Query(_)),
+        }) => Some(String::new()),
+        Payload::Engineering(Engineering {
+            payload: Some(EngineeringPayload::Request(r)),
+        }) => Some(r.request_id.clone()),
+        _ => None,
+    }
+}
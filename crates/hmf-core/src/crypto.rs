@@ -1,2 +1,4 @@
 pub mod ed25519;
 pub mod hash;
+pub mod keyfile;
+pub mod signer;
@@ -1,12 +1,111 @@
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+use crate::error::VerifyError;
+
+/// Length of an Ed25519 signature in bytes.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// The Ed25519 group order `L`, little-endian.
+const GROUP_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
 
 pub fn sign(signing_key: &SigningKey, msg: &[u8]) -> [u8; 64] {
     signing_key.sign(msg).to_bytes()
 }
 
-pub fn verify(verifying_key: &VerifyingKey, msg: &[u8], sig: &[u8]) -> bool {
-    let Ok(sig) = Signature::from_slice(sig) else {
-        return false;
-    };
-    verifying_key.verify(msg, &sig).is_ok()
+/// Verifies `sig` over `msg` with strict semantics.
+///
+/// Signatures are unique: a third party cannot derive a second valid
+/// signature from a published one, so audit can deduplicate on signature
+/// bytes.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::BadSignatureLen`] for a signature that is not 64
+/// bytes, [`VerifyError::MalformedSignature`] when `S` is not a reduced scalar
+/// (RFC 8032 §5.1.7) or `R` is not a torsion-free point of large order,
+/// [`VerifyError::WeakKey`] for a public key that has small order or a
+/// small-order component and [`VerifyError::BadSignature`] when the
+/// signature does not verify.
+pub fn verify(verifying_key: &VerifyingKey, msg: &[u8], sig: &[u8]) -> Result<(), VerifyError> {
+    let sig = parse_signature(sig, verifying_key)?;
+    verifying_key
+        .verify_strict(msg, &sig)
+        .map_err(|_| VerifyError::BadSignature)
+}
+
+/// Verifies `(msg, sig, key)` triples, returning one result per item in order.
+///
+/// Well-formed signatures are checked together with a single batch
+/// verification. If the batch fails, each of them is verified on its own so
+/// only the bad items are reported.
+///
+/// The encoding checks of [`verify`] apply to every item. Requiring `R` and
+/// the key to be torsion-free makes the randomized batch equation accept
+/// exactly the signatures [`verify`] accepts, independent of its coefficients.
+pub fn verify_batch(items: &[(&[u8], &[u8], &VerifyingKey)]) -> Vec<Result<(), VerifyError>> {
+    let mut results = Vec::with_capacity(items.len());
+    let mut messages = Vec::with_capacity(items.len());
+    let mut signatures = Vec::with_capacity(items.len());
+    let mut keys = Vec::with_capacity(items.len());
+    for (msg, sig, key) in items {
+        results.push(parse_signature(sig, key).map(|sig| {
+            messages.push(*msg);
+            signatures.push(sig);
+            keys.push(**key);
+        }));
+    }
+
+    if !messages.is_empty() && ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_err() {
+        for (result, (msg, sig, key)) in results.iter_mut().zip(items) {
+            if result.is_ok() {
+                *result = verify(key, msg, sig);
+            }
+        }
+    }
+    results
+}
+
+fn parse_signature(sig: &[u8], key: &VerifyingKey) -> Result<Signature, VerifyError> {
+    let sig: &[u8; SIGNATURE_LEN] = sig.try_into().map_err(|_| VerifyError::BadSignatureLen {
+        expected: SIGNATURE_LEN,
+        got: sig.len(),
+    })?;
+    if !is_reduced_scalar(&sig[32..]) {
+        return Err(VerifyError::MalformedSignature {
+            reason: "S is not a reduced scalar",
+        });
+    }
+    let r = CompressedEdwardsY::from_slice(&sig[..32]).expect("32-byte R");
+    match r.decompress() {
+        None => Err(VerifyError::MalformedSignature {
+            reason: "R is not a curve point",
+        }),
+        Some(point) if point.is_small_order() => Err(VerifyError::MalformedSignature {
+            reason: "R has small order",
+        }),
+        Some(point) if !point.is_torsion_free() => Err(VerifyError::MalformedSignature {
+            reason: "R has a small-order component",
+        }),
+        Some(_) if !is_torsion_free_key(key) => Err(VerifyError::WeakKey),
+        Some(_) => Ok(Signature::from_bytes(sig)),
+    }
+}
+
+/// `true` if `key` has large order and no small-order component.
+pub(crate) fn is_torsion_free_key(key: &VerifyingKey) -> bool {
+    !key.is_weak() && key.to_edwards().is_torsion_free()
+}
+
+/// `true` if the little-endian scalar `s` is less than the group order.
+fn is_reduced_scalar(s: &[u8]) -> bool {
+    for (b, l) in s.iter().rev().zip(GROUP_ORDER.iter().rev()) {
+        if b != l {
+            return b < l;
+        }
+    }
+    false
 }
@@ -0,0 +1,391 @@
+//! Encrypted on-disk storage for endpoint signing keys (key-management.md,
+//! "Endpoint signing key").
+//!
+//! A key file holds one Ed25519 seed and its `key_id`, sealed with
+//! ChaCha20-Poly1305 under a wrapping key that is either derived from a
+//! passphrase with Argon2id or supplied directly as a local key-encryption
+//! key (KEK). Layout, integers big-endian:
+//!
+//! | field | size |
+//! |---|---|
+//! | magic `HMFKEY` | 6 |
+//! | version (1) | 1 |
+//! | kdf: 0 = KEK, 1 = Argon2id | 1 |
+//! | Argon2id only: `m_cost` KiB, `t_cost`, `p_cost` (u32 each), salt | 28 |
+//! | `key_id` length (u16), `key_id` (UTF-8) | 2 + n |
+//! | nonce | 12 |
+//! | sealed seed (seed + tag) | 48 |
+//!
+//! Everything before the sealed seed is authenticated as associated data.
+//! Decrypted seeds and derived wrapping keys only live in zeroizing buffers.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::crypto::signer::InMemorySigner;
+use crate::error::KeyFileError;
+use crate::hex;
+use crate::ids::DeviceId;
+
+pub const MAGIC: &[u8; 6] = b"HMFKEY";
+pub const VERSION: u8 = 1;
+
+const KDF_KEK: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+
+const SEED_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Environment variable holding the key file passphrase.
+pub const PASSPHRASE_ENV: &str = "HMF_KEY_PASSPHRASE";
+
+/// Environment variable holding a hex-encoded 32-byte KEK.
+pub const KEK_ENV: &str = "HMF_KEY_KEK";
+
+/// Argon2id cost parameters used when sealing under a passphrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    m_cost_kib: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl KdfParams {
+    /// Largest memory cost accepted when opening a file, so a crafted file
+    /// cannot make the loader allocate without bound.
+    pub const MAX_M_COST_KIB: u32 = 1024 * 1024;
+    pub const MAX_T_COST: u32 = 64;
+    pub const MAX_P_COST: u32 = 16;
+
+    pub fn new(m_cost_kib: u32, t_cost: u32, p_cost: u32) -> Self {
+        Self {
+            m_cost_kib,
+            t_cost,
+            p_cost,
+        }
+    }
+
+    pub fn m_cost_kib(&self) -> u32 {
+        self.m_cost_kib
+    }
+
+    pub fn t_cost(&self) -> u32 {
+        self.t_cost
+    }
+
+    pub fn p_cost(&self) -> u32 {
+        self.p_cost
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>, KeyFileError> {
+        if self.m_cost_kib > Self::MAX_M_COST_KIB
+            || self.t_cost > Self::MAX_T_COST
+            || self.p_cost > Self::MAX_P_COST
+        {
+            return Err(KeyFileError::KdfParams(
+                "cost exceeds the supported maximum".to_string(),
+            ));
+        }
+        let params = Params::new(self.m_cost_kib, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| KeyFileError::KdfParams(e.to_string()))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+impl Default for KdfParams {
+    /// 19 MiB, two passes, one lane.
+    fn default() -> Self {
+        Self::new(19 * 1024, 2, 1)
+    }
+}
+
+/// The secret a key file is sealed under.
+pub enum KeyProtection {
+    Passphrase {
+        passphrase: Zeroizing<Vec<u8>>,
+        params: KdfParams,
+    },
+    Kek(Zeroizing<[u8; 32]>),
+}
+
+impl KeyProtection {
+    pub fn passphrase(passphrase: impl Into<Vec<u8>>) -> Self {
+        Self::Passphrase {
+            passphrase: Zeroizing::new(passphrase.into()),
+            params: KdfParams::default(),
+        }
+    }
+
+    pub fn kek(kek: [u8; 32]) -> Self {
+        Self::Kek(Zeroizing::new(kek))
+    }
+
+    /// Argon2id parameters for [`KeyFile::seal`]; ignored for a KEK and when
+    /// opening, where the parameters stored in the file apply.
+    pub fn with_kdf_params(mut self, kdf_params: KdfParams) -> Self {
+        if let Self::Passphrase { params, .. } = &mut self {
+            *params = kdf_params;
+        }
+        self
+    }
+
+    /// Reads the protection from [`PASSPHRASE_ENV`] or, failing that,
+    /// [`KEK_ENV`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyFileError::NoProtection`] if neither is set and
+    /// [`KeyFileError::BadKek`] if the KEK is not 64 hex digits.
+    pub fn from_env() -> Result<Self, KeyFileError> {
+        if let Some(passphrase) = std::env::var_os(PASSPHRASE_ENV) {
+            return Ok(Self::passphrase(passphrase.into_encoded_bytes()));
+        }
+        let digits =
+            Zeroizing::new(std::env::var(KEK_ENV).map_err(|_| KeyFileError::NoProtection)?);
+        let mut kek = Zeroizing::new([0u8; 32]);
+        hex::decode_into(&digits, kek.as_mut()).map_err(|_| KeyFileError::BadKek)?;
+        Ok(Self::Kek(kek))
+    }
+
+    fn kdf(&self) -> u8 {
+        match self {
+            Self::Passphrase { .. } => KDF_ARGON2ID,
+            Self::Kek(_) => KDF_KEK,
+        }
+    }
+}
+
+/// The suggested `key_id` for version `version` of `sender_id`'s key.
+pub fn suggested_key_id(sender_id: &DeviceId, version: u32) -> String {
+    format!("{sender_id}:ed25519:v{version}")
+}
+
+/// An endpoint signing key with its `key_id`, as stored in a key file.
+pub struct KeyFile {
+    key_id: String,
+    signing_key: SigningKey,
+}
+
+impl KeyFile {
+    pub fn new(key_id: &str, signing_key: SigningKey) -> Self {
+        Self {
+            key_id: key_id.to_string(),
+            signing_key,
+        }
+    }
+
+    /// Generates a fresh key from the operating system RNG.
+    pub fn generate(key_id: &str) -> Self {
+        Self::new(key_id, SigningKey::generate(&mut OsRng))
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn into_signer(self) -> InMemorySigner {
+        InMemorySigner::new(&self.key_id, self.signing_key)
+    }
+
+    /// Encodes and encrypts the key under `protection`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyFileError::KdfParams`] for unusable Argon2id parameters
+    /// and [`KeyFileError::Malformed`] for a `key_id` longer than 65535 bytes.
+    pub fn seal(&self, protection: &KeyProtection) -> Result<Vec<u8>, KeyFileError> {
+        let key_id_len = u16::try_from(self.key_id.len()).map_err(|_| KeyFileError::Malformed {
+            reason: "key_id too long",
+        })?;
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(protection.kdf());
+        let mut salt = [0u8; SALT_LEN];
+        if let KeyProtection::Passphrase { params, .. } = protection {
+            OsRng.fill_bytes(&mut salt);
+            out.extend_from_slice(&params.m_cost_kib.to_be_bytes());
+            out.extend_from_slice(&params.t_cost.to_be_bytes());
+            out.extend_from_slice(&params.p_cost.to_be_bytes());
+            out.extend_from_slice(&salt);
+        }
+        out.extend_from_slice(&key_id_len.to_be_bytes());
+        out.extend_from_slice(self.key_id.as_bytes());
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        out.extend_from_slice(&nonce);
+
+        let wrapping_key = wrapping_key(protection, &salt, None)?;
+        let sealed = ChaCha20Poly1305::new(Key::from_slice(wrapping_key.as_ref()))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: self.signing_key.as_bytes(),
+                    aad: &out,
+                },
+            )
+            .expect("sealing 32 bytes cannot fail");
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Decrypts a key file produced by [`KeyFile::seal`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyFileError::Decrypt`] for the wrong passphrase or KEK and
+    /// for any modification of the file, and a format error for input that
+    /// is not a supported key file.
+    pub fn open(bytes: &[u8], protection: &KeyProtection) -> Result<Self, KeyFileError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(MAGIC.len())? != MAGIC {
+            return Err(KeyFileError::BadMagic);
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(KeyFileError::UnsupportedVersion { got: version });
+        }
+        let kdf = r.u8()?;
+        let mut salt = [0u8; SALT_LEN];
+        let params = match kdf {
+            KDF_KEK => None,
+            KDF_ARGON2ID => {
+                let params = KdfParams::new(r.u32()?, r.u32()?, r.u32()?);
+                salt.copy_from_slice(r.take(SALT_LEN)?);
+                Some(params)
+            }
+            got => return Err(KeyFileError::UnsupportedKdf { got }),
+        };
+        if kdf != protection.kdf() {
+            return Err(KeyFileError::ProtectionMismatch);
+        }
+        let key_id_len = u16::from_be_bytes(r.take(2)?.try_into().expect("2 bytes")) as usize;
+        let key_id = std::str::from_utf8(r.take(key_id_len)?)
+            .map_err(|_| KeyFileError::Malformed {
+                reason: "key_id is not UTF-8",
+            })?
+            .to_string();
+        let nonce = r.take(NONCE_LEN)?;
+        let aad = &bytes[..r.pos];
+        let sealed = r.take(SEED_LEN + TAG_LEN)?;
+        if r.pos != bytes.len() {
+            return Err(KeyFileError::Malformed {
+                reason: "trailing bytes",
+            });
+        }
+
+        let wrapping_key = wrapping_key(protection, &salt, params)?;
+        let seed = Zeroizing::new(
+            ChaCha20Poly1305::new(Key::from_slice(wrapping_key.as_ref()))
+                .decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad })
+                .map_err(|_| KeyFileError::Decrypt)?,
+        );
+        let seed: &[u8; SEED_LEN] = seed.as_slice().try_into().expect("32-byte seed");
+        Ok(Self::new(&key_id, SigningKey::from_bytes(seed)))
+    }
+
+    /// Seals the key to a new file at `path`, readable only by its owner.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `path` already exists; key files are never overwritten.
+    pub fn write(
+        &self,
+        path: impl AsRef<Path>,
+        protection: &KeyProtection,
+    ) -> Result<(), KeyFileError> {
+        let sealed = self.seal(protection)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        file.write_all(&sealed)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn read(path: impl AsRef<Path>, protection: &KeyProtection) -> Result<Self, KeyFileError> {
+        Self::open(&fs::read(path)?, protection)
+    }
+}
+
+impl fmt::Debug for KeyFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyFile")
+            .field("key_id", &self.key_id)
+            .field("verifying_key", &self.verifying_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The ChaCha20-Poly1305 key for `protection`. `params` are the stored
+/// Argon2id parameters when opening.
+fn wrapping_key(
+    protection: &KeyProtection,
+    salt: &[u8; SALT_LEN],
+    params: Option<KdfParams>,
+) -> Result<Zeroizing<[u8; 32]>, KeyFileError> {
+    match protection {
+        KeyProtection::Kek(kek) => Ok(kek.clone()),
+        KeyProtection::Passphrase {
+            passphrase,
+            params: seal_params,
+        } => {
+            let mut key = Zeroizing::new([0u8; 32]);
+            params
+                .unwrap_or(*seal_params)
+                .argon2()?
+                .hash_password_into(passphrase, salt, key.as_mut())
+                .map_err(|e| KeyFileError::KdfParams(e.to_string()))?;
+            Ok(key)
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], KeyFileError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(KeyFileError::Malformed {
+                reason: "truncated",
+            })?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, KeyFileError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, KeyFileError> {
+        Ok(u32::from_be_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+}
@@ -0,0 +1,54 @@
+//! Envelope signers (key-management.md, "Endpoint signing key").
+//!
+//! A [`Signer`] signs envelope signing bytes without exposing its key, so a
+//! secure element or a separate signing process can hold the private key.
+
+#[cfg(unix)]
+pub mod socket;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::crypto::ed25519;
+use crate::error::SignerError;
+
+/// Signs envelope signing bytes with an Ed25519 key it does not expose.
+pub trait Signer: Send {
+    /// The `key_id` envelopes signed by this signer carry.
+    fn key_id(&self) -> &str;
+
+    /// The public half of the signing key.
+    fn verifying_key(&self) -> VerifyingKey;
+
+    /// Signs `msg`, normally the output of
+    /// [`crate::envelope::signing_bytes::envelope_signing_bytes`].
+    fn sign(&self, msg: &[u8]) -> Result<[u8; ed25519::SIGNATURE_LEN], SignerError>;
+}
+
+/// A signer holding its key in process memory.
+pub struct InMemorySigner {
+    key_id: String,
+    signing_key: SigningKey,
+}
+
+impl InMemorySigner {
+    pub fn new(key_id: &str, signing_key: SigningKey) -> Self {
+        Self {
+            key_id: key_id.to_string(),
+            signing_key,
+        }
+    }
+}
+
+impl Signer for InMemorySigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<[u8; ed25519::SIGNATURE_LEN], SignerError> {
+        Ok(ed25519::sign(&self.signing_key, msg))
+    }
+}
@@ -0,0 +1,203 @@
+//! Signer reachable over a local Unix socket.
+//!
+//! The signing process owns the key; the application only sends signing
+//! bytes and receives signatures. Frames are:
+//!
+//! - request: `op` (u8), body length (u32, big-endian), body
+//! - response: `status` (u8, 0 = ok), body length (u32, big-endian), body
+//!
+//! | `op` | request body | ok response body |
+//! |---|---|---|
+//! | [`OP_PUBLIC_KEY`] | empty | 32-byte public key, then `key_id` as UTF-8 |
+//! | [`OP_SIGN`] | signing bytes | 64-byte signature |
+//!
+//! A non-zero status carries a UTF-8 error message. Bodies are at most
+//! [`MAX_BODY_LEN`] bytes.
+//!
+//! The socket carries no authentication: any process that can connect to it
+//! obtains signatures. The signing process must create it where only the
+//! application's user can reach it, for example in a directory of mode
+//! `0700`, or restrict the socket itself to mode `0600`.
+
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::crypto::ed25519::{self, SIGNATURE_LEN};
+use crate::crypto::signer::Signer;
+use crate::envelope::signing_bytes::has_domain_tag;
+use crate::error::SignerError;
+
+pub const OP_PUBLIC_KEY: u8 = 1;
+pub const OP_SIGN: u8 = 2;
+
+pub const STATUS_OK: u8 = 0;
+pub const STATUS_ERROR: u8 = 1;
+
+/// Largest request or response body accepted by either side.
+pub const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// Default per-request read and write timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A [`Signer`] backed by a signing process listening on a Unix socket.
+///
+/// Each signature is checked against the public key reported at connect
+/// time before it is used.
+///
+/// Every call performs blocking socket I/O for up to the timeout. On an async
+/// executor, sign through `spawn_blocking` or a dedicated thread rather than
+/// on a runtime worker.
+#[derive(Debug)]
+pub struct SocketSigner {
+    path: PathBuf,
+    key_id: String,
+    verifying_key: VerifyingKey,
+    timeout: Duration,
+}
+
+impl SocketSigner {
+    /// Queries the signer at `path` for its `key_id` and public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SignerError`] if the signer is unreachable, refuses the
+    /// request or answers with a malformed public key.
+    pub fn connect(path: impl AsRef<Path>) -> Result<Self, SignerError> {
+        Self::connect_with_timeout(path, DEFAULT_TIMEOUT)
+    }
+
+    pub fn connect_with_timeout(
+        path: impl AsRef<Path>,
+        timeout: Duration,
+    ) -> Result<Self, SignerError> {
+        let path = path.as_ref().to_path_buf();
+        let body = request(&path, timeout, OP_PUBLIC_KEY, &[])?;
+        if body.len() < 32 {
+            return Err(SignerError::Protocol(
+                "public key response too short".into(),
+            ));
+        }
+        let (key, key_id) = body.split_at(32);
+        let verifying_key = VerifyingKey::from_bytes(key.try_into().expect("32 bytes"))
+            .map_err(|_| SignerError::Protocol("invalid public key".into()))?;
+        let key_id = String::from_utf8(key_id.to_vec())
+            .map_err(|_| SignerError::Protocol("key_id is not UTF-8".into()))?;
+
+        Ok(Self {
+            path,
+            key_id,
+            verifying_key,
+            timeout,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Signer for SocketSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<[u8; SIGNATURE_LEN], SignerError> {
+        let body = request(&self.path, self.timeout, OP_SIGN, msg)?;
+        let sig: [u8; SIGNATURE_LEN] = body
+            .try_into()
+            .map_err(|_| SignerError::Protocol("signature is not 64 bytes".into()))?;
+        ed25519::verify(&self.verifying_key, msg, &sig).map_err(|_| SignerError::BadSignature)?;
+        Ok(sig)
+    }
+}
+
+/// Answers requests on `stream` with `signer` until the peer disconnects.
+///
+/// This is the serving half of the protocol, for signing processes built on
+/// this crate and for tests. Only envelope signing bytes are signed: a
+/// request that does not start with the `HMFv1:envelope-signature` domain tag
+/// is refused, so the key cannot be used to sign other data. See the module
+/// docs for the socket permissions the caller must set up.
+///
+/// # Errors
+///
+/// Returns [`SignerError::Io`] on transport failure and
+/// [`SignerError::Protocol`] for an oversized or unknown request.
+pub fn serve(stream: &mut UnixStream, signer: &dyn Signer) -> Result<(), SignerError> {
+    loop {
+        let (op, body) = match read_frame(stream) {
+            Ok(frame) => frame,
+            Err(SignerError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        match op {
+            OP_PUBLIC_KEY => {
+                let mut out = signer.verifying_key().to_bytes().to_vec();
+                out.extend_from_slice(signer.key_id().as_bytes());
+                write_frame(stream, STATUS_OK, &out)?;
+            }
+            OP_SIGN if !has_domain_tag(&body) => {
+                write_frame(stream, STATUS_ERROR, b"not envelope signing bytes")?;
+            }
+            OP_SIGN => match signer.sign(&body) {
+                Ok(sig) => write_frame(stream, STATUS_OK, &sig)?,
+                Err(e) => write_frame(stream, STATUS_ERROR, e.to_string().as_bytes())?,
+            },
+            other => {
+                write_frame(stream, STATUS_ERROR, b"unknown op")?;
+                return Err(SignerError::Protocol(format!("unknown op {other}")));
+            }
+        }
+    }
+}
+
+fn request(path: &Path, timeout: Duration, op: u8, body: &[u8]) -> Result<Vec<u8>, SignerError> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    write_frame(&mut stream, op, body)?;
+    match read_frame(&mut stream)? {
+        (STATUS_OK, body) => Ok(body),
+        (_, detail) => Err(SignerError::Refused(
+            String::from_utf8_lossy(&detail).into_owned(),
+        )),
+    }
+}
+
+fn write_frame(stream: &mut UnixStream, tag: u8, body: &[u8]) -> Result<(), SignerError> {
+    if body.len() > MAX_BODY_LEN {
+        return Err(SignerError::Protocol(format!(
+            "body of {} bytes exceeds {MAX_BODY_LEN}",
+            body.len()
+        )));
+    }
+    let mut frame = Vec::with_capacity(5 + body.len());
+    frame.push(tag);
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(body);
+    stream.write_all(&frame)?;
+    Ok(())
+}
+
+fn read_frame(stream: &mut UnixStream) -> Result<(u8, Vec<u8>), SignerError> {
+    let mut header = [0u8; 5];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_be_bytes(header[1..].try_into().expect("4 bytes")) as usize;
+    if len > MAX_BODY_LEN {
+        return Err(SignerError::Protocol(format!(
+            "body of {len} bytes exceeds {MAX_BODY_LEN}"
+        )));
+    }
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body)?;
+    Ok((header[0], body))
+}
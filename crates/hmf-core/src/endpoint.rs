@@ -0,0 +1,207 @@
+use ed25519_dalek::SigningKey;
+
+use crate::crypto::signer::{InMemorySigner, Signer};
+use crate::envelope::sign::sign_envelope;
+use crate::envelope::{
+    DeliveryProfile, EXPECTED_PROTO_VER, Envelope, Payload, SigAlg, VerifiedEnvelope,
+};
+use crate::error::EndpointError;
+use crate::ids::{
+    DeviceId, InstanceId, TransactionId, new_idempotency_key, new_sender_instance,
+    new_transaction_id,
+};
+
+/// Default `ttl_ms` for envelopes sent through [`Routing`].
+pub const DEFAULT_TTL_MS: u32 = 5_000;
+
+/// Routing hints and delivery parameters for one outbound envelope.
+#[derive(Clone, Debug)]
+pub struct Routing {
+    topic: String,
+    target: String,
+    scope: String,
+    delivery_profile: DeliveryProfile,
+    ttl_ms: u32,
+    transaction_id: Option<TransactionId>,
+    auth_context: Vec<u8>,
+}
+
+impl Routing {
+    pub fn new(topic: &str, target: &str, scope: &str) -> Self {
+        Self {
+            topic: topic.to_string(),
+            target: target.to_string(),
+            scope: scope.to_string(),
+            delivery_profile: DeliveryProfile::BestEffort,
+            ttl_ms: DEFAULT_TTL_MS,
+            transaction_id: None,
+            auth_context: Vec::new(),
+        }
+    }
+
+    /// Routing for a response to `request`: same topic, scope, delivery
+    /// profile and transaction, targeted back at the requesting sender.
+    ///
+    /// Only verified requests are answered; envelopes rejected before
+    /// signature verification never get a response.
+    pub fn reply_to(request: &VerifiedEnvelope) -> Self {
+        Self {
+            topic: request.topic.clone(),
+            target: request.sender_id.as_str().to_string(),
+            scope: request.scope.clone(),
+            delivery_profile: request.delivery_profile.clone(),
+            ttl_ms: DEFAULT_TTL_MS,
+            transaction_id: Some(request.transaction_id.clone()),
+            auth_context: Vec::new(),
+        }
+    }
+
+    pub fn with_delivery_profile(mut self, profile: DeliveryProfile) -> Self {
+        self.delivery_profile = profile;
+        self
+    }
+
+    pub fn with_ttl_ms(mut self, ttl_ms: u32) -> Self {
+        self.ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Sends within an existing transaction instead of starting a new one.
+    pub fn with_transaction_id(mut self, transaction_id: TransactionId) -> Self {
+        self.transaction_id = Some(transaction_id);
+        self
+    }
+
+    pub fn with_auth_context(mut self, auth_context: Vec<u8>) -> Self {
+        self.auth_context = auth_context;
+        self
+    }
+}
+
+/// Persistable outbound stream state of an endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutboundState {
+    pub sender_instance: InstanceId,
+    /// The last counter value emitted on `sender_instance`.
+    pub counter: u64,
+}
+
+/// A cryptographic identity within a node (runtime-and-dispatch.md).
+///
+/// Owns the endpoint identity, its outbound counter and its [`Signer`].
+/// Every envelope leaves through [`EndpointContext::send`], which assigns the
+/// next counter value on the current `sender_instance` and signs the result,
+/// so a counter value can never be emitted twice on the same stream.
+pub struct EndpointContext {
+    sender_id: DeviceId,
+    sender_instance: InstanceId,
+    signer: Box<dyn Signer>,
+    counter: u64,
+}
+
+impl EndpointContext {
+    /// Starts a new outbound stream with a fresh `sender_instance`.
+    pub fn new(sender_id: DeviceId, key_id: &str, signing_key: SigningKey) -> Self {
+        Self::from_signer(sender_id, InMemorySigner::new(key_id, signing_key))
+    }
+
+    /// [`EndpointContext::new`] with a signer that keeps the key elsewhere,
+    /// such as a secure element or a signing process.
+    pub fn from_signer(sender_id: DeviceId, signer: impl Signer + 'static) -> Self {
+        Self {
+            sender_id,
+            sender_instance: new_sender_instance(),
+            signer: Box::new(signer),
+            counter: 0,
+        }
+    }
+
+    /// Continues a previously persisted outbound stream.
+    ///
+    /// The caller MUST persist [`EndpointContext::state`] after every send it
+    /// relies on; resuming from a stale state reuses counters and is rejected
+    /// by receivers as replay. When in doubt, use [`EndpointContext::new`].
+    pub fn resume(
+        sender_id: DeviceId,
+        key_id: &str,
+        signing_key: SigningKey,
+        state: OutboundState,
+    ) -> Self {
+        Self::resume_from_signer(sender_id, InMemorySigner::new(key_id, signing_key), state)
+    }
+
+    /// [`EndpointContext::resume`] with an external signer.
+    pub fn resume_from_signer(
+        sender_id: DeviceId,
+        signer: impl Signer + 'static,
+        state: OutboundState,
+    ) -> Self {
+        Self {
+            sender_id,
+            sender_instance: state.sender_instance,
+            signer: Box::new(signer),
+            counter: state.counter,
+        }
+    }
+
+    pub fn sender_id(&self) -> &DeviceId {
+        &self.sender_id
+    }
+
+    pub fn sender_instance(&self) -> &InstanceId {
+        &self.sender_instance
+    }
+
+    pub fn key_id(&self) -> &str {
+        self.signer.key_id()
+    }
+
+    pub fn state(&self) -> OutboundState {
+        OutboundState {
+            sender_instance: self.sender_instance.clone(),
+            counter: self.counter,
+        }
+    }
+
+    /// Builds, numbers and signs an envelope carrying `payload`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EndpointError::CounterExhausted`] once the counter space of
+    /// the current `sender_instance` is used up, or [`EndpointError::Sign`]
+    /// if the signer fails. The counter value is consumed either way.
+    pub fn send(&mut self, payload: Payload, routing: Routing) -> Result<Envelope, EndpointError> {
+        let counter =
+            self.counter
+                .checked_add(1)
+                .ok_or_else(|| EndpointError::CounterExhausted {
+                    sender_instance: self.sender_instance.clone(),
+                })?;
+        self.counter = counter;
+
+        let mut env = Envelope {
+            proto_ver: EXPECTED_PROTO_VER,
+            msg_class: payload.msg_class(),
+            sender_id: self.sender_id.clone(),
+            sender_instance: self.sender_instance.clone(),
+            counter,
+            ttl_ms: routing.ttl_ms,
+            transaction_id: routing.transaction_id.unwrap_or_else(new_transaction_id),
+            idempotency_key: new_idempotency_key(),
+            delivery_profile: routing.delivery_profile,
+
+            topic: routing.topic,
+            target: routing.target,
+            scope: routing.scope,
+
+            payload: Some(payload),
+
+            sig_alg: SigAlg::Unspecified,
+            signature: Vec::new(),
+            key_id: String::new(),
+            auth_context: routing.auth_context,
+        };
+        sign_envelope(&mut env, self.signer.as_ref())?;
+        Ok(env)
+    }
+}
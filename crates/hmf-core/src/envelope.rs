@@ -1,8 +1,11 @@
+pub mod limits;
 pub mod sign;
 pub mod signing_bytes;
 mod types;
+mod verified;
 
 pub use types::*;
+pub use verified::VerifiedEnvelope;
 
 use crate::error::ValidateError;
 use crate::ids::{DeviceId, IdempotencyKey, InstanceId, TransactionId};
@@ -239,12 +242,7 @@ impl<R, I, C, D> EnvelopeBuilder<R, I, C, D> {
     }
 
     pub fn with_payload(mut self, payload: Payload) -> Self {
-        self.msg_class = match payload {
-            Payload::Telemetry(_) => MsgClass::Telemetry,
-            Payload::Command(_) => MsgClass::Command,
-            Payload::Config(_) => MsgClass::Config,
-            Payload::Engineering(_) => MsgClass::Engineering,
-        };
+        self.msg_class = payload.msg_class();
         self.payload = Some(payload);
         self
     }
@@ -307,6 +305,7 @@ impl EnvelopeBuilder<Set, Set, Set, Set> {
 /// | `key_id` is non-empty | REQ-ENVELOPE-005 | INV-ENVELOPE-002 |
 /// | payload is present | — | — |
 /// | `msg_class` matches payload type | REQ-ENVELOPE-002 | INV-ENVELOPE-002 |
+/// | field sizes within the default [`limits::DecodeLimits`] | envelope.md, "Size limits" | — |
 ///
 /// # Scope boundary
 ///
@@ -333,6 +332,14 @@ impl EnvelopeBuilder<Set, Set, Set, Set> {
 /// Returns [`ValidateError`] if any structural or security-field check fails.
 /// Validation is fail-closed: the first failing check terminates validation immediately.
 pub fn envelope_validate(env: &Envelope) -> Result<(), ValidateError> {
+    envelope_validate_with(env, &limits::DecodeLimits::default())
+}
+
+/// [`envelope_validate`] with caller-supplied size limits.
+pub fn envelope_validate_with(
+    env: &Envelope,
+    limits: &limits::DecodeLimits,
+) -> Result<(), ValidateError> {
     // REQ-ENVELOPE-001, INV-ENVELOPE-001: proto_ver MUST equal 1 for v1 messages.
     if env.proto_ver != EXPECTED_PROTO_VER {
         return Err(ValidateError::BadProtoVer {
@@ -384,5 +391,7 @@ pub fn envelope_validate(env: &Envelope) -> Result<(), ValidateError> {
         });
     }
 
+    limits::envelope_check_limits(env, limits)?;
+
     Ok(())
 }
@@ -0,0 +1,281 @@
+use crate::envelope::*;
+use crate::error::LimitError;
+
+pub const DEFAULT_MAX_AUTH_CONTEXT: usize = 4 * 1024;
+pub const DEFAULT_MAX_STRING_LEN: usize = 4 * 1024;
+pub const DEFAULT_MAX_MAP_ENTRIES: usize = 256;
+pub const DEFAULT_MAX_REPEATED: usize = 1024;
+pub const DEFAULT_MAX_TELEMETRY_BLOB: usize = 64 * 1024;
+pub const DEFAULT_MAX_COMMAND_BLOB: usize = 64 * 1024;
+/// Fits one engineering transfer chunk plus headroom.
+pub const DEFAULT_MAX_ENGINEERING_BLOB: usize = 512 * 1024;
+
+/// Per-field size limits (envelope.md, "Size limits").
+///
+/// Applied while decoding and again during structural validation, so an
+/// oversized envelope is refused before signature verification or any
+/// handler sees it. The record length cap bounds the envelope as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeLimits {
+    max_auth_context: usize,
+    max_string_len: usize,
+    max_map_entries: usize,
+    max_repeated: usize,
+    max_telemetry_blob: usize,
+    max_command_blob: usize,
+    max_engineering_blob: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_auth_context: DEFAULT_MAX_AUTH_CONTEXT,
+            max_string_len: DEFAULT_MAX_STRING_LEN,
+            max_map_entries: DEFAULT_MAX_MAP_ENTRIES,
+            max_repeated: DEFAULT_MAX_REPEATED,
+            max_telemetry_blob: DEFAULT_MAX_TELEMETRY_BLOB,
+            max_command_blob: DEFAULT_MAX_COMMAND_BLOB,
+            max_engineering_blob: DEFAULT_MAX_ENGINEERING_BLOB,
+        }
+    }
+}
+
+impl DecodeLimits {
+    pub fn with_max_auth_context(mut self, max: usize) -> Self {
+        self.max_auth_context = max;
+        self
+    }
+
+    /// Applies to every string, including map keys and values.
+    pub fn with_max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    pub fn with_max_map_entries(mut self, max: usize) -> Self {
+        self.max_map_entries = max;
+        self
+    }
+
+    pub fn with_max_repeated(mut self, max: usize) -> Self {
+        self.max_repeated = max;
+        self
+    }
+
+    /// Blob limit for payloads of `msg_class`. `Config` carries no blobs.
+    pub fn with_max_blob(mut self, msg_class: MsgClass, max: usize) -> Self {
+        match msg_class {
+            MsgClass::Telemetry => self.max_telemetry_blob = max,
+            MsgClass::Command => self.max_command_blob = max,
+            MsgClass::Engineering => self.max_engineering_blob = max,
+            MsgClass::Config | MsgClass::Unspecified | MsgClass::Unknown(_) => {}
+        }
+        self
+    }
+
+    pub fn max_auth_context(&self) -> usize {
+        self.max_auth_context
+    }
+
+    pub fn max_string_len(&self) -> usize {
+        self.max_string_len
+    }
+
+    pub fn max_map_entries(&self) -> usize {
+        self.max_map_entries
+    }
+
+    pub fn max_repeated(&self) -> usize {
+        self.max_repeated
+    }
+
+    pub fn max_blob(&self, msg_class: &MsgClass) -> usize {
+        match msg_class {
+            MsgClass::Telemetry => self.max_telemetry_blob,
+            MsgClass::Command => self.max_command_blob,
+            MsgClass::Engineering => self.max_engineering_blob,
+            MsgClass::Config | MsgClass::Unspecified | MsgClass::Unknown(_) => 0,
+        }
+    }
+
+    pub fn check_auth_context(&self, len: usize) -> Result<(), LimitError> {
+        if len > self.max_auth_context {
+            return Err(LimitError::AuthContextTooLarge {
+                len,
+                max: self.max_auth_context,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_string(&self, field: &'static str, len: usize) -> Result<(), LimitError> {
+        if len > self.max_string_len {
+            return Err(LimitError::StringTooLong {
+                field,
+                len,
+                max: self.max_string_len,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_map(&self, field: &'static str, len: usize) -> Result<(), LimitError> {
+        if len > self.max_map_entries {
+            return Err(LimitError::TooManyEntries {
+                field,
+                len,
+                max: self.max_map_entries,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_repeated(&self, field: &'static str, len: usize) -> Result<(), LimitError> {
+        if len > self.max_repeated {
+            return Err(LimitError::TooManyItems {
+                field,
+                len,
+                max: self.max_repeated,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_blob(&self, msg_class: &MsgClass, len: usize) -> Result<(), LimitError> {
+        let max = self.max_blob(msg_class);
+        if len > max {
+            return Err(LimitError::BlobTooLarge {
+                msg_class: msg_class.clone(),
+                len,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks every field of a decoded envelope against `limits`.
+pub fn envelope_check_limits(env: &Envelope, limits: &DecodeLimits) -> Result<(), LimitError> {
+    limits.check_auth_context(env.auth_context.len())?;
+    for (field, value) in [
+        ("sender_id", env.sender_id.as_str()),
+        ("sender_instance", env.sender_instance.as_str()),
+        ("transaction_id", env.transaction_id.as_str()),
+        ("idempotency_key", env.idempotency_key.as_str()),
+        ("topic", env.topic.as_str()),
+        ("target", env.target.as_str()),
+        ("scope", env.scope.as_str()),
+        ("key_id", env.key_id.as_str()),
+    ] {
+        limits.check_string(field, value.len())?;
+    }
+
+    let Some(payload) = env.payload.as_ref() else {
+        return Ok(());
+    };
+    let class = payload.msg_class();
+    let strings = |fields: &[(&'static str, &str)]| {
+        fields
+            .iter()
+            .try_for_each(|(field, value)| limits.check_string(field, value.len()))
+    };
+    let map = |field: &'static str, map: &std::collections::BTreeMap<String, String>| {
+        limits.check_map(field, map.len())?;
+        map.iter().try_for_each(|(k, v)| {
+            limits.check_string(field, k.len())?;
+            limits.check_string(field, v.len())
+        })
+    };
+
+    match payload {
+        Payload::Telemetry(t) => match t.payload.as_ref() {
+            Some(TelemetryPayload::Birth(b)) => {
+                strings(&[
+                    ("vendor", &b.vendor),
+                    ("model", &b.model),
+                    ("serial", &b.serial),
+                    ("hw_rev", &b.hw_rev),
+                    ("fw_rev", &b.fw_rev),
+                ])?;
+                limits.check_repeated("capabilities", b.capabilities.len())?;
+                b.capabilities
+                    .iter()
+                    .try_for_each(|c| limits.check_string("capabilities", c.len()))
+            }
+            Some(TelemetryPayload::Heartbeat(_)) | None => Ok(()),
+            Some(TelemetryPayload::Death(d)) => strings(&[("detail", &d.detail)]),
+            Some(TelemetryPayload::State(s)) => {
+                limits.check_repeated("items", s.items.len())?;
+                s.items.iter().try_for_each(|item| {
+                    strings(&[("key", &item.key), ("unit", &item.unit)])?;
+                    match &item.value {
+                        Some(StateValue::S(v)) => limits.check_string("s", v.len()),
+                        Some(StateValue::Blob(v)) => limits.check_blob(&class, v.len()),
+                        _ => Ok(()),
+                    }
+                })
+            }
+            Some(TelemetryPayload::Alarm(a)) => strings(&[
+                ("code", &a.code),
+                ("summary", &a.summary),
+                ("detail", &a.detail),
+                ("related_key", &a.related_key),
+                ("recommended_action", &a.recommended_action),
+            ]),
+            Some(TelemetryPayload::Observation(o)) => {
+                if let Some(subject) = &o.subject {
+                    strings(&[("subject_id", &subject.subject_id)])?;
+                }
+                strings(&[("summary", &o.summary), ("detail", &o.detail)])
+            }
+        },
+        Payload::Command(c) => match c.payload.as_ref() {
+            Some(CommandPayload::Request(r)) => {
+                strings(&[
+                    ("request_id", &r.request_id),
+                    ("command", &r.command),
+                    ("target", &r.target),
+                ])?;
+                map("params", &r.params)?;
+                limits.check_blob(&class, r.blob.len())
+            }
+            Some(CommandPayload::Ack(a)) => strings(&[("detail", &a.detail)]),
+            Some(CommandPayload::Result(r)) => strings(&[("detail", &r.detail)]),
+            None => Ok(()),
+        },
+        Payload::Config(c) => match c.payload.as_ref() {
+            Some(ConfigPayload::Query(q)) => {
+                limits.check_repeated("keys", q.keys.len())?;
+                q.keys
+                    .iter()
+                    .try_for_each(|k| limits.check_string("keys", k.len()))
+            }
+            Some(ConfigPayload::Snapshot(s)) => map("params", &s.params),
+            Some(ConfigPayload::Update(u)) => {
+                strings(&[("update_id", &u.update_id)])?;
+                map("params", &u.params)
+            }
+            Some(ConfigPayload::Ack(a)) => strings(&[("detail", &a.detail)]),
+            Some(ConfigPayload::Result(r)) => strings(&[("detail", &r.detail)]),
+            None => Ok(()),
+        },
+        Payload::Engineering(e) => match e.payload.as_ref() {
+            Some(EngineeringPayload::Request(r)) => {
+                strings(&[
+                    ("request_id", &r.request_id),
+                    ("action", &r.action),
+                    ("target", &r.target),
+                ])?;
+                map("params", &r.params)?;
+                limits.check_blob(&class, r.blob.len())
+            }
+            Some(EngineeringPayload::Ack(a)) => strings(&[("detail", &a.detail)]),
+            Some(EngineeringPayload::Result(r)) => {
+                strings(&[("detail", &r.detail)])?;
+                map("outputs", &r.outputs)?;
+                limits.check_blob(&class, r.blob.len())
+            }
+            None => Ok(()),
+        },
+    }
+}
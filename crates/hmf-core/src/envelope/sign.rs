@@ -1,28 +1,107 @@
 use ed25519_dalek::{SigningKey, VerifyingKey};
 
 use crate::crypto::ed25519;
+use crate::crypto::signer::Signer;
 use crate::envelope::signing_bytes::envelope_signing_bytes;
 use crate::envelope::{Envelope, SigAlg};
+use crate::error::{SignerError, ValidateError, VerifyError};
 
-pub fn sign_envelope_ed25519(env: &mut Envelope, key_id: &str, signing_key: &SigningKey) {
+/// Sets the security header of `env` and signs it with `signing_key`.
+///
+/// # Errors
+///
+/// Returns [`ValidateError::MissingPayload`] if `env` has no payload.
+pub fn sign_envelope_ed25519(
+    env: &mut Envelope,
+    key_id: &str,
+    signing_key: &SigningKey,
+) -> Result<(), ValidateError> {
     env.sig_alg = SigAlg::Ed25519;
     env.key_id = key_id.to_string();
 
-    let msg = envelope_signing_bytes(env);
+    let msg = envelope_signing_bytes(env)?;
     let sig = ed25519::sign(signing_key, &msg);
 
     env.signature = sig.to_vec();
+    Ok(())
 }
 
-pub fn verify_envelope_ed25519(env: &Envelope, verifying_key: &VerifyingKey) -> bool {
+/// Sets the security header of `env` and signs it with `signer`.
+///
+/// # Errors
+///
+/// Returns [`SignerError::Validate`] if `env` has no payload, or the error
+/// reported by `signer`.
+pub fn sign_envelope(env: &mut Envelope, signer: &dyn Signer) -> Result<(), SignerError> {
+    env.sig_alg = SigAlg::Ed25519;
+    env.key_id = signer.key_id().to_string();
+
+    let msg = envelope_signing_bytes(env)?;
+    let sig = signer.sign(&msg)?;
+
+    env.signature = sig.to_vec();
+    Ok(())
+}
+
+/// Verifies the Ed25519 signature of `env` with `verifying_key`.
+///
+/// # Errors
+///
+/// Returns the [`VerifyError`] describing why the signature was not accepted.
+pub fn verify_envelope_ed25519(
+    env: &Envelope,
+    verifying_key: &VerifyingKey,
+) -> Result<(), VerifyError> {
+    let msg = signed_message(env)?;
+    ed25519::verify(verifying_key, &msg, &env.signature)
+}
+
+/// [`verify_envelope_ed25519`] over many envelopes, returning one result per
+/// item in order.
+///
+/// Signatures are checked with one batch verification, which is several times
+/// cheaper per envelope than verifying them one by one. A failing batch falls
+/// back to per-envelope verification so only the bad envelopes are reported.
+pub fn verify_envelopes_ed25519(
+    batch: &[(&Envelope, &VerifyingKey)],
+) -> Vec<Result<(), VerifyError>> {
+    let messages = batch
+        .iter()
+        .map(|(env, _)| signed_message(env))
+        .collect::<Vec<_>>();
+    let items = batch
+        .iter()
+        .zip(&messages)
+        .filter_map(|((env, key), msg)| {
+            Some((msg.as_ref().ok()?.as_slice(), &env.signature[..], *key))
+        })
+        .collect::<Vec<_>>();
+
+    let mut verified = ed25519::verify_batch(&items).into_iter();
+    messages
+        .iter()
+        .map(|msg| match msg {
+            Ok(_) => verified.next().expect("one result per checked envelope"),
+            Err(e) => Err(e.clone()),
+        })
+        .collect()
+}
+
+/// Header checks shared by single and batch verification; returns the
+/// signing bytes to verify against.
+fn signed_message(env: &Envelope) -> Result<Vec<u8>, VerifyError> {
     let SigAlg::Ed25519 = env.sig_alg else {
-        return false;
+        return Err(VerifyError::UnsupportedAlgorithm {
+            got: env.sig_alg.clone(),
+        });
     };
 
-    if env.signature.len() != 64 {
-        return false;
+    if env.signature.len() != ed25519::SIGNATURE_LEN {
+        return Err(VerifyError::BadSignatureLen {
+            expected: ed25519::SIGNATURE_LEN,
+            got: env.signature.len(),
+        });
     }
 
-    let msg = envelope_signing_bytes(env);
-    ed25519::verify(verifying_key, &msg, &env.signature)
+    envelope_signing_bytes(env).map_err(|_| VerifyError::MissingPayload)
 }
@@ -1,6 +1,7 @@
 use sha2::{Digest, Sha256};
 
 use crate::envelope::*;
+use crate::error::ValidateError;
 
 const DOMAIN_TAG: &[u8] = b"HMFv1:envelope-signature";
 
@@ -10,10 +11,10 @@ fn sha256(bytes: &[u8]) -> [u8; 32] {
     h.finalize().into()
 }
 
-fn put_u8(buf: &mut Vec<u8>, v: u8) {
+pub(crate) fn put_u8(buf: &mut Vec<u8>, v: u8) {
     buf.push(v);
 }
-fn put_u32(buf: &mut Vec<u8>, v: u32) {
+pub(crate) fn put_u32(buf: &mut Vec<u8>, v: u32) {
     buf.extend_from_slice(&v.to_be_bytes());
 }
 fn put_u64(buf: &mut Vec<u8>, v: u64) {
@@ -32,11 +33,11 @@ fn put_f64(buf: &mut Vec<u8>, v: f64) {
     buf.extend_from_slice(&v.to_bits().to_be_bytes());
 }
 
-fn put_bytes(buf: &mut Vec<u8>, b: &[u8]) {
+pub(crate) fn put_bytes(buf: &mut Vec<u8>, b: &[u8]) {
     put_u32(buf, b.len() as u32);
     buf.extend_from_slice(b);
 }
-fn put_str(buf: &mut Vec<u8>, s: &str) {
+pub(crate) fn put_str(buf: &mut Vec<u8>, s: &str) {
     put_bytes(buf, s.as_bytes());
 }
 
@@ -85,7 +86,22 @@ pub fn payload_hash(payload: &Payload) -> [u8; 32] {
     sha256(&canonical_payload_bytes(payload))
 }
 
-pub fn envelope_signing_bytes(env: &Envelope) -> Vec<u8> {
+/// `true` if `bytes` opens with the length-prefixed domain tag that every
+/// [`envelope_signing_bytes`] output starts with.
+pub fn has_domain_tag(bytes: &[u8]) -> bool {
+    let mut prefix = Vec::with_capacity(4 + DOMAIN_TAG.len());
+    put_bytes(&mut prefix, DOMAIN_TAG);
+    bytes.starts_with(&prefix)
+}
+
+/// Signing bytes of `env` as defined in envelope.md, "Signing bytes composition".
+///
+/// # Errors
+///
+/// Returns [`ValidateError::MissingPayload`] if `env` has no payload to hash.
+pub fn envelope_signing_bytes(env: &Envelope) -> Result<Vec<u8>, ValidateError> {
+    let payload = env.payload.as_ref().ok_or(ValidateError::MissingPayload)?;
+
     let mut buf = Vec::with_capacity(512);
 
     put_bytes(&mut buf, DOMAIN_TAG);
@@ -113,19 +129,16 @@ pub fn envelope_signing_bytes(env: &Envelope) -> Vec<u8> {
     buf.extend_from_slice(&ac_hash);
 
     // payload hash
-    let payload = env
-        .payload
-        .as_ref()
-        .expect("validated envelope must have payload");
     let ph = payload_hash(payload);
     buf.extend_from_slice(&ph);
 
-    buf
+    Ok(buf)
 }
 
 fn canonical_ack(buf: &mut Vec<u8>, a: &Ack) {
     put_i32(buf, a.status.to_i32());
     put_str(buf, &a.detail);
+    put_i32(buf, a.reason.to_i32());
 }
 
 fn canonical_op_result(buf: &mut Vec<u8>, r: &OpResult) {
@@ -295,6 +308,7 @@ fn canonical_config(buf: &mut Vec<u8>, c: &Config) {
             put_str(buf, &u.update_id);
             put_bool(buf, u.strict);
             put_map_sorted(buf, &u.params);
+            put_opt(buf, u.expected_version.as_ref(), |b, v| put_u64(b, *v));
         }
         Some(ConfigPayload::Ack(a)) => {
             put_u8(buf, 4);
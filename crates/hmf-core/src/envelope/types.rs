@@ -6,7 +6,7 @@ pub const EXPECTED_PROTO_VER: u32 = 1;
 
 // ---------- Envelope + header enums ----------
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MsgClass {
     Unspecified,
     Telemetry,
@@ -121,6 +121,42 @@ impl AckStatus {
     }
 }
 
+/// Rejection category of a rejecting `Ack`.
+///
+/// operator-interface-architecture.md requires the HMI to distinguish
+/// authorization denial, replay denial and validation failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    Unspecified,
+    Validation,
+    Replay,
+    Authorization,
+    Unsupported,
+    Unknown(i32),
+}
+impl RejectReason {
+    pub fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Unspecified,
+            1 => Self::Validation,
+            2 => Self::Replay,
+            3 => Self::Authorization,
+            4 => Self::Unsupported,
+            x => Self::Unknown(x),
+        }
+    }
+    pub fn to_i32(&self) -> i32 {
+        match *self {
+            Self::Unspecified => 0,
+            Self::Validation => 1,
+            Self::Replay => 2,
+            Self::Authorization => 3,
+            Self::Unsupported => 4,
+            Self::Unknown(x) => x,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ResultStatus {
     Unspecified,
@@ -159,9 +195,9 @@ impl ResultStatus {
 pub enum DeathReason {
     Unspecified,
     GracefulShutdown,
-    LostNetwork,
-    WatchdogReset,
-    FatalFault,
+    Reboot,
+    SecurityQuarantine,
+    FatalError,
     Unknown(i32),
 }
 impl DeathReason {
@@ -169,9 +205,9 @@ impl DeathReason {
         match v {
             0 => Self::Unspecified,
             1 => Self::GracefulShutdown,
-            2 => Self::LostNetwork,
-            3 => Self::WatchdogReset,
-            4 => Self::FatalFault,
+            2 => Self::Reboot,
+            3 => Self::SecurityQuarantine,
+            4 => Self::FatalError,
             x => Self::Unknown(x),
         }
     }
@@ -179,9 +215,9 @@ impl DeathReason {
         match *self {
             Self::Unspecified => 0,
             Self::GracefulShutdown => 1,
-            Self::LostNetwork => 2,
-            Self::WatchdogReset => 3,
-            Self::FatalFault => 4,
+            Self::Reboot => 2,
+            Self::SecurityQuarantine => 3,
+            Self::FatalError => 4,
             Self::Unknown(x) => x,
         }
     }
@@ -192,7 +228,8 @@ pub enum Health {
     Unspecified,
     Ok,
     Degraded,
-    Faulted,
+    Fault,
+    Critical,
     Unknown(i32),
 }
 impl Health {
@@ -201,7 +238,8 @@ impl Health {
             0 => Self::Unspecified,
             1 => Self::Ok,
             2 => Self::Degraded,
-            3 => Self::Faulted,
+            3 => Self::Fault,
+            4 => Self::Critical,
             x => Self::Unknown(x),
         }
     }
@@ -210,7 +248,8 @@ impl Health {
             Self::Unspecified => 0,
             Self::Ok => 1,
             Self::Degraded => 2,
-            Self::Faulted => 3,
+            Self::Fault => 3,
+            Self::Critical => 4,
             Self::Unknown(x) => x,
         }
     }
@@ -222,6 +261,7 @@ pub enum Quality {
     Good,
     Uncertain,
     Bad,
+    Stale,
     Unknown(i32),
 }
 impl Quality {
@@ -231,6 +271,7 @@ impl Quality {
             1 => Self::Good,
             2 => Self::Uncertain,
             3 => Self::Bad,
+            4 => Self::Stale,
             x => Self::Unknown(x),
         }
     }
@@ -240,6 +281,7 @@ impl Quality {
             Self::Good => 1,
             Self::Uncertain => 2,
             Self::Bad => 3,
+            Self::Stale => 4,
             Self::Unknown(x) => x,
         }
     }
@@ -250,6 +292,7 @@ pub enum AlarmSeverity {
     Unspecified,
     Info,
     Warning,
+    Error,
     Critical,
     Unknown(i32),
 }
@@ -259,7 +302,8 @@ impl AlarmSeverity {
             0 => Self::Unspecified,
             1 => Self::Info,
             2 => Self::Warning,
-            3 => Self::Critical,
+            3 => Self::Error,
+            4 => Self::Critical,
             x => Self::Unknown(x),
         }
     }
@@ -268,7 +312,8 @@ impl AlarmSeverity {
             Self::Unspecified => 0,
             Self::Info => 1,
             Self::Warning => 2,
-            Self::Critical => 3,
+            Self::Error => 3,
+            Self::Critical => 4,
             Self::Unknown(x) => x,
         }
     }
@@ -277,27 +322,36 @@ impl AlarmSeverity {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ObservationType {
     Unspecified,
-    Threat,
-    Anomaly,
-    Compliance,
+    LinkDown,
+    LinkUp,
+    HeartbeatMissed,
+    PowerLoss,
+    PowerRestored,
+    UnexpectedDeviceSeen,
     Unknown(i32),
 }
 impl ObservationType {
     pub fn from_i32(v: i32) -> Self {
         match v {
             0 => Self::Unspecified,
-            1 => Self::Threat,
-            2 => Self::Anomaly,
-            3 => Self::Compliance,
+            1 => Self::LinkDown,
+            2 => Self::LinkUp,
+            3 => Self::HeartbeatMissed,
+            4 => Self::PowerLoss,
+            5 => Self::PowerRestored,
+            6 => Self::UnexpectedDeviceSeen,
             x => Self::Unknown(x),
         }
     }
     pub fn to_i32(&self) -> i32 {
         match *self {
             Self::Unspecified => 0,
-            Self::Threat => 1,
-            Self::Anomaly => 2,
-            Self::Compliance => 3,
+            Self::LinkDown => 1,
+            Self::LinkUp => 2,
+            Self::HeartbeatMissed => 3,
+            Self::PowerLoss => 4,
+            Self::PowerRestored => 5,
+            Self::UnexpectedDeviceSeen => 6,
             Self::Unknown(x) => x,
         }
     }
@@ -337,6 +391,18 @@ pub enum Payload {
     Engineering(Engineering),
 }
 
+impl Payload {
+    /// The `msg_class` an envelope carrying this payload must declare.
+    pub fn msg_class(&self) -> MsgClass {
+        match self {
+            Self::Telemetry(_) => MsgClass::Telemetry,
+            Self::Command(_) => MsgClass::Command,
+            Self::Config(_) => MsgClass::Config,
+            Self::Engineering(_) => MsgClass::Engineering,
+        }
+    }
+}
+
 // ----- Telemetry -----
 
 #[derive(Clone, Debug, PartialEq)]
@@ -482,6 +548,8 @@ pub struct ConfigUpdate {
     pub update_id: String,
     pub strict: bool,
     pub params: BTreeMap<String, String>,
+    /// Required current `config_version`; `None` applies unconditionally.
+    pub expected_version: Option<u64>,
 }
 
 // ----- Engineering -----
@@ -522,6 +590,8 @@ pub struct EngineeringResult {
 pub struct Ack {
     pub status: AckStatus,
     pub detail: String,
+    /// Set when `status` is [`AckStatus::Rejected`].
+    pub reason: RejectReason,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -0,0 +1,121 @@
+use std::ops::Deref;
+
+use crate::envelope::limits::DecodeLimits;
+use crate::envelope::sign::{verify_envelope_ed25519, verify_envelopes_ed25519};
+use crate::envelope::{Envelope, Payload, envelope_validate_with};
+use crate::error::IngressError;
+use crate::trust::TrustRegistry;
+
+/// An envelope that passed structural validation and whose Ed25519 signature
+/// verified against a key approved for its sender (INV-PIPE-001).
+///
+/// The only public constructor is [`VerifiedEnvelope::verify`], so APIs that
+/// take a `VerifiedEnvelope` cannot be reached with unverified input:
+///
+/// ```compile_fail
+/// # fn bypass(env: hmf_core::Envelope) -> hmf_core::envelope::VerifiedEnvelope {
+/// hmf_core::envelope::VerifiedEnvelope { env }
+/// # }
+/// ```
+///
+/// The wrapper is read-only; [`Self::into_envelope`] gives up the guarantee.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedEnvelope {
+    env: Envelope,
+}
+
+impl VerifiedEnvelope {
+    /// Validates `env` against `limits` and verifies its signature with the
+    /// key `registry` binds to `env.key_id` and `env.sender_id`.
+    ///
+    /// Freshness, replay and authorization are not evaluated here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IngressError::Validate`], [`IngressError::Trust`] or
+    /// [`IngressError::Signature`] for the first failing check.
+    pub fn verify(
+        env: Envelope,
+        registry: &TrustRegistry,
+        limits: &DecodeLimits,
+    ) -> Result<Self, IngressError> {
+        envelope_validate_with(&env, limits)?;
+        Self::verify_signature(env, registry)
+    }
+
+    /// [`Self::verify`] over many envelopes, returning one result per
+    /// envelope in order.
+    ///
+    /// Signatures of the envelopes that pass structural validation and key
+    /// resolution are checked with one batch verification; see
+    /// [`verify_envelopes_ed25519`].
+    pub fn verify_batch(
+        envs: Vec<Envelope>,
+        registry: &TrustRegistry,
+        limits: &DecodeLimits,
+    ) -> Vec<Result<Self, IngressError>> {
+        let checked = envs
+            .iter()
+            .map(|env| {
+                envelope_validate_with(env, limits)?;
+                Ok(registry.resolve(&env.key_id, &env.sender_id)?)
+            })
+            .collect::<Vec<Result<_, IngressError>>>();
+        let batch = envs
+            .iter()
+            .zip(&checked)
+            .filter_map(|(env, key)| Some((env, *key.as_ref().ok()?)))
+            .collect::<Vec<_>>();
+
+        let mut verified = verify_envelopes_ed25519(&batch).into_iter();
+        envs.into_iter()
+            .zip(checked)
+            .map(|(env, key)| {
+                key?;
+                verified.next().expect("one result per checked envelope")?;
+                Ok(Self { env })
+            })
+            .collect()
+    }
+
+    /// Signature phase only, for callers that already ran structural
+    /// validation on `env`.
+    pub(crate) fn verify_signature(
+        env: Envelope,
+        registry: &TrustRegistry,
+    ) -> Result<Self, IngressError> {
+        let verifying_key = registry.resolve(&env.key_id, &env.sender_id)?;
+        verify_envelope_ed25519(&env, verifying_key)?;
+        Ok(Self { env })
+    }
+
+    pub fn envelope(&self) -> &Envelope {
+        &self.env
+    }
+
+    /// The payload; structural validation guarantees it is present.
+    pub fn payload(&self) -> &Payload {
+        self.env
+            .payload
+            .as_ref()
+            .expect("verified envelope has a payload")
+    }
+
+    pub fn into_envelope(self) -> Envelope {
+        self.env
+    }
+}
+
+impl Deref for VerifiedEnvelope {
+    type Target = Envelope;
+
+    fn deref(&self) -> &Envelope {
+        &self.env
+    }
+}
+
+impl AsRef<Envelope> for VerifiedEnvelope {
+    fn as_ref(&self) -> &Envelope {
+        &self.env
+    }
+}
@@ -28,4 +28,271 @@ pub enum ValidateError {
 
     #[error("key_id must not be empty")]
     BadKeyId,
+
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+}
+
+/// A field exceeded its [`crate::envelope::limits::DecodeLimits`] bound.
+#[derive(Debug, Error)]
+pub enum LimitError {
+    #[error("auth_context is {len} bytes, limit {max}")]
+    AuthContextTooLarge { len: usize, max: usize },
+
+    #[error("{field} is {len} bytes, limit {max}")]
+    StringTooLong {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+
+    #[error("{field} has {len} entries, limit {max}")]
+    TooManyEntries {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+
+    #[error("{field} has {len} items, limit {max}")]
+    TooManyItems {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+
+    #[error("{msg_class:?} blob is {len} bytes, limit {max}")]
+    BlobTooLarge {
+        msg_class: crate::envelope::MsgClass,
+        len: usize,
+        max: usize,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum TrustError {
+    #[error("key_id must not be empty")]
+    EmptyKeyId,
+
+    #[error("unknown key_id: {key_id}")]
+    UnknownKeyId { key_id: String },
+
+    #[error("key_id {key_id} has been revoked")]
+    Revoked { key_id: String },
+
+    #[error("key_id {key_id} is already bound to a different key or sender")]
+    KeyIdConflict { key_id: String },
+
+    #[error("key_id {key_id} has a public key of small order or with a small-order component")]
+    WeakKey { key_id: String },
+
+    #[error("key_id {key_id} has a non-canonical public key encoding")]
+    NonCanonicalKey { key_id: String },
+
+    #[error("key_id {key_id} is not bound to sender {sender_id}")]
+    SenderMismatch {
+        key_id: String,
+        sender_id: crate::ids::DeviceId,
+    },
+}
+
+/// Why manufacturer attestation evidence was not accepted.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum AttestationError {
+    #[error("no verifier for evidence format {format}")]
+    Unsupported { format: String },
+
+    #[error("malformed evidence: {reason}")]
+    Malformed { reason: String },
+
+    #[error("certificate chain rejected: {reason}")]
+    Chain { reason: String },
+
+    #[error("evidence does not certify the enrolled public key")]
+    KeyMismatch,
+}
+
+/// Why an enrollment request could not be signed or was not accepted.
+#[derive(Debug, Error)]
+pub enum EnrollmentError {
+    #[error("malformed enrollment request: {reason}")]
+    Malformed { reason: String },
+
+    #[error("enrollment request signature rejected: {0}")]
+    Signature(#[from] VerifyError),
+
+    #[error(transparent)]
+    Sign(#[from] SignerError),
+
+    #[error("signer does not hold the proposed key")]
+    KeyMismatch,
+}
+
+/// Why an envelope signature was not accepted.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("unsupported signature algorithm: {got:?}")]
+    UnsupportedAlgorithm { got: crate::envelope::SigAlg },
+
+    #[error("invalid signature length: expected {expected} bytes, got {got}")]
+    BadSignatureLen { expected: usize, got: usize },
+
+    #[error("malformed signature: {reason}")]
+    MalformedSignature { reason: &'static str },
+
+    #[error("verifying key has small order or a small-order component")]
+    WeakKey,
+
+    #[error("envelope has no payload to verify")]
+    MissingPayload,
+
+    #[error("signature verification failed")]
+    BadSignature,
+}
+
+#[derive(Debug, Error)]
+pub enum IngressError {
+    #[error(transparent)]
+    Validate(#[from] ValidateError),
+
+    #[error("ttl expired: observed {age_ms} ms ago, ttl_ms {ttl_ms}")]
+    Expired { age_ms: u128, ttl_ms: u32 },
+
+    #[error(transparent)]
+    Trust(#[from] TrustError),
+
+    #[error(transparent)]
+    Signature(#[from] VerifyError),
+
+    #[error("counter {counter} does not advance past last seen {last_seen}")]
+    Replay { counter: u64, last_seen: u64 },
+
+    #[error("authorization denied")]
+    Unauthorized,
+
+    #[error("{payload_kind} payload carries no message")]
+    EmptyPayload { payload_kind: &'static str },
+
+    #[error("no handler bound for {payload_kind} requests")]
+    Unsupported { payload_kind: &'static str },
+
+    #[error(transparent)]
+    Endpoint(#[from] EndpointError),
+}
+
+/// Text was not the expected hexadecimal encoding ([`crate::hex`]).
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum HexError {
+    #[error("odd number of hex digits")]
+    OddLength,
+
+    #[error("expected {expected} hex digits, got {got}")]
+    BadLength { expected: usize, got: usize },
+
+    #[error("invalid hex digit at position {index}")]
+    InvalidDigit { index: usize },
+}
+
+/// A key file could not be written or opened
+/// ([`crate::crypto::keyfile::KeyFile`]).
+#[derive(Debug, Error)]
+pub enum KeyFileError {
+    #[error("key file i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("not an hmf key file")]
+    BadMagic,
+
+    #[error("unsupported key file version {got}")]
+    UnsupportedVersion { got: u8 },
+
+    #[error("unsupported key derivation {got}")]
+    UnsupportedKdf { got: u8 },
+
+    #[error("malformed key file: {reason}")]
+    Malformed { reason: &'static str },
+
+    #[error("key file is sealed with a passphrase where a KEK was supplied, or vice versa")]
+    ProtectionMismatch,
+
+    #[error("invalid key derivation parameters: {0}")]
+    KdfParams(String),
+
+    #[error("wrong passphrase or KEK, or the key file was modified")]
+    Decrypt,
+
+    #[error("neither HMF_KEY_PASSPHRASE nor HMF_KEY_KEK is set")]
+    NoProtection,
+
+    #[error("HMF_KEY_KEK must be 64 hex digits")]
+    BadKek,
+}
+
+/// A [`crate::crypto::signer::Signer`] could not produce a signature.
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error(transparent)]
+    Validate(#[from] ValidateError),
+
+    #[error("signer i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("signer protocol error: {0}")]
+    Protocol(String),
+
+    #[error("signer refused: {0}")]
+    Refused(String),
+
+    #[error("signer returned a signature that does not verify under its public key")]
+    BadSignature,
+}
+
+#[derive(Debug, Error)]
+pub enum EndpointError {
+    #[error(
+        "outbound counter exhausted for sender_instance {sender_instance}; a new instance is required"
+    )]
+    CounterExhausted {
+        sender_instance: crate::ids::InstanceId,
+    },
+
+    #[error(transparent)]
+    Sign(#[from] SignerError),
+}
+
+#[derive(Debug, Error)]
+pub enum CorrelationError {
+    #[error("envelope does not carry a request payload")]
+    NotARequest,
+
+    #[error("transaction {transaction_id} is already outstanding")]
+    DuplicateTransaction {
+        transaction_id: crate::ids::TransactionId,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("config journal is corrupt at line {line}")]
+    CorruptJournal { line: usize },
+
+    #[error("config journal out of sequence: expected version {expected}, got {got}")]
+    VersionGap { expected: u64, got: u64 },
+
+    #[error("update_id {update_id} was already applied with different contents")]
+    UpdateIdReused { update_id: String },
+
+    #[error("config_version conflict: expected {expected}, current {current}")]
+    VersionConflict { expected: u64, current: u64 },
+
+    #[error("config_version exhausted")]
+    VersionExhausted,
+
+    #[error("unknown config key: {key}")]
+    UnknownKey { key: String },
+
+    #[error("invalid value for {key}: {reason}")]
+    InvalidValue { key: String, reason: String },
 }
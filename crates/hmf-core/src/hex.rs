@@ -0,0 +1,60 @@
+//! Lowercase hexadecimal encoding for keys, digests and identifiers shown to
+//! operators or stored in text files.
+
+use crate::error::HexError;
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        out.push(DIGITS[(b >> 4) as usize] as char);
+        out.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// Decodes `s`, accepting either case.
+///
+/// # Errors
+///
+/// Returns [`HexError`] for an odd length or a non-hex digit.
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(HexError::OddLength);
+    }
+    let mut out = vec![0; s.len() / 2];
+    decode_into(s, &mut out)?;
+    Ok(out)
+}
+
+/// Decodes `s` into exactly `out.len()` bytes, so secrets can be decoded
+/// straight into a zeroizing buffer.
+///
+/// # Errors
+///
+/// Returns [`HexError::BadLength`] unless `s` has `2 * out.len()` digits,
+/// and [`HexError::InvalidDigit`] for a non-hex digit.
+pub fn decode_into(s: &str, out: &mut [u8]) -> Result<(), HexError> {
+    if s.len() != out.len() * 2 {
+        return Err(HexError::BadLength {
+            expected: out.len() * 2,
+            got: s.len(),
+        });
+    }
+    for (i, (byte, pair)) in out.iter_mut().zip(s.as_bytes().chunks(2)).enumerate() {
+        let hi = digit(pair[0]).ok_or(HexError::InvalidDigit { index: 2 * i })?;
+        let lo = digit(pair[1]).ok_or(HexError::InvalidDigit { index: 2 * i + 1 })?;
+        *byte = (hi << 4) | lo;
+    }
+    Ok(())
+}
+
+fn digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
@@ -1,6 +1,12 @@
+pub mod correlation;
 pub mod crypto;
+pub mod endpoint;
 pub mod envelope;
 pub mod error;
+pub mod hex;
 pub mod ids;
+pub mod replay;
+pub mod runtime;
+pub mod trust;
 
 pub use envelope::Envelope;
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use crate::ids::{DeviceId, InstanceId};
+
+/// Monotonic counter state per `(sender_id, sender_instance)`.
+///
+/// Implements the counter model of replay-and-freshness.md: a message is
+/// accepted only if `counter_new > counter_last_seen` for its sender stream.
+/// Callers MUST only record counters for envelopes whose signature verified.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayGuard {
+    last_seen: HashMap<(DeviceId, InstanceId), u64>,
+}
+
+impl ReplayGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn last_seen(&self, sender_id: &DeviceId, sender_instance: &InstanceId) -> Option<u64> {
+        self.last_seen
+            .get(&(sender_id.clone(), sender_instance.clone()))
+            .copied()
+    }
+
+    /// Checks `counter` against the stream state and records it on success.
+    ///
+    /// Returns the previously seen counter on regression or duplication.
+    pub fn check_and_record(
+        &mut self,
+        sender_id: &DeviceId,
+        sender_instance: &InstanceId,
+        counter: u64,
+    ) -> Result<(), u64> {
+        let last = self
+            .last_seen
+            .entry((sender_id.clone(), sender_instance.clone()))
+            .or_insert(0);
+        if counter <= *last {
+            return Err(*last);
+        }
+        *last = counter;
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+pub mod config;
+pub mod engineering;
+pub mod handler;
+
+use std::time::Instant;
+
+use crate::endpoint::{EndpointContext, Routing};
+use crate::envelope::limits::DecodeLimits;
+use crate::envelope::*;
+use crate::error::{EndpointError, IngressError};
+use crate::replay::ReplayGuard;
+use crate::trust::TrustRegistry;
+
+use handler::{
+    Authorizer, CommandHandler, ConfigHandler, EngineeringHandler, TelemetrySink, VerifiedContext,
+};
+
+/// Default `ttl_ms` applied to responses emitted by the runtime.
+pub const DEFAULT_RESPONSE_TTL_MS: u32 = 5_000;
+
+/// The [`RejectReason`] reported to the sender for `err`.
+fn reject_reason(err: &IngressError) -> RejectReason {
+    match err {
+        IngressError::Replay { .. } => RejectReason::Replay,
+        IngressError::Unauthorized => RejectReason::Authorization,
+        IngressError::Unsupported { .. } => RejectReason::Unsupported,
+        _ => RejectReason::Validation,
+    }
+}
+
+/// An inbound envelope that did not reach semantic execution.
+#[derive(Debug)]
+pub struct Rejected {
+    pub reason: IngressError,
+    /// Signed rejecting `Ack` for the sender, when the sender was authenticated
+    /// and the envelope was a request.
+    pub response: Option<Box<Envelope>>,
+}
+
+/// An inbound envelope that passed every ingress phase.
+#[derive(Debug)]
+pub struct Accepted {
+    pub envelope: VerifiedEnvelope,
+    /// Signed responses, in transmission order.
+    pub responses: Vec<Envelope>,
+    /// Responses that could not be signed. The envelope was still executed,
+    /// so these are never reported as an ingress rejection.
+    pub unsigned: Vec<EndpointError>,
+}
+
+/// Receiving endpoint runtime.
+///
+/// Runs every inbound envelope through the ingress pipeline of
+/// receiver-validation.md and only then dispatches it to the bound
+/// application handler. Responses returned by handlers are sent through the
+/// owned [`EndpointContext`] and handed back to the caller for transmission.
+pub struct Runtime {
+    endpoint: EndpointContext,
+    response_ttl_ms: u32,
+    limits: DecodeLimits,
+
+    registry: TrustRegistry,
+    replay: ReplayGuard,
+    authorizer: Box<dyn Authorizer>,
+
+    commands: Option<Box<dyn CommandHandler>>,
+    config: Option<Box<dyn ConfigHandler>>,
+    engineering: Option<Box<dyn EngineeringHandler>>,
+    telemetry: Option<Box<dyn TelemetrySink>>,
+}
+
+impl Runtime {
+    pub fn new(
+        endpoint: EndpointContext,
+        registry: TrustRegistry,
+        authorizer: impl Authorizer + 'static,
+    ) -> Self {
+        Self {
+            endpoint,
+            response_ttl_ms: DEFAULT_RESPONSE_TTL_MS,
+            limits: DecodeLimits::default(),
+
+            registry,
+            replay: ReplayGuard::new(),
+            authorizer: Box::new(authorizer),
+
+            commands: None,
+            config: None,
+            engineering: None,
+            telemetry: None,
+        }
+    }
+
+    pub fn with_command_handler(mut self, handler: impl CommandHandler + 'static) -> Self {
+        self.commands = Some(Box::new(handler));
+        self
+    }
+
+    pub fn with_config_handler(mut self, handler: impl ConfigHandler + 'static) -> Self {
+        self.config = Some(Box::new(handler));
+        self
+    }
+
+    pub fn with_engineering_handler(mut self, handler: impl EngineeringHandler + 'static) -> Self {
+        self.engineering = Some(Box::new(handler));
+        self
+    }
+
+    pub fn with_telemetry_sink(mut self, sink: impl TelemetrySink + 'static) -> Self {
+        self.telemetry = Some(Box::new(sink));
+        self
+    }
+
+    pub fn with_response_ttl_ms(mut self, ttl_ms: u32) -> Self {
+        self.response_ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Size limits enforced during structural validation.
+    pub fn with_decode_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn endpoint(&self) -> &EndpointContext {
+        &self.endpoint
+    }
+
+    pub fn endpoint_mut(&mut self) -> &mut EndpointContext {
+        &mut self.endpoint
+    }
+
+    pub fn registry(&self) -> &TrustRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut TrustRegistry {
+        &mut self.registry
+    }
+
+    /// Processes one inbound envelope first observed at `first_observed`.
+    ///
+    /// Phases run in the normative order and fail closed. On success the
+    /// verified envelope is handed back with the signed responses (possibly
+    /// none) in transmission order, and any that failed to sign.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Rejected`] when any phase fails. Rejections raised after the
+    /// signature verified carry a signed rejecting `Ack` for request payloads.
+    pub fn process(
+        &mut self,
+        env: Envelope,
+        first_observed: Instant,
+    ) -> Result<Accepted, Rejected> {
+        let unanswered = |reason: IngressError| Rejected {
+            reason,
+            response: None,
+        };
+
+        // 1. Structural validation.
+        envelope_validate_with(&env, &self.limits).map_err(|e| unanswered(e.into()))?;
+
+        // 2. Freshness validation against receiver-local monotonic time.
+        let age_ms = first_observed.elapsed().as_millis();
+        if age_ms > u128::from(env.ttl_ms) {
+            return Err(unanswered(IngressError::Expired {
+                age_ms,
+                ttl_ms: env.ttl_ms,
+            }));
+        }
+
+        // 3. Signature validation against the local trust registry.
+        let env = VerifiedEnvelope::verify_signature(env, &self.registry).map_err(unanswered)?;
+
+        // 4. Replay validation; only signed envelopes update replay state.
+        if let Err(last_seen) =
+            self.replay
+                .check_and_record(&env.sender_id, &env.sender_instance, env.counter)
+        {
+            let counter = env.counter;
+            return Err(self.reject(&env, IngressError::Replay { counter, last_seen }));
+        }
+
+        // 5. Authorization validation.
+        let ctx = VerifiedContext::new(env, first_observed);
+        if !self.authorizer.authorize(&ctx, ctx.envelope().payload()) {
+            return Err(self.reject(ctx.envelope(), IngressError::Unauthorized));
+        }
+
+        // 6. Semantic execution.
+        match self.dispatch(&ctx, ctx.envelope().payload()) {
+            Ok(payloads) => {
+                let envelope = ctx.into_envelope();
+                let mut responses = Vec::with_capacity(payloads.len());
+                let mut unsigned = Vec::new();
+                for payload in payloads {
+                    match self.respond(&envelope, payload) {
+                        Ok(response) => responses.push(response),
+                        Err(e) => unsigned.push(e),
+                    }
+                }
+                Ok(Accepted {
+                    envelope,
+                    responses,
+                    unsigned,
+                })
+            }
+            Err(e) => Err(self.reject(ctx.envelope(), e)),
+        }
+    }
+
+    fn dispatch(
+        &mut self,
+        ctx: &VerifiedContext,
+        payload: &Payload,
+    ) -> Result<Vec<Payload>, IngressError> {
+        match payload {
+            Payload::Telemetry(t) => {
+                let body = t.payload.as_ref().ok_or(IngressError::EmptyPayload {
+                    payload_kind: "telemetry",
+                })?;
+                if let Some(sink) = self.telemetry.as_mut() {
+                    sink.on_telemetry(ctx, body);
+                }
+                Ok(Vec::new())
+            }
+            Payload::Command(c) => match c.payload.as_ref() {
+                None => Err(IngressError::EmptyPayload {
+                    payload_kind: "command",
+                }),
+                Some(CommandPayload::Request(req)) => {
+                    let handler = self.commands.as_mut().ok_or(IngressError::Unsupported {
+                        payload_kind: "command",
+                    })?;
+                    let ack = CommandPayload::Ack(received());
+                    let result = CommandPayload::Result(handler.handle_command(ctx, req));
+                    Ok(vec![command(ack), command(result)])
+                }
+                // Responses to our own requests are not dispatched to handlers.
+                Some(CommandPayload::Ack(_) | CommandPayload::Result(_)) => Ok(Vec::new()),
+            },
+            Payload::Config(c) => match c.payload.as_ref() {
+                None => Err(IngressError::EmptyPayload {
+                    payload_kind: "config",
+                }),
+                Some(ConfigPayload::Query(q)) => {
+                    let handler = self.config.as_mut().ok_or(IngressError::Unsupported {
+                        payload_kind: "config",
+                    })?;
+                    let snapshot = ConfigPayload::Snapshot(handler.query(ctx, q));
+                    Ok(vec![config(snapshot)])
+                }
+                Some(ConfigPayload::Update(u)) => {
+                    let handler = self.config.as_mut().ok_or(IngressError::Unsupported {
+                        payload_kind: "config",
+                    })?;
+                    let ack = ConfigPayload::Ack(received());
+                    let result = ConfigPayload::Result(handler.update(ctx, u));
+                    Ok(vec![config(ack), config(result)])
+                }
+                Some(
+                    ConfigPayload::Snapshot(_) | ConfigPayload::Ack(_) | ConfigPayload::Result(_),
+                ) => Ok(Vec::new()),
+            },
+            Payload::Engineering(e) => match e.payload.as_ref() {
+                None => Err(IngressError::EmptyPayload {
+                    payload_kind: "engineering",
+                }),
+                Some(EngineeringPayload::Request(req)) => {
+                    let handler = self.engineering.as_mut().ok_or(IngressError::Unsupported {
+                        payload_kind: "engineering",
+                    })?;
+                    let ack = EngineeringPayload::Ack(received());
+                    let result = EngineeringPayload::Result(handler.handle_engineering(ctx, req));
+                    Ok(vec![engineering(ack), engineering(result)])
+                }
+                Some(EngineeringPayload::Ack(_) | EngineeringPayload::Result(_)) => Ok(Vec::new()),
+            },
+        }
+    }
+
+    /// Builds the rejection for an authenticated envelope, answering requests
+    /// with a signed rejecting `Ack` that carries the [`RejectReason`].
+    fn reject(&mut self, env: &VerifiedEnvelope, reason: IngressError) -> Rejected {
+        let ack = Ack {
+            status: AckStatus::Rejected,
+            detail: reason.to_string(),
+            reason: reject_reason(&reason),
+        };
+        let payload = match env.payload() {
+            Payload::Command(Command {
+                payload: Some(CommandPayload::Request(_)),
+            }) => Some(command(CommandPayload::Ack(ack))),
+            Payload::Config(Config {
+                payload: Some(ConfigPayload::Query(_) | ConfigPayload::Update(_)),
+            }) => Some(config(ConfigPayload::Ack(ack))),
+            Payload::Engineering(Engineering {
+                payload: Some(EngineeringPayload::Request(_)),
+            }) => Some(engineering(EngineeringPayload::Ack(ack))),
+            _ => None,
+        };
+
+        Rejected {
+            reason,
+            response: payload
+                .and_then(|p| self.respond(env, p).ok())
+                .map(Box::new),
+        }
+    }
+
+    /// Wraps `payload` in a signed envelope answering `request`.
+    fn respond(
+        &mut self,
+        request: &VerifiedEnvelope,
+        payload: Payload,
+    ) -> Result<Envelope, EndpointError> {
+        let routing = Routing::reply_to(request).with_ttl_ms(self.response_ttl_ms);
+        self.endpoint.send(payload, routing)
+    }
+}
+
+fn received() -> Ack {
+    Ack {
+        status: AckStatus::Received,
+        detail: String::new(),
+        reason: RejectReason::Unspecified,
+    }
+}
+
+fn command(p: CommandPayload) -> Payload {
+    Payload::Command(Command { payload: Some(p) })
+}
+
+fn config(p: ConfigPayload) -> Payload {
+    Payload::Config(Config { payload: Some(p) })
+}
+
+fn engineering(p: EngineeringPayload) -> Payload {
+    Payload::Engineering(Engineering { payload: Some(p) })
+}
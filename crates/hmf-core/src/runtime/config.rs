@@ -0,0 +1,516 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::envelope::signing_bytes::payload_hash;
+use crate::envelope::{
+    Config, ConfigPayload, ConfigQuery, ConfigSnapshot, ConfigUpdate, OpResult, Payload,
+    ResultStatus,
+};
+use crate::error::ConfigError;
+use crate::hex;
+use crate::ids::{DeviceId, TransactionId};
+use crate::runtime::handler::{ConfigHandler, VerifiedContext};
+
+/// Value type and constraints of one configuration parameter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamType {
+    Bool,
+    Int { min: i64, max: i64 },
+    Str { max_len: usize },
+    OneOf(Vec<String>),
+}
+
+impl ParamType {
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Self::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err("expected true or false".to_string()),
+            },
+            Self::Int { min, max } => {
+                let v: i64 = value.parse().map_err(|_| "expected integer".to_string())?;
+                if v < *min || v > *max {
+                    return Err(format!("out of range [{min}, {max}]"));
+                }
+                Ok(())
+            }
+            Self::Str { max_len } => {
+                if value.len() > *max_len {
+                    return Err(format!("longer than {max_len} bytes"));
+                }
+                Ok(())
+            }
+            Self::OneOf(allowed) => {
+                if !allowed.iter().any(|a| a == value) {
+                    return Err(format!("expected one of {}", allowed.join(", ")));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamSpec {
+    pub ty: ParamType,
+    pub default: String,
+}
+
+/// The typed set of parameters an endpoint exposes for configuration.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSchema {
+    params: BTreeMap<String, ParamSpec>,
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares `key` with type `ty` and initial value `default`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::InvalidValue`] if `default` does not satisfy `ty`.
+    pub fn with_param(
+        mut self,
+        key: &str,
+        ty: ParamType,
+        default: &str,
+    ) -> Result<Self, ConfigError> {
+        ty.check(default)
+            .map_err(|reason| ConfigError::InvalidValue {
+                key: key.to_string(),
+                reason,
+            })?;
+        self.params.insert(
+            key.to_string(),
+            ParamSpec {
+                ty,
+                default: default.to_string(),
+            },
+        );
+        Ok(self)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ParamSpec> {
+        self.params.get(key)
+    }
+
+    fn defaults(&self) -> BTreeMap<String, String> {
+        self.params
+            .iter()
+            .map(|(k, spec)| (k.clone(), spec.default.clone()))
+            .collect()
+    }
+}
+
+/// One applied `ConfigUpdate`, as recorded in the config history.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppliedUpdate {
+    /// `config_version` produced by this update.
+    pub config_version: u64,
+    pub update_id: String,
+    pub transaction_id: TransactionId,
+    pub sender_id: DeviceId,
+    /// Parameters written by this update, after validation.
+    pub params: BTreeMap<String, String>,
+    /// [`update_digest`] of the `ConfigUpdate` as received, used to tell a
+    /// re-delivery from a different update reusing its `update_id`.
+    pub digest: [u8; 32],
+}
+
+/// Digest of `update`: the payload hash of a config payload carrying it.
+pub fn update_digest(update: &ConfigUpdate) -> [u8; 32] {
+    payload_hash(&Payload::Config(Config {
+        payload: Some(ConfigPayload::Update(update.clone())),
+    }))
+}
+
+/// Durable, append-only record of applied config updates.
+pub trait ConfigJournal {
+    /// Returns all previously recorded updates, oldest first.
+    fn load(&mut self) -> Result<Vec<AppliedUpdate>, ConfigError>;
+
+    /// Durably records `entry`. Called before the update takes effect.
+    fn append(&mut self, entry: &AppliedUpdate) -> Result<(), ConfigError>;
+}
+
+/// A journal that keeps history in memory only.
+#[derive(Debug, Default)]
+pub struct MemoryJournal {
+    entries: Vec<AppliedUpdate>,
+}
+
+impl ConfigJournal for MemoryJournal {
+    fn load(&mut self) -> Result<Vec<AppliedUpdate>, ConfigError> {
+        Ok(self.entries.clone())
+    }
+
+    fn append(&mut self, entry: &AppliedUpdate) -> Result<(), ConfigError> {
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+}
+
+/// A line-oriented journal file, one applied update per line.
+///
+/// Line format (tab separated, fields percent-escaped):
+/// `config_version update_id transaction_id sender_id key=value&key=value digest`
+///
+/// A final line without its newline is the remainder of an interrupted
+/// append. It never took effect: [`ConfigJournal::load`] discards it and
+/// truncates the file, and an append in the same process first cuts it off
+/// so the new entry starts on its own line.
+#[derive(Debug)]
+pub struct FileJournal {
+    path: PathBuf,
+}
+
+impl FileJournal {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ConfigJournal for FileJournal {
+    fn load(&mut self) -> Result<Vec<AppliedUpdate>, ConfigError> {
+        let bytes = match fs::read(&self.path) {
+            Ok(b) => b,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let complete = complete_len(&bytes);
+        if complete < bytes.len() {
+            // The entry was never fully written, so it never took effect.
+            let file = OpenOptions::new().write(true).open(&self.path)?;
+            file.set_len(complete as u64)?;
+            file.sync_data()?;
+        }
+
+        let mut out = Vec::new();
+        for (idx, line) in bytes[..complete].split(|&b| b == b'\n').enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let entry = std::str::from_utf8(line)
+                .ok()
+                .and_then(parse_line)
+                .ok_or(ConfigError::CorruptJournal { line: idx + 1 })?;
+            out.push(entry);
+        }
+        Ok(out)
+    }
+
+    fn append(&mut self, entry: &AppliedUpdate) -> Result<(), ConfigError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        let len = trim_torn_tail(&mut file)?;
+        let params: Vec<String> = entry
+            .params
+            .iter()
+            .map(|(k, v)| format!("{}={}", escape(k), escape(v)))
+            .collect();
+        let line = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            entry.config_version,
+            escape(&entry.update_id),
+            escape(entry.transaction_id.as_str()),
+            escape(entry.sender_id.as_str()),
+            params.join("&"),
+            hex::encode(&entry.digest),
+        );
+        if let Err(e) = file
+            .write_all(line.as_bytes())
+            .and_then(|()| file.sync_data())
+        {
+            // Best effort; a torn tail left behind is cut off before the next
+            // append or load.
+            let _ = file.set_len(len);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+/// Length of `bytes` up to and including its last newline.
+fn complete_len(bytes: &[u8]) -> usize {
+    bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1)
+}
+
+/// Truncates `file` to its last complete line and returns the new length.
+fn trim_torn_tail(file: &mut File) -> std::io::Result<u64> {
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(0);
+    }
+    let mut last = [0u8];
+    file.seek(SeekFrom::Start(len - 1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(len);
+    }
+
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut bytes)?;
+    let complete = complete_len(&bytes) as u64;
+    file.set_len(complete)?;
+    file.sync_data()?;
+    Ok(complete)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '%' | '\t' | '\n' | '\r' | '=' | '&' => out.push_str(&format!("%{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hi = (bytes.next()? as char).to_digit(16)?;
+            let lo = (bytes.next()? as char).to_digit(16)?;
+            out.push((hi * 16 + lo) as u8);
+        } else {
+            out.push(b);
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn parse_line(line: &str) -> Option<AppliedUpdate> {
+    let mut fields = line.split('\t');
+    let config_version = fields.next()?.parse().ok()?;
+    let update_id = unescape(fields.next()?)?;
+    let transaction_id = TransactionId::new(unescape(fields.next()?)?);
+    let sender_id = DeviceId::new(unescape(fields.next()?)?);
+    let raw_params = fields.next()?;
+    let mut digest = [0u8; 32];
+    hex::decode_into(fields.next()?, &mut digest).ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let mut params = BTreeMap::new();
+    for pair in raw_params.split('&').filter(|p| !p.is_empty()) {
+        let (k, v) = pair.split_once('=')?;
+        params.insert(unescape(k)?, unescape(v)?);
+    }
+
+    Some(AppliedUpdate {
+        config_version,
+        update_id,
+        transaction_id,
+        sender_id,
+        params,
+        digest,
+    })
+}
+
+/// Versioned configuration state of a receiving endpoint.
+///
+/// Updates are atomic: either every parameter of a `ConfigUpdate` is applied
+/// and `config_version` advances by one, or nothing changes. A present
+/// `ConfigUpdate.expected_version` makes the update a compare-and-set against
+/// the current version, which is 0 before the first update. A re-delivered
+/// `update_id` reports the original result without re-applying it, and is
+/// rejected if its contents differ from the original. Strict updates are rejected if they name any key
+/// outside the schema; non-strict updates skip unknown keys. Each applied
+/// update is journaled, with the transaction that caused it, before it takes
+/// effect.
+pub struct ConfigStore {
+    schema: ConfigSchema,
+    version: u64,
+    values: BTreeMap<String, String>,
+    history: Vec<AppliedUpdate>,
+    journal: Box<dyn ConfigJournal>,
+}
+
+impl ConfigStore {
+    /// Opens the store, replaying `journal` on top of the schema defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError`] if the journal cannot be read, or a recorded
+    /// update is out of sequence or no longer satisfies `schema`.
+    pub fn open(
+        schema: ConfigSchema,
+        mut journal: impl ConfigJournal + 'static,
+    ) -> Result<Self, ConfigError> {
+        let history = journal.load()?;
+        let mut values = schema.defaults();
+        let mut version = 0;
+
+        for entry in &history {
+            if entry.config_version != version + 1 {
+                return Err(ConfigError::VersionGap {
+                    expected: version + 1,
+                    got: entry.config_version,
+                });
+            }
+            for (k, v) in &entry.params {
+                let spec = schema
+                    .get(k)
+                    .ok_or_else(|| ConfigError::UnknownKey { key: k.clone() })?;
+                spec.ty
+                    .check(v)
+                    .map_err(|reason| ConfigError::InvalidValue {
+                        key: k.clone(),
+                        reason,
+                    })?;
+                values.insert(k.clone(), v.clone());
+            }
+            version = entry.config_version;
+        }
+
+        Ok(Self {
+            schema,
+            version,
+            values,
+            history,
+            journal: Box::new(journal),
+        })
+    }
+
+    pub fn config_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    pub fn history(&self) -> &[AppliedUpdate] {
+        &self.history
+    }
+
+    /// Returns the requested keys, or every key if `keys` is empty.
+    /// Keys outside the schema are omitted.
+    pub fn snapshot(&self, keys: &[String]) -> ConfigSnapshot {
+        let params = if keys.is_empty() {
+            self.values.clone()
+        } else {
+            keys.iter()
+                .filter_map(|k| self.values.get_key_value(k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        };
+        ConfigSnapshot {
+            config_version: self.version,
+            params,
+        }
+    }
+
+    /// Validates and applies `update` on behalf of `sender_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError`] if the update is rejected; the configuration
+    /// is left unchanged in that case.
+    pub fn apply(
+        &mut self,
+        update: &ConfigUpdate,
+        sender_id: &DeviceId,
+        transaction_id: &TransactionId,
+    ) -> Result<&AppliedUpdate, ConfigError> {
+        let digest = update_digest(update);
+        if let Some(idx) = self
+            .history
+            .iter()
+            .position(|h| !update.update_id.is_empty() && h.update_id == update.update_id)
+        {
+            if self.history[idx].digest != digest {
+                return Err(ConfigError::UpdateIdReused {
+                    update_id: update.update_id.clone(),
+                });
+            }
+            // Re-delivery of an applied update: report it without re-applying.
+            return Ok(&self.history[idx]);
+        }
+
+        if let Some(expected) = update.expected_version
+            && expected != self.version
+        {
+            return Err(ConfigError::VersionConflict {
+                expected,
+                current: self.version,
+            });
+        }
+
+        let mut params = BTreeMap::new();
+        for (k, v) in &update.params {
+            let Some(spec) = self.schema.get(k) else {
+                if update.strict {
+                    return Err(ConfigError::UnknownKey { key: k.clone() });
+                }
+                continue;
+            };
+            spec.ty
+                .check(v)
+                .map_err(|reason| ConfigError::InvalidValue {
+                    key: k.clone(),
+                    reason,
+                })?;
+            params.insert(k.clone(), v.clone());
+        }
+
+        let version = self
+            .version
+            .checked_add(1)
+            .ok_or(ConfigError::VersionExhausted)?;
+        let entry = AppliedUpdate {
+            config_version: version,
+            update_id: update.update_id.clone(),
+            transaction_id: transaction_id.clone(),
+            sender_id: sender_id.clone(),
+            params,
+            digest,
+        };
+        self.journal.append(&entry)?;
+
+        self.values
+            .extend(entry.params.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.version = version;
+        self.history.push(entry);
+        Ok(self.history.last().expect("entry was just pushed"))
+    }
+}
+
+impl ConfigHandler for ConfigStore {
+    fn query(&mut self, _ctx: &VerifiedContext, query: &ConfigQuery) -> ConfigSnapshot {
+        self.snapshot(&query.keys)
+    }
+
+    fn update(&mut self, ctx: &VerifiedContext, update: &ConfigUpdate) -> OpResult {
+        match self.apply(update, ctx.sender_id(), ctx.transaction_id()) {
+            Ok(applied) => OpResult {
+                status: ResultStatus::Applied,
+                detail: format!("config_version {}", applied.config_version),
+            },
+            Err(e @ ConfigError::Io(_)) => OpResult {
+                status: ResultStatus::Failed,
+                detail: e.to_string(),
+            },
+            Err(e) => OpResult {
+                status: ResultStatus::Rejected,
+                detail: e.to_string(),
+            },
+        }
+    }
+}
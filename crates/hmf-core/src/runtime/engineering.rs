@@ -0,0 +1,497 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use crate::envelope::{EngineeringRequest, EngineeringResult, ResultStatus};
+use crate::hex;
+use crate::ids::DeviceId;
+use crate::runtime::handler::{EngineeringHandler, VerifiedContext};
+
+/// `EngineeringRequest.action` values of the chunked transfer flow.
+///
+/// Every transfer request carries `params["transfer_id"]`.
+///
+/// | Action | Params | Blob |
+/// |---|---|---|
+/// | `transfer.begin` | `artifact_len`, `artifact_sha256` (hex) | — |
+/// | `transfer.chunk` | `offset`, `chunk_sha256` (hex) | chunk bytes |
+/// | `transfer.status` | — | — |
+/// | `transfer.commit` | — | — |
+/// | `transfer.abort` | — | — |
+///
+/// The whole-artifact hash is fixed by the signed `transfer.begin` request;
+/// `transfer.commit` activates the staged artifact only if every byte was
+/// received and its SHA-256 matches that hash.
+pub mod action {
+    pub const BEGIN: &str = "transfer.begin";
+    pub const CHUNK: &str = "transfer.chunk";
+    pub const STATUS: &str = "transfer.status";
+    pub const COMMIT: &str = "transfer.commit";
+    pub const ABORT: &str = "transfer.abort";
+}
+
+/// Default upper bound for one chunk, well below the transport record limit.
+pub const DEFAULT_MAX_CHUNK_LEN: usize = 256 * 1024;
+
+/// Default upper bound for a whole artifact.
+pub const DEFAULT_MAX_ARTIFACT_LEN: u64 = 64 * 1024 * 1024;
+
+/// Default number of transfers in flight across all senders.
+pub const DEFAULT_MAX_TRANSFERS: usize = 8;
+
+/// Default number of transfers in flight for one sender.
+pub const DEFAULT_MAX_TRANSFERS_PER_SENDER: usize = 2;
+
+/// Default time after its last request before an unfinished transfer is
+/// discarded.
+pub const DEFAULT_TRANSFER_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Storage for artifacts in transit and their activation.
+///
+/// Nothing written through `write` may take effect before `activate`.
+pub trait ArtifactStage {
+    fn begin(&mut self, transfer_id: &str, target: &str, len: u64) -> Result<(), String>;
+
+    fn write(&mut self, transfer_id: &str, offset: u64, data: &[u8]) -> Result<(), String>;
+
+    /// Makes the fully received and verified artifact effective on `target`.
+    fn activate(&mut self, transfer_id: &str, target: &str) -> Result<(), String>;
+
+    fn discard(&mut self, transfer_id: &str);
+}
+
+/// An [`ArtifactStage`] holding staged and activated artifacts in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStage {
+    staged: HashMap<String, Vec<u8>>,
+    active: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The artifact currently active on `target`.
+    pub fn active(&self, target: &str) -> Option<&[u8]> {
+        self.active.get(target).map(Vec::as_slice)
+    }
+}
+
+impl ArtifactStage for MemoryStage {
+    fn begin(&mut self, transfer_id: &str, _target: &str, _len: u64) -> Result<(), String> {
+        self.staged.insert(transfer_id.to_string(), Vec::new());
+        Ok(())
+    }
+
+    fn write(&mut self, transfer_id: &str, offset: u64, data: &[u8]) -> Result<(), String> {
+        let buf = self
+            .staged
+            .get_mut(transfer_id)
+            .ok_or_else(|| "no staged artifact".to_string())?;
+        if buf.len() as u64 != offset {
+            return Err(format!("stage at {}, write at {offset}", buf.len()));
+        }
+        buf.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn activate(&mut self, transfer_id: &str, target: &str) -> Result<(), String> {
+        let buf = self
+            .staged
+            .remove(transfer_id)
+            .ok_or_else(|| "no staged artifact".to_string())?;
+        self.active.insert(target.to_string(), buf);
+        Ok(())
+    }
+
+    fn discard(&mut self, transfer_id: &str) {
+        self.staged.remove(transfer_id);
+    }
+}
+
+struct Transfer {
+    owner: DeviceId,
+    target: String,
+    artifact_len: u64,
+    artifact_sha256: [u8; 32],
+    received: u64,
+    hasher: Sha256,
+    /// SHA-256 of each accepted chunk by offset, to acknowledge retransmits.
+    chunks: BTreeMap<u64, [u8; 32]>,
+    last_active: Instant,
+}
+
+/// Receiving side of the chunked engineering transfer flow (see [`action`]).
+///
+/// Chunks must arrive in order; a sender resumes an interrupted transfer by
+/// asking for `transfer.status` and continuing from `next_offset`.
+/// Retransmitted chunks that match an accepted chunk are acknowledged without
+/// being written twice. A transfer belongs to the sender that began it.
+///
+/// The number of transfers in flight is capped per sender and overall, and a
+/// transfer with no request from its owner for the idle timeout is discarded.
+/// Time is taken from [`VerifiedContext::received_at`].
+///
+/// Requests with other actions are passed to the fallback handler, if any.
+pub struct TransferManager<S: ArtifactStage> {
+    stage: S,
+    transfers: HashMap<String, Transfer>,
+    max_chunk_len: usize,
+    max_artifact_len: u64,
+    max_transfers: usize,
+    max_transfers_per_sender: usize,
+    idle_timeout: Duration,
+    fallback: Option<Box<dyn EngineeringHandler>>,
+}
+
+impl<S: ArtifactStage> TransferManager<S> {
+    pub fn new(stage: S) -> Self {
+        Self {
+            stage,
+            transfers: HashMap::new(),
+            max_chunk_len: DEFAULT_MAX_CHUNK_LEN,
+            max_artifact_len: DEFAULT_MAX_ARTIFACT_LEN,
+            max_transfers: DEFAULT_MAX_TRANSFERS,
+            max_transfers_per_sender: DEFAULT_MAX_TRANSFERS_PER_SENDER,
+            idle_timeout: DEFAULT_TRANSFER_IDLE_TIMEOUT,
+            fallback: None,
+        }
+    }
+
+    pub fn with_max_chunk_len(mut self, len: usize) -> Self {
+        self.max_chunk_len = len;
+        self
+    }
+
+    pub fn with_max_artifact_len(mut self, len: u64) -> Self {
+        self.max_artifact_len = len;
+        self
+    }
+
+    pub fn with_max_transfers(mut self, max: usize) -> Self {
+        self.max_transfers = max;
+        self
+    }
+
+    pub fn with_max_transfers_per_sender(mut self, max: usize) -> Self {
+        self.max_transfers_per_sender = max;
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub fn with_fallback(mut self, handler: impl EngineeringHandler + 'static) -> Self {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    pub fn stage(&self) -> &S {
+        &self.stage
+    }
+
+    /// Number of transfers in flight.
+    pub fn in_flight(&self) -> usize {
+        self.transfers.len()
+    }
+
+    /// Discards every transfer idle for the idle timeout as of `now`.
+    ///
+    /// Runs before each transfer request; call it periodically to release
+    /// staged data when no requests arrive.
+    pub fn expire_idle(&mut self, now: Instant) {
+        let stage = &mut self.stage;
+        let timeout = self.idle_timeout;
+        self.transfers.retain(|id, t| {
+            let live = now.saturating_duration_since(t.last_active) < timeout;
+            if !live {
+                stage.discard(id);
+            }
+            live
+        });
+    }
+
+    fn begin(&mut self, ctx: &VerifiedContext, req: &EngineeringRequest, id: &str) -> Outcome {
+        if self.transfers.contains_key(id) {
+            return Outcome::rejected(id, "transfer_id already in progress");
+        }
+        let Some(artifact_len) = param_u64(req, "artifact_len") else {
+            return Outcome::rejected(id, "missing or invalid artifact_len");
+        };
+        let Some(artifact_sha256) = param_hash(req, "artifact_sha256") else {
+            return Outcome::rejected(id, "missing or invalid artifact_sha256");
+        };
+        if artifact_len == 0 || artifact_len > self.max_artifact_len {
+            return Outcome::rejected(
+                id,
+                &format!("artifact_len must be in 1..={}", self.max_artifact_len),
+            );
+        }
+        let owned = self
+            .transfers
+            .values()
+            .filter(|t| &t.owner == ctx.sender_id())
+            .count();
+        if owned >= self.max_transfers_per_sender {
+            return Outcome::rejected(id, "too many transfers in flight for this sender");
+        }
+        if self.transfers.len() >= self.max_transfers {
+            return Outcome::rejected(id, "too many transfers in flight");
+        }
+        if let Err(e) = self.stage.begin(id, &req.target, artifact_len) {
+            return Outcome::failed(id, &e);
+        }
+
+        self.transfers.insert(
+            id.to_string(),
+            Transfer {
+                owner: ctx.sender_id().clone(),
+                target: req.target.clone(),
+                artifact_len,
+                artifact_sha256,
+                received: 0,
+                hasher: Sha256::new(),
+                chunks: BTreeMap::new(),
+                last_active: ctx.received_at(),
+            },
+        );
+        Outcome::progress(id, 0, ResultStatus::Completed, "transfer started")
+    }
+
+    fn chunk(&mut self, req: &EngineeringRequest, t: &mut Transfer, id: &str) -> Outcome {
+        let (Some(offset), Some(chunk_sha256)) =
+            (param_u64(req, "offset"), param_hash(req, "chunk_sha256"))
+        else {
+            return Outcome::rejected(id, "missing or invalid offset or chunk_sha256");
+        };
+        if req.blob.is_empty() || req.blob.len() > self.max_chunk_len {
+            return Outcome::rejected(
+                id,
+                &format!("chunk length must be in 1..={}", self.max_chunk_len),
+            );
+        }
+        if sha256(&req.blob) != chunk_sha256 {
+            return Outcome::rejected(id, "chunk_sha256 mismatch");
+        }
+
+        if offset < t.received {
+            return match t.chunks.get(&offset) {
+                Some(h) if *h == chunk_sha256 => {
+                    Outcome::progress(id, t.received, ResultStatus::Completed, "duplicate chunk")
+                }
+                _ => Outcome::rejected(id, "chunk conflicts with received data"),
+            };
+        }
+        if offset != t.received {
+            return Outcome::rejected(id, &format!("expected offset {}", t.received));
+        }
+        let end = offset + req.blob.len() as u64;
+        if end > t.artifact_len {
+            return Outcome::rejected(id, "chunk exceeds artifact_len");
+        }
+
+        if let Err(e) = self.stage.write(id, offset, &req.blob) {
+            return Outcome::failed(id, &e);
+        }
+        t.hasher.update(&req.blob);
+        t.chunks.insert(offset, chunk_sha256);
+        t.received = end;
+        Outcome::progress(id, end, ResultStatus::Completed, "chunk accepted")
+    }
+
+    fn commit(&mut self, t: Transfer, id: &str) -> Outcome {
+        if t.received != t.artifact_len {
+            let next = t.received;
+            self.transfers.insert(id.to_string(), t);
+            return Outcome::progress(id, next, ResultStatus::Rejected, "artifact incomplete");
+        }
+        if <[u8; 32]>::from(t.hasher.finalize()) != t.artifact_sha256 {
+            // The signed whole-artifact hash does not match: never activate.
+            self.stage.discard(id);
+            return Outcome::rejected(id, "artifact_sha256 mismatch; transfer discarded");
+        }
+        match self.stage.activate(id, &t.target) {
+            Ok(()) => Outcome::progress(id, t.artifact_len, ResultStatus::Applied, "activated"),
+            Err(e) => {
+                self.stage.discard(id);
+                Outcome::failed(id, &e)
+            }
+        }
+    }
+
+    fn transfer(&mut self, ctx: &VerifiedContext, req: &EngineeringRequest) -> Outcome {
+        let Some(id) = req.params.get("transfer_id").filter(|s| !s.is_empty()) else {
+            return Outcome::rejected("", "missing transfer_id");
+        };
+        self.expire_idle(ctx.received_at());
+        if req.action == action::BEGIN {
+            return self.begin(ctx, req, id);
+        }
+
+        let Some(mut t) = self.transfers.remove(id) else {
+            return Outcome::rejected(id, "unknown transfer_id");
+        };
+        if &t.owner != ctx.sender_id() {
+            self.transfers.insert(id.clone(), t);
+            return Outcome::rejected(id, "transfer belongs to another sender");
+        }
+        t.last_active = ctx.received_at();
+
+        match req.action.as_str() {
+            action::CHUNK => {
+                let out = self.chunk(req, &mut t, id);
+                self.transfers.insert(id.clone(), t);
+                out
+            }
+            action::STATUS => {
+                let out = Outcome::progress(id, t.received, ResultStatus::Completed, "in progress");
+                self.transfers.insert(id.clone(), t);
+                out
+            }
+            action::COMMIT => self.commit(t, id),
+            // action::ABORT
+            _ => {
+                self.stage.discard(id);
+                Outcome::progress(id, t.received, ResultStatus::Completed, "aborted")
+            }
+        }
+    }
+}
+
+impl<S: ArtifactStage> EngineeringHandler for TransferManager<S> {
+    fn handle_engineering(
+        &mut self,
+        ctx: &VerifiedContext,
+        req: &EngineeringRequest,
+    ) -> EngineeringResult {
+        match req.action.as_str() {
+            action::BEGIN | action::CHUNK | action::STATUS | action::COMMIT | action::ABORT => {
+                self.transfer(ctx, req).into_result()
+            }
+            _ => match self.fallback.as_mut() {
+                Some(handler) => handler.handle_engineering(ctx, req),
+                None => EngineeringResult {
+                    status: ResultStatus::Rejected,
+                    detail: format!("unsupported action {}", req.action),
+                    outputs: BTreeMap::new(),
+                    blob: Vec::new(),
+                },
+            },
+        }
+    }
+}
+
+/// Splits `artifact` into the `transfer.begin`, `transfer.chunk` and
+/// `transfer.commit` requests a sender issues, in order.
+pub fn transfer_requests(
+    transfer_id: &str,
+    target: &str,
+    artifact: &[u8],
+    chunk_len: usize,
+) -> Vec<EngineeringRequest> {
+    let request = |action: &str, params: &[(&str, String)], blob: &[u8]| {
+        let mut p = BTreeMap::new();
+        p.insert("transfer_id".to_string(), transfer_id.to_string());
+        for (k, v) in params {
+            p.insert(k.to_string(), v.clone());
+        }
+        EngineeringRequest {
+            request_id: format!(
+                "{transfer_id}:{action}:{}",
+                p.get("offset").map_or("", String::as_str)
+            ),
+            action: action.to_string(),
+            target: target.to_string(),
+            params: p,
+            blob: blob.to_vec(),
+            requires_confirmation: false,
+        }
+    };
+
+    let mut out = vec![request(
+        action::BEGIN,
+        &[
+            ("artifact_len", artifact.len().to_string()),
+            ("artifact_sha256", hex::encode(&sha256(artifact))),
+        ],
+        &[],
+    )];
+    let mut offset = 0;
+    for chunk in artifact.chunks(chunk_len.max(1)) {
+        out.push(request(
+            action::CHUNK,
+            &[
+                ("offset", offset.to_string()),
+                ("chunk_sha256", hex::encode(&sha256(chunk))),
+            ],
+            chunk,
+        ));
+        offset += chunk.len();
+    }
+    out.push(request(action::COMMIT, &[], &[]));
+    out
+}
+
+struct Outcome {
+    status: ResultStatus,
+    detail: String,
+    outputs: BTreeMap<String, String>,
+}
+
+impl Outcome {
+    fn progress(id: &str, next_offset: u64, status: ResultStatus, detail: &str) -> Self {
+        let mut outputs = BTreeMap::new();
+        outputs.insert("transfer_id".to_string(), id.to_string());
+        outputs.insert("next_offset".to_string(), next_offset.to_string());
+        Self {
+            status,
+            detail: detail.to_string(),
+            outputs,
+        }
+    }
+
+    fn rejected(id: &str, detail: &str) -> Self {
+        Self::ended(id, ResultStatus::Rejected, detail)
+    }
+
+    fn failed(id: &str, detail: &str) -> Self {
+        Self::ended(id, ResultStatus::Failed, detail)
+    }
+
+    fn ended(id: &str, status: ResultStatus, detail: &str) -> Self {
+        let mut outputs = BTreeMap::new();
+        outputs.insert("transfer_id".to_string(), id.to_string());
+        Self {
+            status,
+            detail: detail.to_string(),
+            outputs,
+        }
+    }
+
+    fn into_result(self) -> EngineeringResult {
+        EngineeringResult {
+            status: self.status,
+            detail: self.detail,
+            outputs: self.outputs,
+            blob: Vec::new(),
+        }
+    }
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+fn param_u64(req: &EngineeringRequest, key: &str) -> Option<u64> {
+    req.params.get(key)?.parse().ok()
+}
+
+fn param_hash(req: &EngineeringRequest, key: &str) -> Option<[u8; 32]> {
+    let mut out = [0u8; 32];
+    hex::decode_into(req.params.get(key)?, &mut out).ok()?;
+    Some(out)
+}
@@ -0,0 +1,108 @@
+use std::time::Instant;
+
+use crate::envelope::{
+    CommandRequest, ConfigQuery, ConfigSnapshot, ConfigUpdate, EngineeringRequest,
+    EngineeringResult, OpResult, Payload, TelemetryPayload, VerifiedEnvelope,
+};
+use crate::ids::{DeviceId, IdempotencyKey, InstanceId, TransactionId};
+
+/// Facts about an inbound envelope that have been established by the ingress
+/// pipeline before any handler runs.
+///
+/// A `VerifiedContext` is only constructed by the runtime after structural,
+/// freshness, signature, replay and authorization validation all succeeded,
+/// so handlers can rely on the sender identity and capability it carries.
+#[derive(Clone, Debug)]
+pub struct VerifiedContext {
+    envelope: VerifiedEnvelope,
+    received_at: Instant,
+}
+
+impl VerifiedContext {
+    pub(crate) fn new(envelope: VerifiedEnvelope, received_at: Instant) -> Self {
+        Self {
+            envelope,
+            received_at,
+        }
+    }
+
+    pub(crate) fn into_envelope(self) -> VerifiedEnvelope {
+        self.envelope
+    }
+
+    /// The verified envelope the handler is running for.
+    pub fn envelope(&self) -> &VerifiedEnvelope {
+        &self.envelope
+    }
+
+    pub fn sender_id(&self) -> &DeviceId {
+        &self.envelope.sender_id
+    }
+    pub fn sender_instance(&self) -> &InstanceId {
+        &self.envelope.sender_instance
+    }
+    pub fn key_id(&self) -> &str {
+        &self.envelope.key_id
+    }
+    pub fn counter(&self) -> u64 {
+        self.envelope.counter
+    }
+    pub fn transaction_id(&self) -> &TransactionId {
+        &self.envelope.transaction_id
+    }
+    pub fn idempotency_key(&self) -> &IdempotencyKey {
+        &self.envelope.idempotency_key
+    }
+    /// The signed `auth_context` the request was authorized under.
+    pub fn capability(&self) -> &[u8] {
+        &self.envelope.auth_context
+    }
+    pub fn topic(&self) -> &str {
+        &self.envelope.topic
+    }
+    pub fn target(&self) -> &str {
+        &self.envelope.target
+    }
+    pub fn scope(&self) -> &str {
+        &self.envelope.scope
+    }
+    /// When the envelope was first observed, as passed to
+    /// [`crate::runtime::Runtime::process`].
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
+}
+
+/// Local authorization policy evaluated before semantic execution (INV-AUTH-004).
+pub trait Authorizer {
+    fn authorize(&self, ctx: &VerifiedContext, payload: &Payload) -> bool;
+}
+
+/// Executes `CommandRequest`s.
+///
+/// Once the handler returns, the runtime answers with a `Received` ack
+/// followed by the returned `OpResult`, in that transmission order.
+pub trait CommandHandler {
+    fn handle_command(&mut self, ctx: &VerifiedContext, req: &CommandRequest) -> OpResult;
+}
+
+/// Serves `ConfigQuery` and applies `ConfigUpdate` messages.
+pub trait ConfigHandler {
+    fn query(&mut self, ctx: &VerifiedContext, query: &ConfigQuery) -> ConfigSnapshot;
+
+    fn update(&mut self, ctx: &VerifiedContext, update: &ConfigUpdate) -> OpResult;
+}
+
+/// Executes `EngineeringRequest`s.
+pub trait EngineeringHandler {
+    fn handle_engineering(
+        &mut self,
+        ctx: &VerifiedContext,
+        req: &EngineeringRequest,
+    ) -> EngineeringResult;
+}
+
+/// Receives verified telemetry. Telemetry is never answered.
+pub trait TelemetrySink {
+    fn on_telemetry(&mut self, ctx: &VerifiedContext, telemetry: &TelemetryPayload);
+}
@@ -0,0 +1,179 @@
+pub mod attestation;
+pub mod enrollment;
+
+use std::collections::BTreeMap;
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::crypto::ed25519::is_torsion_free_key;
+use crate::error::TrustError;
+use crate::ids::DeviceId;
+use crate::trust::attestation::{AttestationResult, EnrollmentRequest};
+
+/// Approval status of a key in the trust registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyStatus {
+    Approved,
+    Revoked,
+}
+
+/// A single `key_id` binding held by the site trust registry.
+#[derive(Clone, Debug)]
+pub struct TrustEntry {
+    pub sender_id: DeviceId,
+    pub verifying_key: VerifyingKey,
+    pub status: KeyStatus,
+    pub metadata: ApprovalMetadata,
+}
+
+/// Evidence recorded with an approval for audit (key-management.md,
+/// "Persistence requirements"). None of it is consulted when resolving keys.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApprovalMetadata {
+    pub attestation: AttestationResult,
+}
+
+/// Local trust registry state: `key_id` → approved public key.
+///
+/// Only keys that were explicitly approved resolve; unknown and revoked keys
+/// are rejected (key-management.md).
+#[derive(Clone, Debug, Default)]
+pub struct TrustRegistry {
+    entries: BTreeMap<String, TrustEntry>,
+}
+
+impl TrustRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Approves `verifying_key` under `key_id` for `sender_id`.
+    ///
+    /// A `key_id` MUST NOT refer to multiple distinct public keys, so
+    /// re-approving an existing `key_id` with a different key (or for a
+    /// different sender) is refused. Revoked keys cannot be re-approved.
+    /// Small-order and non-canonically encoded keys are refused, since strict
+    /// verification could never accept a signature under them.
+    pub fn approve(
+        &mut self,
+        key_id: &str,
+        sender_id: DeviceId,
+        verifying_key: VerifyingKey,
+    ) -> Result<(), TrustError> {
+        self.approve_with_metadata(
+            key_id,
+            sender_id,
+            verifying_key,
+            ApprovalMetadata::default(),
+        )
+    }
+
+    /// Approves the key proposed by an enrollment request, recording the
+    /// outcome of [`attestation::attest`] with it.
+    ///
+    /// The attestation result is metadata only: it is stored whatever it says
+    /// and the approval is subject to the same checks as [`Self::approve`].
+    pub fn approve_enrollment(
+        &mut self,
+        request: &EnrollmentRequest,
+        attestation: AttestationResult,
+    ) -> Result<(), TrustError> {
+        self.approve_with_metadata(
+            &request.key_id,
+            request.sender_id.clone(),
+            request.verifying_key,
+            ApprovalMetadata { attestation },
+        )
+    }
+
+    /// [`Self::approve`] with explicit approval metadata. Re-approving an
+    /// existing binding replaces its metadata, so the registry records the
+    /// attestation of the latest approval.
+    pub fn approve_with_metadata(
+        &mut self,
+        key_id: &str,
+        sender_id: DeviceId,
+        verifying_key: VerifyingKey,
+        metadata: ApprovalMetadata,
+    ) -> Result<(), TrustError> {
+        if key_id.is_empty() {
+            return Err(TrustError::EmptyKeyId);
+        }
+        if verifying_key.to_edwards().compress().as_bytes() != verifying_key.as_bytes() {
+            return Err(TrustError::NonCanonicalKey {
+                key_id: key_id.to_string(),
+            });
+        }
+        if !is_torsion_free_key(&verifying_key) {
+            return Err(TrustError::WeakKey {
+                key_id: key_id.to_string(),
+            });
+        }
+        if let Some(existing) = self.entries.get_mut(key_id) {
+            if existing.status == KeyStatus::Revoked {
+                return Err(TrustError::Revoked {
+                    key_id: key_id.to_string(),
+                });
+            }
+            if existing.verifying_key != verifying_key || existing.sender_id != sender_id {
+                return Err(TrustError::KeyIdConflict {
+                    key_id: key_id.to_string(),
+                });
+            }
+            existing.metadata = metadata;
+            return Ok(());
+        }
+
+        self.entries.insert(
+            key_id.to_string(),
+            TrustEntry {
+                sender_id,
+                verifying_key,
+                status: KeyStatus::Approved,
+                metadata,
+            },
+        );
+        Ok(())
+    }
+
+    /// Revokes `key_id`. Revocation is permanent for the lifetime of the registry.
+    pub fn revoke(&mut self, key_id: &str) -> Result<(), TrustError> {
+        let entry = self
+            .entries
+            .get_mut(key_id)
+            .ok_or_else(|| TrustError::UnknownKeyId {
+                key_id: key_id.to_string(),
+            })?;
+        entry.status = KeyStatus::Revoked;
+        Ok(())
+    }
+
+    pub fn get(&self, key_id: &str) -> Option<&TrustEntry> {
+        self.entries.get(key_id)
+    }
+
+    /// Resolves the verification key for an envelope's `key_id`.
+    ///
+    /// The key must be approved and bound to `sender_id`; a key enrolled for
+    /// one endpoint never authenticates messages claiming another identity.
+    pub fn resolve(&self, key_id: &str, sender_id: &DeviceId) -> Result<&VerifyingKey, TrustError> {
+        let entry = self
+            .entries
+            .get(key_id)
+            .ok_or_else(|| TrustError::UnknownKeyId {
+                key_id: key_id.to_string(),
+            })?;
+        if entry.status == KeyStatus::Revoked {
+            return Err(TrustError::Revoked {
+                key_id: key_id.to_string(),
+            });
+        }
+        if &entry.sender_id != sender_id {
+            return Err(TrustError::SenderMismatch {
+                key_id: key_id.to_string(),
+                sender_id: sender_id.clone(),
+            });
+        }
+        Ok(&entry.verifying_key)
+    }
+}
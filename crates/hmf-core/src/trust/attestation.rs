@@ -0,0 +1,115 @@
+//! Optional manufacturer attestation (key-management.md, "Optional
+//! manufacturer attestation").
+//!
+//! The authority runs [`attest`] over the evidence attached to an enrollment
+//! request and records the [`AttestationResult`] with the approval. A
+//! verified attestation never approves a key by itself; approval remains an
+//! explicit [`super::TrustRegistry::approve_enrollment`] call.
+
+#[cfg(feature = "x509")]
+pub mod x509;
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::error::AttestationError;
+use crate::ids::{DeviceId, InstanceId};
+
+/// Manufacturer evidence attached to an enrollment request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttestationEvidence {
+    /// DER certificates, device certificate first, followed by any
+    /// intermediates. The manufacturer root is configured at the verifier.
+    X509Chain(Vec<Vec<u8>>),
+    /// Evidence in another format, such as a TPM quote or secure element
+    /// proof, for verifiers outside this crate.
+    Other { format: String, data: Vec<u8> },
+}
+
+impl AttestationEvidence {
+    pub fn format(&self) -> &str {
+        match self {
+            Self::X509Chain(_) => "x509-chain",
+            Self::Other { format, .. } => format,
+        }
+    }
+}
+
+/// The fields of an enrollment request (key-management.md, "Enrollment").
+#[derive(Clone, Debug)]
+pub struct EnrollmentRequest {
+    pub sender_id: DeviceId,
+    pub sender_instance: InstanceId,
+    pub key_id: String,
+    pub verifying_key: VerifyingKey,
+    pub attestation: Option<AttestationEvidence>,
+}
+
+/// Outcome of attestation for one enrollment, kept as approval metadata.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AttestationResult {
+    /// The request carried no evidence.
+    #[default]
+    NotProvided,
+    /// No configured verifier handles the evidence format.
+    Unsupported {
+        format: String,
+    },
+    Verified {
+        verifier: String,
+        detail: String,
+    },
+    Failed {
+        verifier: String,
+        reason: String,
+    },
+}
+
+/// Checks one kind of manufacturer evidence.
+pub trait AttestationVerifier {
+    /// Name recorded with the result.
+    fn name(&self) -> &str;
+
+    /// Checks that `evidence` is valid and certifies the public key proposed
+    /// by `request`, returning a short description of what was verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AttestationError::Unsupported`] for evidence this verifier
+    /// does not handle, and another [`AttestationError`] if the evidence is
+    /// rejected.
+    fn verify(
+        &self,
+        request: &EnrollmentRequest,
+        evidence: &AttestationEvidence,
+    ) -> Result<String, AttestationError>;
+}
+
+/// Runs the first of `verifiers` that handles the evidence in `request`.
+pub fn attest(
+    request: &EnrollmentRequest,
+    verifiers: &[&dyn AttestationVerifier],
+) -> AttestationResult {
+    let Some(evidence) = &request.attestation else {
+        return AttestationResult::NotProvided;
+    };
+    for verifier in verifiers {
+        match verifier.verify(request, evidence) {
+            Ok(detail) => {
+                return AttestationResult::Verified {
+                    verifier: verifier.name().to_string(),
+                    detail,
+                };
+            }
+            Err(AttestationError::Unsupported { .. }) => continue,
+            Err(e) => {
+                return AttestationResult::Failed {
+                    verifier: verifier.name().to_string(),
+                    reason: e.to_string(),
+                };
+            }
+        }
+    }
+    AttestationResult::Unsupported {
+        format: evidence.format().to_string(),
+    }
+}
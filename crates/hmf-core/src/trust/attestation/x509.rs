@@ -0,0 +1,99 @@
+//! X.509 device-certificate chain verifier.
+
+use rustls_pki_types::{CertificateDer, TrustAnchor, UnixTime};
+use webpki::{ALL_VERIFICATION_ALGS, EndEntityCert, KeyUsage};
+
+use crate::error::AttestationError;
+use crate::hex;
+use crate::trust::attestation::{AttestationEvidence, AttestationVerifier, EnrollmentRequest};
+
+/// DER `SubjectPublicKeyInfo` prefix for an Ed25519 key (RFC 8410), followed
+/// by the 32 key bytes.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Verifies [`AttestationEvidence::X509Chain`] against manufacturer roots.
+///
+/// The device certificate must chain to a configured root, be within its
+/// validity period, allow client authentication if it restricts extended key
+/// usage, and carry the enrolled Ed25519 key as its subject public key.
+pub struct X509ChainVerifier {
+    roots: Vec<TrustAnchor<'static>>,
+}
+
+impl X509ChainVerifier {
+    /// Trusts the DER-encoded manufacturer root certificates `roots`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AttestationError::Malformed`] if a root cannot be parsed.
+    pub fn new(roots: &[Vec<u8>]) -> Result<Self, AttestationError> {
+        let roots = roots
+            .iter()
+            .map(|der| {
+                webpki::anchor_from_trusted_cert(&CertificateDer::from(der.as_slice()))
+                    .map(|anchor| anchor.to_owned())
+                    .map_err(|e| AttestationError::Malformed {
+                        reason: format!("root certificate: {e}"),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { roots })
+    }
+}
+
+impl AttestationVerifier for X509ChainVerifier {
+    fn name(&self) -> &str {
+        "x509-chain"
+    }
+
+    fn verify(
+        &self,
+        request: &EnrollmentRequest,
+        evidence: &AttestationEvidence,
+    ) -> Result<String, AttestationError> {
+        let AttestationEvidence::X509Chain(chain) = evidence else {
+            return Err(AttestationError::Unsupported {
+                format: evidence.format().to_string(),
+            });
+        };
+        let [device, intermediates @ ..] = chain.as_slice() else {
+            return Err(AttestationError::Malformed {
+                reason: "empty certificate chain".to_string(),
+            });
+        };
+
+        let device = CertificateDer::from(device.as_slice());
+        let intermediates = intermediates
+            .iter()
+            .map(|der| CertificateDer::from(der.as_slice()))
+            .collect::<Vec<_>>();
+        let cert = EndEntityCert::try_from(&device).map_err(|e| AttestationError::Malformed {
+            reason: format!("device certificate: {e}"),
+        })?;
+        cert.verify_for_usage(
+            ALL_VERIFICATION_ALGS,
+            &self.roots,
+            &intermediates,
+            UnixTime::now(),
+            KeyUsage::client_auth(),
+            None,
+            None,
+        )
+        .map_err(|e| AttestationError::Chain {
+            reason: e.to_string(),
+        })?;
+
+        let mut spki = ED25519_SPKI_PREFIX.to_vec();
+        spki.extend_from_slice(request.verifying_key.as_bytes());
+        if cert.subject_public_key_info().as_ref() != spki.as_slice() {
+            return Err(AttestationError::KeyMismatch);
+        }
+
+        Ok(format!(
+            "device certificate serial {}",
+            hex::encode(cert.serial())
+        ))
+    }
+}
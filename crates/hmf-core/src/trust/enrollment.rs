@@ -0,0 +1,165 @@
+//! Signed enrollment requests (key-management.md, "Enrollment").
+//!
+//! An endpoint signs its [`EnrollmentRequest`] with the proposed key, which
+//! proves it holds the private half. The encoded request is its signing
+//! bytes followed by the 64-byte signature.
+//!
+//! Signing bytes start with the length-prefixed `HMFv1:enrollment-request`
+//! domain tag, so they can never be taken for envelope signing bytes, then
+//! carry `sender_id`, `sender_instance` and `key_id` as length-prefixed
+//! UTF-8, the 32-byte public key, and the evidence: a kind byte (0 none,
+//! 1 X.509 chain, 2 other) followed by the certificate count and each
+//! length-prefixed certificate, or by the length-prefixed format and data.
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::crypto::ed25519::{self, SIGNATURE_LEN};
+use crate::crypto::signer::Signer;
+use crate::envelope::signing_bytes::{put_bytes, put_str, put_u8, put_u32};
+use crate::error::EnrollmentError;
+use crate::ids::{DeviceId, InstanceId};
+use crate::trust::attestation::{AttestationEvidence, EnrollmentRequest};
+
+const DOMAIN_TAG: &[u8] = b"HMFv1:enrollment-request";
+
+const EVIDENCE_NONE: u8 = 0;
+const EVIDENCE_X509_CHAIN: u8 = 1;
+const EVIDENCE_OTHER: u8 = 2;
+
+/// Signing bytes of `request`, as described in the module docs.
+pub fn enrollment_signing_bytes(request: &EnrollmentRequest) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(256);
+    put_bytes(&mut buf, DOMAIN_TAG);
+    put_str(&mut buf, request.sender_id.as_str());
+    put_str(&mut buf, request.sender_instance.as_str());
+    put_str(&mut buf, &request.key_id);
+    buf.extend_from_slice(request.verifying_key.as_bytes());
+    match &request.attestation {
+        None => put_u8(&mut buf, EVIDENCE_NONE),
+        Some(AttestationEvidence::X509Chain(chain)) => {
+            put_u8(&mut buf, EVIDENCE_X509_CHAIN);
+            put_u32(&mut buf, chain.len() as u32);
+            for der in chain {
+                put_bytes(&mut buf, der);
+            }
+        }
+        Some(AttestationEvidence::Other { format, data }) => {
+            put_u8(&mut buf, EVIDENCE_OTHER);
+            put_str(&mut buf, format);
+            put_bytes(&mut buf, data);
+        }
+    }
+    buf
+}
+
+/// Signs `request` with `signer` and returns the encoded request.
+///
+/// `signer` must hold the key the request proposes. A signing process behind
+/// [`crate::crypto::signer::socket`] only signs envelopes and refuses this.
+///
+/// # Errors
+///
+/// Returns [`EnrollmentError::KeyMismatch`] if `signer` holds another key and
+/// [`EnrollmentError::Sign`] if signing fails.
+pub fn sign_request(
+    request: &EnrollmentRequest,
+    signer: &dyn Signer,
+) -> Result<Vec<u8>, EnrollmentError> {
+    if signer.verifying_key() != request.verifying_key {
+        return Err(EnrollmentError::KeyMismatch);
+    }
+    let mut buf = enrollment_signing_bytes(request);
+    let signature = signer.sign(&buf)?;
+    buf.extend_from_slice(&signature);
+    Ok(buf)
+}
+
+/// Decodes an encoded request and returns it once its signature verifies
+/// under the key it proposes.
+///
+/// # Errors
+///
+/// Returns [`EnrollmentError::Malformed`] if `bytes` is not an encoded
+/// request and [`EnrollmentError::Signature`] if the signature is invalid.
+pub fn verify_request(bytes: &[u8]) -> Result<EnrollmentRequest, EnrollmentError> {
+    let Some(body_len) = bytes.len().checked_sub(SIGNATURE_LEN) else {
+        return Err(malformed("shorter than a signature"));
+    };
+    let (body, signature) = bytes.split_at(body_len);
+
+    let mut r = Reader(body);
+    if r.bytes()? != DOMAIN_TAG {
+        return Err(malformed("not an enrollment request"));
+    }
+    let sender_id = DeviceId::new(r.str()?);
+    let sender_instance = InstanceId::new(r.str()?);
+    let key_id = r.str()?;
+    let key: [u8; 32] = r.take(32)?.try_into().expect("32 bytes");
+    let verifying_key =
+        VerifyingKey::from_bytes(&key).map_err(|_| malformed("invalid public key"))?;
+    let attestation = match r.u8()? {
+        EVIDENCE_NONE => None,
+        EVIDENCE_X509_CHAIN => {
+            let count = r.u32()?;
+            let chain = (0..count)
+                .map(|_| r.bytes().map(<[u8]>::to_vec))
+                .collect::<Result<_, _>>()?;
+            Some(AttestationEvidence::X509Chain(chain))
+        }
+        EVIDENCE_OTHER => Some(AttestationEvidence::Other {
+            format: r.str()?,
+            data: r.bytes()?.to_vec(),
+        }),
+        kind => return Err(malformed(&format!("unknown evidence kind {kind}"))),
+    };
+    if !r.0.is_empty() {
+        return Err(malformed("trailing bytes"));
+    }
+
+    ed25519::verify(&verifying_key, body, signature)?;
+    Ok(EnrollmentRequest {
+        sender_id,
+        sender_instance,
+        key_id,
+        verifying_key,
+        attestation,
+    })
+}
+
+fn malformed(reason: &str) -> EnrollmentError {
+    EnrollmentError::Malformed {
+        reason: reason.to_string(),
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], EnrollmentError> {
+        if self.0.len() < n {
+            return Err(malformed("truncated"));
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, EnrollmentError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, EnrollmentError> {
+        Ok(u32::from_be_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], EnrollmentError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn str(&mut self) -> Result<String, EnrollmentError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| malformed("field is not UTF-8"))
+    }
+}
@@ -0,0 +1,202 @@
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hmf_core::error::{AttestationError, TrustError};
+use hmf_core::ids::{DeviceId, InstanceId};
+use hmf_core::trust::TrustRegistry;
+use hmf_core::trust::attestation::{
+    AttestationEvidence, AttestationResult, AttestationVerifier, EnrollmentRequest, attest,
+};
+
+const KEY_ID: &str = "device-1:ed25519:v1";
+
+fn request(
+    verifying_key: VerifyingKey,
+    attestation: Option<AttestationEvidence>,
+) -> EnrollmentRequest {
+    EnrollmentRequest {
+        sender_id: DeviceId::new("device-1"),
+        sender_instance: InstanceId::new("boot-1"),
+        key_id: KEY_ID.to_string(),
+        verifying_key,
+        attestation,
+    }
+}
+
+/// Accepts any `tpm-quote` evidence.
+struct AcceptQuotes;
+
+impl AttestationVerifier for AcceptQuotes {
+    fn name(&self) -> &str {
+        "tpm"
+    }
+
+    fn verify(
+        &self,
+        _request: &EnrollmentRequest,
+        evidence: &AttestationEvidence,
+    ) -> Result<String, AttestationError> {
+        match evidence.format() {
+            "tpm-quote" => Ok("quote ok".to_string()),
+            format => Err(AttestationError::Unsupported {
+                format: format.to_string(),
+            }),
+        }
+    }
+}
+
+#[test]
+fn attestation_is_recorded_but_never_approves() {
+    let key = SigningKey::from_bytes(&[1; 32]).verifying_key();
+    let quote = AttestationEvidence::Other {
+        format: "tpm-quote".to_string(),
+        data: vec![1, 2, 3],
+    };
+    let enrollment = request(key, Some(quote));
+
+    let result = attest(&enrollment, &[&AcceptQuotes]);
+    assert_eq!(
+        result,
+        AttestationResult::Verified {
+            verifier: "tpm".to_string(),
+            detail: "quote ok".to_string(),
+        }
+    );
+
+    let mut registry = TrustRegistry::new();
+    let sender_id = DeviceId::new("device-1");
+    assert!(matches!(
+        registry.resolve(KEY_ID, &sender_id),
+        Err(TrustError::UnknownKeyId { .. })
+    ));
+    registry
+        .approve_enrollment(&enrollment, result.clone())
+        .unwrap();
+    assert_eq!(registry.resolve(KEY_ID, &sender_id).unwrap(), &key);
+    assert_eq!(registry.get(KEY_ID).unwrap().metadata.attestation, result);
+}
+
+#[test]
+fn re_approval_records_the_latest_attestation() {
+    let key = SigningKey::from_bytes(&[1; 32]).verifying_key();
+    let mut registry = TrustRegistry::new();
+    let enrollment = request(key, None);
+    registry
+        .approve_enrollment(&enrollment, AttestationResult::NotProvided)
+        .unwrap();
+
+    let verified = AttestationResult::Verified {
+        verifier: "tpm".to_string(),
+        detail: "quote ok".to_string(),
+    };
+    registry
+        .approve_enrollment(&enrollment, verified.clone())
+        .unwrap();
+    assert_eq!(registry.get(KEY_ID).unwrap().metadata.attestation, verified);
+}
+
+#[test]
+fn missing_and_unhandled_evidence_are_distinguished() {
+    let key = SigningKey::from_bytes(&[1; 32]).verifying_key();
+    assert_eq!(
+        attest(&request(key, None), &[&AcceptQuotes]),
+        AttestationResult::NotProvided
+    );
+    assert_eq!(
+        attest(
+            &request(key, Some(AttestationEvidence::X509Chain(Vec::new()))),
+            &[&AcceptQuotes]
+        ),
+        AttestationResult::Unsupported {
+            format: "x509-chain".to_string()
+        }
+    );
+}
+
+#[cfg(feature = "x509")]
+mod x509 {
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, KeyPair, PKCS_ED25519};
+
+    use super::*;
+    use hmf_core::trust::attestation::x509::X509ChainVerifier;
+
+    fn ca(name: &str, issuer: Option<(&Certificate, &KeyPair)>) -> (Certificate, KeyPair) {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let cert = match issuer {
+            Some((cert, issuer_key)) => params.signed_by(&key, cert, issuer_key),
+            None => params.self_signed(&key),
+        };
+        (cert.unwrap(), key)
+    }
+
+    /// A device certificate for a fresh Ed25519 key, returned with that key.
+    fn device(issuer: &Certificate, issuer_key: &KeyPair) -> (Vec<u8>, VerifyingKey) {
+        let key = KeyPair::generate_for(&PKCS_ED25519).unwrap();
+        let cert = CertificateParams::new(vec!["device-1".to_string()])
+            .unwrap()
+            .signed_by(&key, issuer, issuer_key)
+            .unwrap();
+        let public = key.public_key_raw().try_into().unwrap();
+        (
+            cert.der().to_vec(),
+            VerifyingKey::from_bytes(&public).unwrap(),
+        )
+    }
+
+    fn outcome(verifier: &X509ChainVerifier, enrollment: &EnrollmentRequest) -> AttestationResult {
+        attest(enrollment, &[&AcceptQuotes, verifier])
+    }
+
+    #[test]
+    fn device_chains_to_a_manufacturer_root_are_verified() {
+        let (root, root_key) = ca("manufacturer root", None);
+        let (intermediate, intermediate_key) = ca("manufacturer line", Some((&root, &root_key)));
+        let (device_cert, key) = device(&intermediate, &intermediate_key);
+        let verifier = X509ChainVerifier::new(&[root.der().to_vec()]).unwrap();
+
+        let chain = vec![device_cert, intermediate.der().to_vec()];
+        let result = outcome(
+            &verifier,
+            &request(key, Some(AttestationEvidence::X509Chain(chain))),
+        );
+        assert!(matches!(
+            result,
+            AttestationResult::Verified { verifier, detail }
+                if verifier == "x509-chain" && detail.starts_with("device certificate serial")
+        ));
+    }
+
+    #[test]
+    fn untrusted_chains_and_other_keys_fail() {
+        let (root, root_key) = ca("manufacturer root", None);
+        let (other_root, other_key) = ca("someone else", None);
+        let verifier = X509ChainVerifier::new(&[root.der().to_vec()]).unwrap();
+
+        let (untrusted, key) = device(&other_root, &other_key);
+        let result = outcome(
+            &verifier,
+            &request(key, Some(AttestationEvidence::X509Chain(vec![untrusted]))),
+        );
+        assert!(
+            matches!(result, AttestationResult::Failed { reason, .. } if reason.contains("chain"))
+        );
+
+        // A genuine certificate for a different key does not vouch for this one.
+        let (genuine, _) = device(&root, &root_key);
+        let enrolled = SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let result = outcome(
+            &verifier,
+            &request(
+                enrolled,
+                Some(AttestationEvidence::X509Chain(vec![genuine])),
+            ),
+        );
+        assert_eq!(
+            result,
+            AttestationResult::Failed {
+                verifier: "x509-chain".to_string(),
+                reason: AttestationError::KeyMismatch.to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hmf_core::Envelope;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::sign::verify_envelopes_ed25519;
+use hmf_core::envelope::{
+    Health, LifecycleHeartbeat, Payload, Telemetry, TelemetryPayload, VerifiedEnvelope,
+};
+use hmf_core::error::{IngressError, TrustError, VerifyError};
+use hmf_core::ids::DeviceId;
+use hmf_core::trust::TrustRegistry;
+
+fn key(device: u8) -> SigningKey {
+    SigningKey::from_bytes(&[device; 32])
+}
+
+fn heartbeats(device: u8, n: usize) -> Vec<Envelope> {
+    let name = format!("device-{device}");
+    let mut endpoint = EndpointContext::new(
+        DeviceId::new(&name),
+        &format!("{name}:ed25519:v1"),
+        key(device),
+    );
+    (0..n)
+        .map(|i| {
+            let payload = Payload::Telemetry(Telemetry {
+                payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+                    uptime_ms: i as u64,
+                    health: Health::Ok,
+                })),
+            });
+            endpoint
+                .send(payload, Routing::new("hb", "warden", "site"))
+                .unwrap()
+        })
+        .collect()
+}
+
+fn verify(envs: &[Envelope], key: &VerifyingKey) -> Vec<Result<(), VerifyError>> {
+    let batch = envs.iter().map(|env| (env, key)).collect::<Vec<_>>();
+    verify_envelopes_ed25519(&batch)
+}
+
+#[test]
+fn a_clean_batch_verifies() {
+    let envs = heartbeats(1, 64);
+    let results = verify(&envs, &key(1).verifying_key());
+    assert_eq!(results, vec![Ok(()); 64]);
+    assert!(verify(&[], &key(1).verifying_key()).is_empty());
+}
+
+#[test]
+fn a_failing_batch_pinpoints_the_bad_envelopes() {
+    let mut envs = heartbeats(1, 16);
+    envs[3].signature[32] ^= 1;
+    envs[9].counter += 1;
+    envs[12].signature.truncate(10);
+
+    let results = verify(&envs, &key(1).verifying_key());
+    for (i, result) in results.iter().enumerate() {
+        match i {
+            3 | 9 => assert_eq!(*result, Err(VerifyError::BadSignature), "{i}"),
+            12 => assert!(
+                matches!(result, Err(VerifyError::BadSignatureLen { got: 10, .. })),
+                "{i}"
+            ),
+            _ => assert_eq!(*result, Ok(()), "{i}"),
+        }
+    }
+}
+
+#[test]
+fn verified_batches_resolve_keys_per_sender() {
+    let mut registry = TrustRegistry::new();
+    for device in [1, 2] {
+        registry
+            .approve(
+                &format!("device-{device}:ed25519:v1"),
+                DeviceId::new(format!("device-{device}")),
+                key(device).verifying_key(),
+            )
+            .unwrap();
+    }
+
+    let mut envs = heartbeats(1, 4);
+    envs.extend(heartbeats(2, 4));
+    envs.extend(heartbeats(3, 1));
+    envs[5].scope = "other".to_string();
+    let expected = envs.clone();
+
+    let results = VerifiedEnvelope::verify_batch(envs, &registry, &DecodeLimits::default());
+    assert_eq!(results.len(), 9);
+    for (i, result) in results.into_iter().enumerate() {
+        match i {
+            5 => assert!(matches!(
+                result,
+                Err(IngressError::Signature(VerifyError::BadSignature))
+            )),
+            8 => assert!(matches!(
+                result,
+                Err(IngressError::Trust(TrustError::UnknownKeyId { .. }))
+            )),
+            _ => assert_eq!(result.unwrap().envelope(), &expected[i]),
+        }
+    }
+}
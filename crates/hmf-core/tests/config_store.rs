@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use hmf_core::envelope::ConfigUpdate;
+use hmf_core::error::ConfigError;
+use hmf_core::ids::{DeviceId, TransactionId};
+use hmf_core::runtime::config::{ConfigSchema, ConfigStore, FileJournal, MemoryJournal, ParamType};
+
+fn schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .with_param(
+            "mode",
+            ParamType::OneOf(vec!["auto".into(), "manual".into()]),
+            "auto",
+        )
+        .unwrap()
+        .with_param("setpoint", ParamType::Int { min: 0, max: 100 }, "20")
+        .unwrap()
+}
+
+fn update(update_id: &str, strict: bool, params: &[(&str, &str)]) -> ConfigUpdate {
+    ConfigUpdate {
+        update_id: update_id.to_string(),
+        strict,
+        params: params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+        expected_version: None,
+    }
+}
+
+fn apply(store: &mut ConfigStore, update: &ConfigUpdate) -> Result<u64, ConfigError> {
+    store
+        .apply(
+            update,
+            &DeviceId::new("warden-1"),
+            &TransactionId::new("txn-1"),
+        )
+        .map(|applied| applied.config_version)
+}
+
+fn journal_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("hmf-config-{name}-{}.journal", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn redelivered_update_id_is_reported_once_and_must_match() {
+    let mut store = ConfigStore::open(schema(), MemoryJournal::default()).unwrap();
+    let first = update("upd-1", true, &[("setpoint", "30")]);
+
+    assert_eq!(apply(&mut store, &first).unwrap(), 1);
+    assert_eq!(apply(&mut store, &first).unwrap(), 1);
+    assert_eq!(store.config_version(), 1);
+    assert_eq!(store.history().len(), 1);
+
+    let changed = update("upd-1", true, &[("setpoint", "40")]);
+    assert!(matches!(
+        apply(&mut store, &changed),
+        Err(ConfigError::UpdateIdReused { update_id }) if update_id == "upd-1"
+    ));
+    assert_eq!(store.get("setpoint"), Some("30"));
+    assert_eq!(store.config_version(), 1);
+}
+
+#[test]
+fn expected_version_is_compare_and_set() {
+    let mut store = ConfigStore::open(schema(), MemoryJournal::default()).unwrap();
+
+    let mut initial = update("upd-1", true, &[("setpoint", "30")]);
+    initial.expected_version = Some(0);
+    assert_eq!(apply(&mut store, &initial).unwrap(), 1);
+
+    let mut stale = update("upd-2", true, &[("setpoint", "40")]);
+    stale.expected_version = Some(0);
+    assert!(matches!(
+        apply(&mut store, &stale),
+        Err(ConfigError::VersionConflict {
+            expected: 0,
+            current: 1
+        })
+    ));
+    assert_eq!(store.get("setpoint"), Some("30"));
+
+    stale.expected_version = Some(1);
+    assert_eq!(apply(&mut store, &stale).unwrap(), 2);
+
+    let unconditional = update("upd-3", true, &[("setpoint", "50")]);
+    assert_eq!(apply(&mut store, &unconditional).unwrap(), 3);
+    assert_eq!(store.get("setpoint"), Some("50"));
+}
+
+#[test]
+fn strict_updates_reject_unknown_keys_and_are_atomic() {
+    let mut store = ConfigStore::open(schema(), MemoryJournal::default()).unwrap();
+
+    let strict = update("upd-1", true, &[("setpoint", "30"), ("colour", "red")]);
+    assert!(matches!(
+        apply(&mut store, &strict),
+        Err(ConfigError::UnknownKey { key }) if key == "colour"
+    ));
+    let invalid = update("upd-2", true, &[("mode", "manual"), ("setpoint", "300")]);
+    assert!(matches!(
+        apply(&mut store, &invalid),
+        Err(ConfigError::InvalidValue { key, .. }) if key == "setpoint"
+    ));
+    assert_eq!(store.config_version(), 0);
+    assert_eq!(store.get("mode"), Some("auto"));
+    assert_eq!(store.get("setpoint"), Some("20"));
+
+    let lenient = update("upd-3", false, &[("setpoint", "30"), ("colour", "red")]);
+    assert_eq!(apply(&mut store, &lenient).unwrap(), 1);
+    assert_eq!(store.get("setpoint"), Some("30"));
+    assert_eq!(store.get("colour"), None);
+    assert!(!store.history()[0].params.contains_key("colour"));
+}
+
+#[test]
+fn file_journal_replays_history() {
+    let path = journal_path("replay");
+    let first = update("upd-1", true, &[("mode", "manual")]);
+    let second = update("upd-2", false, &[("setpoint", "55"), ("colour", "red")]);
+    {
+        let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+        assert_eq!(apply(&mut store, &first).unwrap(), 1);
+        assert_eq!(apply(&mut store, &second).unwrap(), 2);
+    }
+
+    let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+    assert_eq!(store.config_version(), 2);
+    assert_eq!(store.get("mode"), Some("manual"));
+    assert_eq!(store.get("setpoint"), Some("55"));
+    assert_eq!(store.history()[1].update_id, "upd-2");
+
+    // Deduplication survives a restart.
+    assert_eq!(apply(&mut store, &first).unwrap(), 1);
+    assert!(matches!(
+        apply(&mut store, &update("upd-2", false, &[("setpoint", "56")])),
+        Err(ConfigError::UpdateIdReused { .. })
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn torn_trailing_record_is_discarded() {
+    let path = journal_path("torn");
+    {
+        let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+        apply(&mut store, &update("upd-1", true, &[("setpoint", "30")])).unwrap();
+    }
+    let intact = std::fs::read(&path).unwrap();
+    let mut torn = intact.clone();
+    torn.extend_from_slice(b"2\tupd-2\ttxn");
+    std::fs::write(&path, &torn).unwrap();
+
+    let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+    assert_eq!(store.config_version(), 1);
+    assert_eq!(std::fs::read(&path).unwrap(), intact);
+
+    assert_eq!(
+        apply(&mut store, &update("upd-2", true, &[("setpoint", "40")])).unwrap(),
+        2
+    );
+    let store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+    assert_eq!(store.config_version(), 2);
+    assert_eq!(store.get("setpoint"), Some("40"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn append_after_a_torn_record_starts_a_new_line() {
+    let path = journal_path("torn-append");
+    let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+    apply(&mut store, &update("upd-1", true, &[("setpoint", "30")])).unwrap();
+
+    // A failed append leaves part of its line behind while the store is open.
+    let mut torn = std::fs::read(&path).unwrap();
+    torn.extend_from_slice(b"2\tupd-x\ttxn");
+    std::fs::write(&path, &torn).unwrap();
+
+    assert_eq!(
+        apply(&mut store, &update("upd-2", true, &[("setpoint", "40")])).unwrap(),
+        2
+    );
+    let store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+    assert_eq!(store.config_version(), 2);
+    assert_eq!(store.history()[1].update_id, "upd-2");
+    assert_eq!(store.get("setpoint"), Some("40"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn corrupt_complete_record_is_an_error() {
+    let path = journal_path("corrupt");
+    {
+        let mut store = ConfigStore::open(schema(), FileJournal::new(&path)).unwrap();
+        apply(&mut store, &update("upd-1", true, &[("setpoint", "30")])).unwrap();
+    }
+    let mut bytes = b"garbage\n".to_vec();
+    bytes.extend_from_slice(&std::fs::read(&path).unwrap());
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        ConfigStore::open(schema(), FileJournal::new(&path)),
+        Err(ConfigError::CorruptJournal { line: 1 })
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+use hmf_core::Envelope;
+use hmf_core::correlation::{Event, Outcome, PendingRequests, Resolution};
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    Ack, AckStatus, Command, CommandPayload, CommandRequest, Config, ConfigPayload, OpResult,
+    Payload, RejectReason, ResultStatus, StateUpdate, Telemetry, TelemetryPayload,
+    VerifiedEnvelope,
+};
+use hmf_core::error::CorrelationError;
+use hmf_core::ids::DeviceId;
+use hmf_core::trust::TrustRegistry;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn endpoint(name: &str, seed: u8) -> EndpointContext {
+    EndpointContext::new(
+        DeviceId::new(name),
+        &format!("{name}:ed25519:v1"),
+        SigningKey::from_bytes(&[seed; 32]),
+    )
+}
+
+fn registry() -> TrustRegistry {
+    let mut registry = TrustRegistry::new();
+    for (name, seed) in [("device-1", 2), ("device-2", 3)] {
+        registry
+            .approve(
+                &format!("{name}:ed25519:v1"),
+                DeviceId::new(name),
+                SigningKey::from_bytes(&[seed; 32]).verifying_key(),
+            )
+            .unwrap();
+    }
+    registry
+}
+
+fn request(request_id: &str) -> Payload {
+    Payload::Command(Command {
+        payload: Some(CommandPayload::Request(CommandRequest {
+            request_id: request_id.to_string(),
+            command: "open".to_string(),
+            target: "valve-1".to_string(),
+            params: BTreeMap::new(),
+            blob: Vec::new(),
+            requires_confirmation: false,
+        })),
+    })
+}
+
+fn ack(status: AckStatus, reason: RejectReason) -> Payload {
+    Payload::Command(Command {
+        payload: Some(CommandPayload::Ack(Ack {
+            status,
+            detail: "d".to_string(),
+            reason,
+        })),
+    })
+}
+
+fn completed() -> OpResult {
+    OpResult {
+        status: ResultStatus::Completed,
+        detail: "done".to_string(),
+    }
+}
+
+/// Sends `payload` from `responder` within the transaction of `request`.
+fn respond(
+    responder: &mut EndpointContext,
+    request: &Envelope,
+    payload: Payload,
+) -> VerifiedEnvelope {
+    let routing = Routing::new(&request.topic, request.sender_id.as_str(), &request.scope)
+        .with_transaction_id(request.transaction_id.clone());
+    let env = responder.send(payload, routing).unwrap();
+    VerifiedEnvelope::verify(env, &registry(), &DecodeLimits::default()).unwrap()
+}
+
+fn send(warden: &mut EndpointContext, request_id: &str) -> Envelope {
+    warden
+        .send(
+            request(request_id),
+            Routing::new("cmd", "device-1", "line-2"),
+        )
+        .unwrap()
+}
+
+#[test]
+fn unanswered_requests_time_out_in_deadline_order() {
+    let mut warden = endpoint("warden-1", 1);
+    let mut device = endpoint("device-1", 2);
+    let now = Instant::now();
+    let mut pending = PendingRequests::new();
+
+    let slow = send(&mut warden, "req-slow");
+    let fast = send(&mut warden, "req-fast");
+    pending
+        .track(&slow, DeviceId::new("device-1"), now, TIMEOUT * 2)
+        .unwrap();
+    pending
+        .track(&fast, DeviceId::new("device-1"), now, TIMEOUT)
+        .unwrap();
+    assert_eq!(pending.next_deadline(), Some(now + TIMEOUT));
+
+    let received = respond(
+        &mut device,
+        &slow,
+        ack(AckStatus::Received, RejectReason::Unspecified),
+    );
+    assert!(matches!(
+        pending.on_response(&received),
+        Some(Event::Acknowledged { request_id, .. }) if request_id == "req-slow"
+    ));
+
+    assert!(
+        pending
+            .expire(now + TIMEOUT - Duration::from_millis(1))
+            .is_empty()
+    );
+    let expired = pending.expire(now + TIMEOUT * 2);
+    assert_eq!(
+        expired,
+        vec![
+            Resolution {
+                transaction_id: fast.transaction_id.clone(),
+                request_id: "req-fast".to_string(),
+                outcome: Outcome::TimedOut {
+                    acknowledged: false
+                },
+            },
+            Resolution {
+                transaction_id: slow.transaction_id.clone(),
+                request_id: "req-slow".to_string(),
+                outcome: Outcome::TimedOut { acknowledged: true },
+            },
+        ]
+    );
+    assert!(pending.is_empty());
+    assert_eq!(pending.next_deadline(), None);
+
+    // A late answer for an expired request matches nothing.
+    let late = respond(
+        &mut device,
+        &fast,
+        Payload::Command(Command {
+            payload: Some(CommandPayload::Result(completed())),
+        }),
+    );
+    assert_eq!(pending.on_response(&late), None);
+}
+
+#[test]
+fn responses_from_another_endpoint_are_ignored() {
+    let mut warden = endpoint("warden-1", 1);
+    let mut device = endpoint("device-1", 2);
+    let mut impostor = endpoint("device-2", 3);
+    let mut pending = PendingRequests::new();
+
+    // The request is routed through a gateway; the device is the responder.
+    let request = warden
+        .send(request("req-1"), Routing::new("cmd", "gateway-1", "line-2"))
+        .unwrap();
+    pending
+        .track(&request, DeviceId::new("device-1"), Instant::now(), TIMEOUT)
+        .unwrap();
+
+    let result = Payload::Command(Command {
+        payload: Some(CommandPayload::Result(completed())),
+    });
+    let forged = respond(&mut impostor, &request, result.clone());
+    assert_eq!(pending.on_response(&forged), None);
+    assert!(pending.is_pending(&request.transaction_id));
+
+    let genuine = respond(&mut device, &request, result);
+    assert_eq!(
+        pending.on_response(&genuine),
+        Some(Event::Resolved(Resolution {
+            transaction_id: request.transaction_id.clone(),
+            request_id: "req-1".to_string(),
+            outcome: Outcome::Completed(completed()),
+        }))
+    );
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn responses_of_another_msg_class_are_ignored() {
+    let mut warden = endpoint("warden-1", 1);
+    let mut device = endpoint("device-1", 2);
+    let mut pending = PendingRequests::new();
+
+    let request = send(&mut warden, "req-1");
+    pending
+        .track(&request, DeviceId::new("device-1"), Instant::now(), TIMEOUT)
+        .unwrap();
+
+    let config_result = respond(
+        &mut device,
+        &request,
+        Payload::Config(Config {
+            payload: Some(ConfigPayload::Result(completed())),
+        }),
+    );
+    assert_eq!(pending.on_response(&config_result), None);
+    assert!(pending.is_pending(&request.transaction_id));
+}
+
+#[test]
+fn rejections_carry_the_structured_reason() {
+    let mut warden = endpoint("warden-1", 1);
+    let mut device = endpoint("device-1", 2);
+    let mut pending = PendingRequests::new();
+
+    let request = send(&mut warden, "req-1");
+    pending
+        .track(&request, DeviceId::new("device-1"), Instant::now(), TIMEOUT)
+        .unwrap();
+
+    let rejected = respond(
+        &mut device,
+        &request,
+        ack(AckStatus::Rejected, RejectReason::Authorization),
+    );
+    let Some(Event::Resolved(resolution)) = pending.on_response(&rejected) else {
+        panic!("expected a resolution");
+    };
+    assert_eq!(
+        resolution.outcome,
+        Outcome::Rejected {
+            reason: RejectReason::Authorization,
+            detail: "d".to_string(),
+        }
+    );
+}
+
+#[test]
+fn duplicate_transactions_and_non_requests_are_refused() {
+    let mut warden = endpoint("warden-1", 1);
+    let mut pending = PendingRequests::new();
+    let now = Instant::now();
+
+    let request = send(&mut warden, "req-1");
+    pending
+        .track(&request, DeviceId::new("device-1"), now, TIMEOUT)
+        .unwrap();
+    assert!(matches!(
+        pending.track(&request, DeviceId::new("device-1"), now, TIMEOUT),
+        Err(CorrelationError::DuplicateTransaction { transaction_id })
+            if transaction_id == request.transaction_id
+    ));
+    assert_eq!(pending.len(), 1);
+
+    let state = warden
+        .send(
+            Payload::Telemetry(Telemetry {
+                payload: Some(TelemetryPayload::State(StateUpdate {
+                    local_seq: 1,
+                    items: Vec::new(),
+                })),
+            }),
+            Routing::new("state", "device-1", "line-2"),
+        )
+        .unwrap();
+    assert!(matches!(
+        pending.track(&state, DeviceId::new("device-1"), now, TIMEOUT),
+        Err(CorrelationError::NotARequest)
+    ));
+}
@@ -0,0 +1,100 @@
+use ed25519_dalek::SigningKey;
+use hmf_core::endpoint::{EndpointContext, OutboundState, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{Payload, StateUpdate, Telemetry, TelemetryPayload, VerifiedEnvelope};
+use hmf_core::error::EndpointError;
+use hmf_core::ids::{DeviceId, InstanceId, TransactionId};
+use hmf_core::trust::TrustRegistry;
+
+const KEY_ID: &str = "device-1:ed25519:v1";
+
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&[2; 32])
+}
+
+fn endpoint() -> EndpointContext {
+    EndpointContext::new(DeviceId::new("device-1"), KEY_ID, key())
+}
+
+fn state() -> Payload {
+    Payload::Telemetry(Telemetry {
+        payload: Some(TelemetryPayload::State(StateUpdate {
+            local_seq: 1,
+            items: Vec::new(),
+        })),
+    })
+}
+
+fn routing() -> Routing {
+    Routing::new("site/state", "warden-1", "site")
+}
+
+#[test]
+fn sends_are_numbered_consecutively_and_signed() {
+    let mut registry = TrustRegistry::new();
+    registry
+        .approve(KEY_ID, DeviceId::new("device-1"), key().verifying_key())
+        .unwrap();
+    let mut endpoint = endpoint();
+
+    for expected in 1..=3 {
+        let env = endpoint.send(state(), routing()).unwrap();
+        assert_eq!(env.counter, expected);
+        assert_eq!(&env.sender_instance, endpoint.sender_instance());
+        assert_eq!(env.key_id, KEY_ID);
+        VerifiedEnvelope::verify(env, &registry, &DecodeLimits::default()).unwrap();
+    }
+    assert_eq!(endpoint.state().counter, 3);
+}
+
+#[test]
+fn routing_controls_transaction_and_delivery_fields() {
+    let mut endpoint = endpoint();
+    let first = endpoint.send(state(), routing()).unwrap();
+    let second = endpoint.send(state(), routing()).unwrap();
+    assert_ne!(first.transaction_id, second.transaction_id);
+    assert_ne!(first.idempotency_key, second.idempotency_key);
+
+    let txn = TransactionId::new("txn-1");
+    let env = endpoint
+        .send(
+            state(),
+            routing().with_transaction_id(txn.clone()).with_ttl_ms(250),
+        )
+        .unwrap();
+    assert_eq!(env.transaction_id, txn);
+    assert_eq!(env.ttl_ms, 250);
+}
+
+#[test]
+fn new_contexts_start_fresh_streams_and_resume_continues_one() {
+    let mut first = endpoint();
+    first.send(state(), routing()).unwrap();
+    let saved = first.state();
+
+    assert_ne!(endpoint().sender_instance(), first.sender_instance());
+
+    let mut resumed = EndpointContext::resume(DeviceId::new("device-1"), KEY_ID, key(), saved);
+    assert_eq!(resumed.sender_instance(), first.sender_instance());
+    assert_eq!(resumed.send(state(), routing()).unwrap().counter, 2);
+}
+
+#[test]
+fn exhausted_counters_are_refused() {
+    let state_at_max = OutboundState {
+        sender_instance: InstanceId::new("boot-1"),
+        counter: u64::MAX,
+    };
+    let mut endpoint = EndpointContext::resume(
+        DeviceId::new("device-1"),
+        KEY_ID,
+        key(),
+        state_at_max.clone(),
+    );
+
+    assert!(matches!(
+        endpoint.send(state(), routing()),
+        Err(EndpointError::CounterExhausted { sender_instance }) if sender_instance == state_at_max.sender_instance
+    ));
+    assert_eq!(endpoint.state(), state_at_max);
+}
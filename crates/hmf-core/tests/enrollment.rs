@@ -0,0 +1,95 @@
+use ed25519_dalek::SigningKey;
+use hmf_core::crypto::signer::InMemorySigner;
+use hmf_core::error::{EnrollmentError, VerifyError};
+use hmf_core::ids::{DeviceId, InstanceId};
+use hmf_core::trust::attestation::{AttestationEvidence, EnrollmentRequest};
+use hmf_core::trust::enrollment::{sign_request, verify_request};
+
+const KEY_ID: &str = "device-1:ed25519:v1";
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn request(attestation: Option<AttestationEvidence>) -> EnrollmentRequest {
+    EnrollmentRequest {
+        sender_id: DeviceId::new("device-1"),
+        sender_instance: InstanceId::new("boot-1"),
+        key_id: KEY_ID.to_string(),
+        verifying_key: key(1).verifying_key(),
+        attestation,
+    }
+}
+
+fn fields(request: &EnrollmentRequest) -> (String, String, String, [u8; 32]) {
+    (
+        request.sender_id.to_string(),
+        request.sender_instance.to_string(),
+        request.key_id.clone(),
+        request.verifying_key.to_bytes(),
+    )
+}
+
+#[test]
+fn signed_requests_round_trip() {
+    let evidence = [
+        None,
+        Some(AttestationEvidence::X509Chain(vec![vec![1, 2], vec![3]])),
+        Some(AttestationEvidence::Other {
+            format: "tpm-quote".to_string(),
+            data: vec![4, 5],
+        }),
+    ];
+    for attestation in evidence {
+        let sent = request(attestation);
+        let bytes = sign_request(&sent, &InMemorySigner::new(KEY_ID, key(1))).unwrap();
+        let received = verify_request(&bytes).unwrap();
+        assert_eq!(fields(&received), fields(&sent));
+        assert_eq!(received.attestation, sent.attestation);
+    }
+}
+
+#[test]
+fn requests_must_be_signed_by_the_proposed_key() {
+    assert!(matches!(
+        sign_request(&request(None), &InMemorySigner::new(KEY_ID, key(2))),
+        Err(EnrollmentError::KeyMismatch)
+    ));
+
+    let bytes = sign_request(&request(None), &InMemorySigner::new(KEY_ID, key(1))).unwrap();
+    // Flip a byte of the proposed sender_instance.
+    let at = bytes.windows(6).position(|w| w == b"boot-1").unwrap();
+    let mut tampered = bytes.clone();
+    tampered[at] ^= 1;
+    assert!(matches!(
+        verify_request(&tampered),
+        Err(EnrollmentError::Signature(VerifyError::BadSignature))
+    ));
+}
+
+#[test]
+fn malformed_requests_are_refused() {
+    let bytes = sign_request(&request(None), &InMemorySigner::new(KEY_ID, key(1))).unwrap();
+    let body_len = bytes.len() - 64;
+
+    let mut truncated = bytes[..body_len - 1].to_vec();
+    truncated.extend_from_slice(&bytes[body_len..]);
+    let mut trailing = bytes[..body_len].to_vec();
+    trailing.push(0);
+    trailing.extend_from_slice(&bytes[body_len..]);
+    let mut envelope_tag = bytes.clone();
+    envelope_tag[4..12].copy_from_slice(b"HMFv1:en");
+    envelope_tag[12..28].copy_from_slice(b"velope-signature");
+
+    for (bytes, reason) in [
+        (&bytes[..10], "shorter than a signature"),
+        (&truncated[..], "truncated"),
+        (&trailing[..], "trailing bytes"),
+        (&envelope_tag[..], "not an enrollment request"),
+    ] {
+        match verify_request(bytes) {
+            Err(EnrollmentError::Malformed { reason: got }) => assert_eq!(got, reason),
+            other => panic!("expected {reason}, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use hmf_core::error::HexError;
+use hmf_core::hex;
+
+#[test]
+fn round_trips_and_accepts_either_case() {
+    let bytes = [0x00, 0x1f, 0xa0, 0xff];
+    assert_eq!(hex::encode(&bytes), "001fa0ff");
+    assert_eq!(hex::decode("001fa0ff").unwrap(), bytes);
+    assert_eq!(hex::decode("001FA0FF").unwrap(), bytes);
+    assert_eq!(hex::decode("").unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    assert_eq!(hex::decode("abc"), Err(HexError::OddLength));
+    assert_eq!(hex::decode("0g"), Err(HexError::InvalidDigit { index: 1 }));
+
+    let mut out = [0u8; 2];
+    assert_eq!(
+        hex::decode_into("00", &mut out),
+        Err(HexError::BadLength {
+            expected: 4,
+            got: 2
+        })
+    );
+    // Multi-byte UTF-8 is never a digit.
+    assert!(matches!(
+        hex::decode_into("é0", &mut [0u8; 1]),
+        Err(HexError::BadLength { .. })
+    ));
+    assert_eq!(
+        hex::decode_into("éé", &mut out),
+        Err(HexError::InvalidDigit { index: 0 })
+    );
+}
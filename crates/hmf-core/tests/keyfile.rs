@@ -0,0 +1,108 @@
+use ed25519_dalek::SigningKey;
+use hmf_core::crypto::keyfile::{KdfParams, KeyFile, KeyProtection, suggested_key_id};
+use hmf_core::crypto::signer::Signer;
+use hmf_core::error::KeyFileError;
+use hmf_core::ids::DeviceId;
+
+const KEY_ID: &str = "device-1:ed25519:v1";
+
+/// Cheap Argon2id costs so tests stay fast; never use these for real keys.
+fn passphrase(p: &str) -> KeyProtection {
+    KeyProtection::passphrase(p).with_kdf_params(KdfParams::new(64, 1, 1))
+}
+
+fn key_file() -> KeyFile {
+    KeyFile::new(KEY_ID, SigningKey::from_bytes(&[1; 32]))
+}
+
+#[test]
+fn sealed_keys_open_with_the_same_protection() {
+    for protection in [passphrase("correct horse"), KeyProtection::kek([9; 32])] {
+        let sealed = key_file().seal(&protection).unwrap();
+        let opened = KeyFile::open(&sealed, &protection).unwrap();
+        assert_eq!(opened.key_id(), KEY_ID);
+        assert_eq!(opened.verifying_key(), key_file().verifying_key());
+        assert!(!sealed.windows(32).any(|w| w == [1; 32]));
+    }
+}
+
+#[test]
+fn wrong_secrets_and_tampering_are_refused() {
+    let sealed = key_file().seal(&passphrase("correct horse")).unwrap();
+    assert!(matches!(
+        KeyFile::open(&sealed, &passphrase("battery staple")),
+        Err(KeyFileError::Decrypt)
+    ));
+    assert!(matches!(
+        KeyFile::open(&sealed, &KeyProtection::kek([9; 32])),
+        Err(KeyFileError::ProtectionMismatch)
+    ));
+
+    // The key_id is authenticated along with the seed.
+    let kek = KeyProtection::kek([9; 32]);
+    let mut sealed = key_file().seal(&kek).unwrap();
+    let at = sealed
+        .windows(KEY_ID.len())
+        .position(|w| w == KEY_ID.as_bytes())
+        .unwrap();
+    sealed[at] = b'D';
+    assert!(matches!(
+        KeyFile::open(&sealed, &kek),
+        Err(KeyFileError::Decrypt)
+    ));
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let kek = KeyProtection::kek([9; 32]);
+    let sealed = key_file().seal(&kek).unwrap();
+
+    assert!(matches!(
+        KeyFile::open(b"not a key file", &kek),
+        Err(KeyFileError::BadMagic)
+    ));
+    assert!(matches!(
+        KeyFile::open(&sealed[..sealed.len() - 1], &kek),
+        Err(KeyFileError::Malformed { .. })
+    ));
+
+    let mut newer = sealed.clone();
+    newer[6] = 2;
+    assert!(matches!(
+        KeyFile::open(&newer, &kek),
+        Err(KeyFileError::UnsupportedVersion { got: 2 })
+    ));
+
+    // Stored Argon2id costs are bounded before any memory is allocated.
+    let mut costly = key_file().seal(&passphrase("p")).unwrap();
+    costly[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(matches!(
+        KeyFile::open(&costly, &passphrase("p")),
+        Err(KeyFileError::KdfParams(_))
+    ));
+}
+
+#[test]
+fn key_files_are_written_once_and_load_as_signers() {
+    let path = std::env::temp_dir().join(format!("hmf-keyfile-{}.key", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let kek = KeyProtection::kek([9; 32]);
+
+    let generated = KeyFile::generate(&suggested_key_id(&DeviceId::new("device-1"), 1));
+    generated.write(&path, &kek).unwrap();
+    assert!(matches!(
+        generated.write(&path, &kek),
+        Err(KeyFileError::Io(_))
+    ));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let signer = KeyFile::read(&path, &kek).unwrap().into_signer();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(signer.key_id(), KEY_ID);
+    assert_eq!(signer.verifying_key(), generated.verifying_key());
+}
@@ -0,0 +1,239 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hmf_core::Envelope;
+use hmf_core::crypto::signer::Signer;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    AckStatus, Command, CommandPayload, CommandRequest, OpResult, Payload, RejectReason,
+    ResultStatus, VerifiedEnvelope,
+};
+use hmf_core::error::{EndpointError, IngressError, SignerError, VerifyError};
+use hmf_core::ids::DeviceId;
+use hmf_core::runtime::Runtime;
+use hmf_core::runtime::handler::{Authorizer, CommandHandler, VerifiedContext};
+use hmf_core::trust::TrustRegistry;
+
+/// Allows commands only within scope `line-2`.
+struct LineTwoOnly;
+
+impl Authorizer for LineTwoOnly {
+    fn authorize(&self, ctx: &VerifiedContext, _payload: &Payload) -> bool {
+        ctx.scope() == "line-2"
+    }
+}
+
+/// Counts executions so tests can assert nothing ran.
+struct Counting(Rc<Cell<u32>>);
+
+impl CommandHandler for Counting {
+    fn handle_command(&mut self, _ctx: &VerifiedContext, req: &CommandRequest) -> OpResult {
+        self.0.set(self.0.get() + 1);
+        OpResult {
+            status: ResultStatus::Completed,
+            detail: req.request_id.clone(),
+        }
+    }
+}
+
+/// A signer whose backend is unreachable.
+struct Unreachable;
+
+impl Signer for Unreachable {
+    fn key_id(&self) -> &str {
+        "device-1:ed25519:v1"
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        key(2).verifying_key()
+    }
+
+    fn sign(&self, _msg: &[u8]) -> Result<[u8; 64], SignerError> {
+        Err(SignerError::Refused("backend unreachable".to_string()))
+    }
+}
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn endpoint(name: &str, seed: u8) -> EndpointContext {
+    EndpointContext::new(
+        DeviceId::new(name),
+        &format!("{name}:ed25519:v1"),
+        key(seed),
+    )
+}
+
+fn registry(name: &str, seed: u8) -> TrustRegistry {
+    let mut registry = TrustRegistry::new();
+    registry
+        .approve(
+            &format!("{name}:ed25519:v1"),
+            DeviceId::new(name),
+            key(seed).verifying_key(),
+        )
+        .unwrap();
+    registry
+}
+
+fn request() -> Payload {
+    Payload::Command(Command {
+        payload: Some(CommandPayload::Request(CommandRequest {
+            request_id: "req-1".to_string(),
+            command: "open".to_string(),
+            target: "valve-1".to_string(),
+            params: BTreeMap::new(),
+            blob: Vec::new(),
+            requires_confirmation: false,
+        })),
+    })
+}
+
+/// The device runtime under test, and its execution counter.
+fn device() -> (Runtime, Rc<Cell<u32>>) {
+    let executed = Rc::new(Cell::new(0));
+    let runtime = Runtime::new(
+        endpoint("device-1", 2),
+        registry("warden-1", 1),
+        LineTwoOnly,
+    )
+    .with_command_handler(Counting(executed.clone()));
+    (runtime, executed)
+}
+
+fn send(warden: &mut EndpointContext, scope: &str) -> Envelope {
+    warden
+        .send(request(), Routing::new("cmd", "device-1", scope))
+        .unwrap()
+}
+
+/// Checks that `response` is a signed rejecting `Ack` from the device.
+fn assert_signed_rejection(response: Option<Box<Envelope>>, reason: RejectReason) {
+    let response = *response.expect("rejecting ack");
+    let verified =
+        VerifiedEnvelope::verify(response, &registry("device-1", 2), &DecodeLimits::default())
+            .unwrap();
+    let Payload::Command(Command {
+        payload: Some(CommandPayload::Ack(ack)),
+    }) = verified.payload()
+    else {
+        panic!("expected an ack, got {:?}", verified.payload());
+    };
+    assert_eq!(ack.status, AckStatus::Rejected);
+    assert_eq!(ack.reason, reason);
+}
+
+#[test]
+fn accepted_requests_are_acknowledged_then_answered() {
+    let (mut device, executed) = device();
+    let mut warden = endpoint("warden-1", 1);
+
+    let accepted = device
+        .process(send(&mut warden, "line-2"), Instant::now())
+        .unwrap();
+    assert_eq!(executed.get(), 1);
+
+    let payloads = accepted
+        .responses
+        .into_iter()
+        .map(|r| {
+            VerifiedEnvelope::verify(r, &registry("device-1", 2), &DecodeLimits::default())
+                .unwrap()
+                .into_envelope()
+                .payload
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        &payloads[..],
+        [
+            Payload::Command(Command {
+                payload: Some(CommandPayload::Ack(_))
+            }),
+            Payload::Command(Command {
+                payload: Some(CommandPayload::Result(_))
+            }),
+        ]
+    ));
+}
+
+#[test]
+fn freshness_is_checked_before_the_signature() {
+    let (mut device, executed) = device();
+    let mut env = send(&mut endpoint("warden-1", 1), "line-2");
+    env.signature[32] ^= 1;
+
+    let stale = Instant::now() - Duration::from_millis(u64::from(env.ttl_ms) + 1_000);
+    let rejected = device.process(env, stale).unwrap_err();
+    assert!(matches!(rejected.reason, IngressError::Expired { .. }));
+    assert!(rejected.response.is_none());
+    assert_eq!(executed.get(), 0);
+}
+
+#[test]
+fn only_verified_envelopes_advance_replay_state() {
+    let (mut device, executed) = device();
+    let mut warden = endpoint("warden-1", 1);
+    let env = send(&mut warden, "line-2");
+
+    // A forged copy with the same counter must not burn that counter.
+    let mut forged = env.clone();
+    forged.signature[32] ^= 1;
+    let rejected = device.process(forged, Instant::now()).unwrap_err();
+    assert!(matches!(
+        rejected.reason,
+        IngressError::Signature(VerifyError::BadSignature)
+    ));
+    assert!(rejected.response.is_none());
+
+    device.process(env.clone(), Instant::now()).unwrap();
+
+    let rejected = device.process(env, Instant::now()).unwrap_err();
+    assert!(matches!(
+        rejected.reason,
+        IngressError::Replay {
+            counter: 1,
+            last_seen: 1
+        }
+    ));
+    assert_signed_rejection(rejected.response, RejectReason::Replay);
+    assert_eq!(executed.get(), 1);
+}
+
+#[test]
+fn unauthorized_requests_get_a_signed_rejection_and_do_not_execute() {
+    let (mut device, executed) = device();
+    let rejected = device
+        .process(send(&mut endpoint("warden-1", 1), "line-3"), Instant::now())
+        .unwrap_err();
+
+    assert!(matches!(rejected.reason, IngressError::Unauthorized));
+    assert_signed_rejection(rejected.response, RejectReason::Authorization);
+    assert_eq!(executed.get(), 0);
+}
+
+#[test]
+fn executed_requests_are_accepted_even_if_responses_fail_to_sign() {
+    let executed = Rc::new(Cell::new(0));
+    let mut device = Runtime::new(
+        EndpointContext::from_signer(DeviceId::new("device-1"), Unreachable),
+        registry("warden-1", 1),
+        LineTwoOnly,
+    )
+    .with_command_handler(Counting(executed.clone()));
+
+    let accepted = device
+        .process(send(&mut endpoint("warden-1", 1), "line-2"), Instant::now())
+        .unwrap();
+    assert_eq!(executed.get(), 1);
+    assert!(accepted.responses.is_empty());
+    assert!(matches!(
+        &accepted.unsigned[..],
+        [EndpointError::Sign(_), EndpointError::Sign(_)]
+    ));
+}
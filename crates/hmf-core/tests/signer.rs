@@ -0,0 +1,153 @@
+#![cfg(unix)]
+
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hmf_core::crypto::ed25519;
+use hmf_core::crypto::signer::socket::{self, SocketSigner};
+use hmf_core::crypto::signer::{InMemorySigner, Signer};
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{Payload, StateUpdate, Telemetry, TelemetryPayload, VerifiedEnvelope};
+use hmf_core::error::{EndpointError, SignerError};
+use hmf_core::ids::DeviceId;
+use hmf_core::trust::TrustRegistry;
+
+const KEY_ID: &str = "device-1:ed25519:v1";
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+/// Claims `key(1)` but signs with `key(2)`.
+struct Lying;
+
+impl Signer for Lying {
+    fn key_id(&self) -> &str {
+        KEY_ID
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        key(1).verifying_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<[u8; 64], SignerError> {
+        Ok(ed25519::sign(&key(2), msg))
+    }
+}
+
+struct Locked;
+
+impl Signer for Locked {
+    fn key_id(&self) -> &str {
+        KEY_ID
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        key(1).verifying_key()
+    }
+
+    fn sign(&self, _msg: &[u8]) -> Result<[u8; 64], SignerError> {
+        Err(SignerError::Refused("token locked".to_string()))
+    }
+}
+
+/// Serves `signer` on a fresh socket path, one connection at a time.
+fn spawn_signer(signer: impl Signer + 'static) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "hmf-signer-{}-{}.sock",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let _ = socket::serve(&mut stream.unwrap(), &signer);
+        }
+    });
+    path
+}
+
+fn state() -> Payload {
+    Payload::Telemetry(Telemetry {
+        payload: Some(TelemetryPayload::State(StateUpdate {
+            local_seq: 1,
+            items: Vec::new(),
+        })),
+    })
+}
+
+fn routing() -> Routing {
+    Routing::new("site/events", "warden-1", "site")
+}
+
+#[test]
+fn socket_signer_signs_verifiable_envelopes() {
+    let path = spawn_signer(InMemorySigner::new(KEY_ID, key(1)));
+    let signer = SocketSigner::connect(&path).unwrap();
+    assert_eq!(signer.key_id(), KEY_ID);
+    assert_eq!(signer.verifying_key(), key(1).verifying_key());
+
+    let mut endpoint = EndpointContext::from_signer(DeviceId::new("device-1"), signer);
+    let env = endpoint.send(state(), routing()).unwrap();
+    assert_eq!(env.key_id, KEY_ID);
+
+    let mut registry = TrustRegistry::new();
+    registry
+        .approve(KEY_ID, DeviceId::new("device-1"), key(1).verifying_key())
+        .unwrap();
+    VerifiedEnvelope::verify(env, &registry, &DecodeLimits::default()).unwrap();
+}
+
+#[test]
+fn signatures_not_matching_the_reported_key_are_refused() {
+    let signer = SocketSigner::connect(spawn_signer(Lying)).unwrap();
+    let mut endpoint = EndpointContext::from_signer(DeviceId::new("device-1"), signer);
+
+    assert!(matches!(
+        endpoint.send(state(), routing()),
+        Err(EndpointError::Sign(SignerError::BadSignature))
+    ));
+}
+
+/// `msg` behind the length-prefixed envelope-signature domain tag.
+fn tagged(msg: &[u8]) -> Vec<u8> {
+    let tag = b"HMFv1:envelope-signature";
+    let mut out = (tag.len() as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(tag);
+    out.extend_from_slice(msg);
+    out
+}
+
+#[test]
+fn signer_errors_reach_the_caller() {
+    let signer = SocketSigner::connect(spawn_signer(Locked)).unwrap();
+
+    match signer.sign(&tagged(b"msg")) {
+        Err(SignerError::Refused(detail)) => assert!(detail.contains("token locked")),
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn only_envelope_signing_bytes_are_signed() {
+    let signer = SocketSigner::connect(spawn_signer(InMemorySigner::new(KEY_ID, key(1)))).unwrap();
+
+    for msg in [
+        &b"msg"[..],
+        b"HMFv1:envelope-signature",
+        &tagged(b"msg")[1..],
+    ] {
+        match signer.sign(msg) {
+            Err(SignerError::Refused(detail)) => assert_eq!(detail, "not envelope signing bytes"),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+    let sig = signer.sign(&tagged(b"msg")).unwrap();
+    ed25519::verify(&key(1).verifying_key(), &tagged(b"msg"), &sig).unwrap();
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeSet;
+
+use ed25519_dalek::SigningKey;
+use hmf_core::envelope::sign::{sign_envelope_ed25519, verify_envelope_ed25519};
+use hmf_core::envelope::signing_bytes::{
+    canonical_payload_bytes, envelope_signing_bytes, payload_hash,
+};
+use hmf_core::error::VerifyError;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+#[path = "support/vectors.rs"]
+mod vectors;
+
+use vectors::{envelope_from_json, envelope_to_json, hex, unhex};
+
+const GOLDEN: &str = include_str!("vectors/signing-v1.json");
+
+fn golden() -> Value {
+    serde_json::from_str(GOLDEN).expect("golden vectors are valid JSON")
+}
+
+fn text<'a>(v: &'a Value, name: &str) -> &'a str {
+    v[name]
+        .as_str()
+        .unwrap_or_else(|| panic!("`{name}` must be a string"))
+}
+
+fn signing_key(file: &Value, key_id: &str) -> SigningKey {
+    let key = file["keys"]
+        .as_array()
+        .expect("keys")
+        .iter()
+        .find(|k| k["key_id"] == key_id)
+        .unwrap_or_else(|| panic!("no key `{key_id}` in vector file"));
+    let seed: [u8; 32] = unhex(text(key, "seed")).try_into().expect("32-byte seed");
+    let signing_key = SigningKey::from_bytes(&seed);
+    assert_eq!(
+        hex(signing_key.verifying_key().as_bytes()),
+        text(key, "public_key"),
+        "public key for `{key_id}` does not match its seed"
+    );
+    signing_key
+}
+
+#[test]
+fn golden_file_header_is_v1() {
+    let file = golden();
+    assert_eq!(file["format"], vectors::FORMAT);
+    assert_eq!(file["format_version"], vectors::FORMAT_VERSION);
+    assert_eq!(file["domain_tag"], vectors::DOMAIN_TAG);
+}
+
+#[test]
+fn golden_vectors_match_this_implementation() {
+    let file = golden();
+    for v in file["vectors"].as_array().expect("vectors") {
+        let name = text(v, "name");
+        let mut env = envelope_from_json(&v["envelope"]);
+        let payload = env.payload.clone().expect("vector has a payload");
+
+        let cpb = canonical_payload_bytes(&payload);
+        assert_eq!(hex(&cpb), text(v, "canonical_payload_bytes"), "{name}");
+        let hash: [u8; 32] = Sha256::digest(&cpb).into();
+        assert_eq!(hash, payload_hash(&payload), "{name}");
+        assert_eq!(hex(&hash), text(v, "payload_hash"), "{name}");
+        assert_eq!(
+            hex(&envelope_signing_bytes(&env).unwrap()),
+            text(v, "envelope_signing_bytes"),
+            "{name}"
+        );
+
+        // Ed25519 is deterministic, so the signature is reproducible.
+        let key_id = env.key_id.clone();
+        let key = signing_key(&file, &key_id);
+        sign_envelope_ed25519(&mut env, &key_id, &key).unwrap();
+        assert_eq!(hex(&env.signature), text(v, "signature"), "{name}");
+        assert_eq!(
+            verify_envelope_ed25519(&env, &key.verifying_key()),
+            Ok(()),
+            "{name}"
+        );
+
+        env.counter += 1;
+        assert_eq!(
+            verify_envelope_ed25519(&env, &key.verifying_key()),
+            Err(VerifyError::BadSignature),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn golden_envelopes_round_trip_through_json() {
+    for v in golden()["vectors"].as_array().expect("vectors") {
+        assert_eq!(
+            envelope_to_json(&envelope_from_json(&v["envelope"])),
+            v["envelope"]
+        );
+    }
+}
+
+#[test]
+fn golden_vectors_cover_every_payload_variant() {
+    let names = golden()["vectors"]
+        .as_array()
+        .expect("vectors")
+        .iter()
+        .map(|v| {
+            let env = envelope_from_json(&v["envelope"]);
+            let variant = vectors::variant(env.payload.as_ref().expect("payload"));
+            assert_eq!(variant, text(v, "name"));
+            variant
+        })
+        .collect::<BTreeSet<_>>();
+
+    let expected = [
+        "telemetry.birth",
+        "telemetry.heartbeat",
+        "telemetry.death",
+        "telemetry.state",
+        "telemetry.alarm",
+        "telemetry.observation",
+        "command.request",
+        "command.ack",
+        "command.result",
+        "config.query",
+        "config.snapshot",
+        "config.update",
+        "config.ack",
+        "config.result",
+        "engineering.request",
+        "engineering.ack",
+        "engineering.result",
+    ];
+    assert_eq!(names, expected.into_iter().collect());
+}
+
+#[test]
+fn golden_file_is_up_to_date() {
+    assert!(
+        vectors::render(&vectors::generate()) == GOLDEN,
+        "tests/vectors/signing-v1.json is stale; regenerate with \
+         `cargo run -p hmf-core --example signing_vectors > crates/hmf-core/tests/vectors/signing-v1.json`"
+    );
+}
@@ -0,0 +1,226 @@
+//! Malleability and non-canonical encoding vectors for strict verification.
+
+use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hmf_core::crypto::ed25519::{verify, verify_batch};
+use hmf_core::error::{TrustError, VerifyError};
+use hmf_core::ids::DeviceId;
+use hmf_core::trust::TrustRegistry;
+use sha2::{Digest, Sha512};
+
+const MSG: &[u8] = b"HMFv1 strict verification vector";
+
+/// Encoding of the identity point, which has order 1.
+const IDENTITY: [u8; 32] = {
+    let mut b = [0; 32];
+    b[0] = 1;
+    b
+};
+
+/// The group order `L`, little-endian.
+const L: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&[5; 32])
+}
+
+fn challenge(r: &[u8; 32], a: &[u8; 32], msg: &[u8]) -> Scalar {
+    let mut h = Sha512::new();
+    h.update(r);
+    h.update(a);
+    h.update(msg);
+    Scalar::from_bytes_mod_order_wide(&h.finalize().into())
+}
+
+fn secret_scalar(key: &SigningKey) -> Scalar {
+    let h = Sha512::digest(key.as_bytes());
+    let mut a: [u8; 32] = h[..32].try_into().unwrap();
+    a[0] &= 248;
+    a[31] &= 127;
+    a[31] |= 64;
+    Scalar::from_bytes_mod_order(a)
+}
+
+/// `true` if `8·(S·B - R - k·A)` is the identity: the cofactored equation.
+fn cofactored_equation_holds(r: &EdwardsPoint, s: &Scalar, k: &Scalar, a: &EdwardsPoint) -> bool {
+    (ED25519_BASEPOINT_POINT * s - r - a * k)
+        .mul_by_cofactor()
+        .is_identity()
+}
+
+fn signature(r: &[u8; 32], s: &Scalar) -> [u8; 64] {
+    let mut sig = [0; 64];
+    sig[..32].copy_from_slice(r);
+    sig[32..].copy_from_slice(s.as_bytes());
+    sig
+}
+
+/// Little-endian `a + b`, ignoring the final carry.
+fn add(a: &[u8], b: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0; 32];
+    let mut carry = 0u16;
+    for i in 0..32 {
+        let sum = u16::from(a[i]) + u16::from(b[i]) + carry;
+        out[i] = sum as u8;
+        carry = sum >> 8;
+    }
+    out
+}
+
+#[test]
+fn honest_signatures_verify() {
+    let sig = key().sign(MSG).to_bytes();
+    assert_eq!(verify(&key().verifying_key(), MSG, &sig), Ok(()));
+}
+
+#[test]
+fn s_plus_l_is_rejected() {
+    let sig = key().sign(MSG).to_bytes();
+    let mut malleated = sig;
+    malleated[32..].copy_from_slice(&add(&sig[32..], &L));
+    assert_ne!(malleated, sig);
+
+    assert!(matches!(
+        verify(&key().verifying_key(), MSG, &malleated),
+        Err(VerifyError::MalformedSignature {
+            reason: "S is not a reduced scalar"
+        })
+    ));
+}
+
+#[test]
+fn small_order_r_is_rejected() {
+    // S = k·a with R = identity satisfies the cofactorless equation
+    // S·B = R + k·A, so plain verification accepts it.
+    let public = key().verifying_key();
+    let s = challenge(&IDENTITY, public.as_bytes(), MSG) * secret_scalar(&key());
+    let sig = signature(&IDENTITY, &s);
+    assert!(public.verify(MSG, &Signature::from_bytes(&sig)).is_ok());
+
+    let expected = Err(VerifyError::MalformedSignature {
+        reason: "R has small order",
+    });
+    assert_eq!(verify(&public, MSG, &sig), expected);
+    assert_eq!(verify_batch(&[(MSG, &sig, &public)]), vec![expected]);
+}
+
+#[test]
+fn mixed_order_r_is_rejected() {
+    // R' = r·B + T for a point T of order 8, with S = r + k·a over R'. The
+    // cofactored equation holds, and a randomized batch equation holds
+    // whenever its random coefficient is a multiple of 8.
+    let public = key().verifying_key();
+    let r = Scalar::from_bytes_mod_order([3; 32]);
+    let mixed = ED25519_BASEPOINT_POINT * r + EIGHT_TORSION[1];
+    let k = challenge(mixed.compress().as_bytes(), public.as_bytes(), MSG);
+    let s = r + k * secret_scalar(&key());
+    assert!(cofactored_equation_holds(
+        &mixed,
+        &s,
+        &k,
+        &public.to_edwards()
+    ));
+    let sig = signature(mixed.compress().as_bytes(), &s);
+
+    let expected = Err(VerifyError::MalformedSignature {
+        reason: "R has a small-order component",
+    });
+    assert_eq!(verify(&public, MSG, &sig), expected);
+    assert_eq!(verify_batch(&[(MSG, &sig, &public)]), vec![expected]);
+}
+
+#[test]
+fn mixed_order_keys_are_rejected() {
+    // A' = a·B + T, signing with a over A'; the cofactored equation holds.
+    let a = secret_scalar(&key());
+    let point = ED25519_BASEPOINT_POINT * a + EIGHT_TORSION[1];
+    let mixed = VerifyingKey::from_bytes(point.compress().as_bytes()).unwrap();
+    assert!(!mixed.is_weak());
+    let r = ED25519_BASEPOINT_POINT * Scalar::from_bytes_mod_order([3; 32]);
+    let k = challenge(r.compress().as_bytes(), mixed.as_bytes(), MSG);
+    let s = Scalar::from_bytes_mod_order([3; 32]) + k * a;
+    assert!(cofactored_equation_holds(&r, &s, &k, &point));
+    let sig = signature(r.compress().as_bytes(), &s);
+
+    assert_eq!(verify(&mixed, MSG, &sig), Err(VerifyError::WeakKey));
+    assert_eq!(
+        verify_batch(&[(MSG, &sig, &mixed)]),
+        vec![Err(VerifyError::WeakKey)]
+    );
+    let mut registry = TrustRegistry::new();
+    assert!(matches!(
+        registry.approve("mixed:ed25519:v1", DeviceId::new("mixed"), mixed),
+        Err(TrustError::WeakKey { .. })
+    ));
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    // With A = identity, R = S·B verifies for every message under plain
+    // verification.
+    let weak = VerifyingKey::from_bytes(&IDENTITY).unwrap();
+    let s = Scalar::from_bytes_mod_order([9; 32]);
+    let r = (ED25519_BASEPOINT_POINT * s).compress();
+    let sig = signature(r.as_bytes(), &s);
+    assert!(weak.verify(MSG, &Signature::from_bytes(&sig)).is_ok());
+    assert!(
+        weak.verify(b"any other message", &Signature::from_bytes(&sig))
+            .is_ok()
+    );
+
+    assert_eq!(verify(&weak, MSG, &sig), Err(VerifyError::WeakKey));
+    assert_eq!(
+        verify_batch(&[(MSG, &sig, &weak)]),
+        vec![Err(VerifyError::WeakKey)]
+    );
+
+    let mut registry = TrustRegistry::new();
+    assert!(matches!(
+        registry.approve("weak:ed25519:v1", DeviceId::new("weak"), weak),
+        Err(TrustError::WeakKey { .. })
+    ));
+}
+
+#[test]
+fn non_canonical_key_encodings_are_refused() {
+    // y and y + p encode the same point when y < 19; find one of large order.
+    let p = {
+        let mut b = [0xff; 32];
+        b[0] = 0xed;
+        b[31] = 0x7f;
+        b
+    };
+    let (canonical, key) = (2u8..19)
+        .find_map(|y| {
+            let mut bytes = [0; 32];
+            bytes[0] = y;
+            let key = VerifyingKey::from_bytes(&bytes).ok()?;
+            (!key.is_weak()).then_some((bytes, key))
+        })
+        .expect("a large-order point with small y");
+    let non_canonical = add(&canonical, &p);
+    assert_ne!(non_canonical, canonical);
+    let aliased = VerifyingKey::from_bytes(&non_canonical).unwrap();
+    assert_eq!(
+        CompressedEdwardsY(non_canonical).decompress(),
+        CompressedEdwardsY(canonical).decompress()
+    );
+
+    let mut registry = TrustRegistry::new();
+    assert!(matches!(
+        registry.approve("alias:ed25519:v1", DeviceId::new("alias"), aliased),
+        Err(TrustError::NonCanonicalKey { .. })
+    ));
+    // No point with y < 19 is torsion-free, so the canonical form is refused
+    // too, for its small-order component.
+    assert!(matches!(
+        registry.approve("alias:ed25519:v1", DeviceId::new("alias"), key),
+        Err(TrustError::WeakKey { .. })
+    ));
+}
@@ -0,0 +1,967 @@
+//! Golden signing-bytes vectors (format version 1).
+//!
+//! Shared by the `signing_vectors` test and the `signing_vectors` example,
+//! which regenerates `tests/vectors/signing-v1.json`. The file format is
+//! described in `docs/06-testing/conformance.md`.
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use ed25519_dalek::SigningKey;
+use hmf_core::Envelope;
+use hmf_core::envelope::sign::sign_envelope_ed25519;
+use hmf_core::envelope::signing_bytes::{
+    canonical_payload_bytes, envelope_signing_bytes, payload_hash,
+};
+use hmf_core::envelope::*;
+use hmf_core::ids::{IdempotencyKey, TransactionId};
+use serde_json::{Map, Value, json};
+
+pub const FORMAT: &str = "hmf-signing-vectors";
+pub const FORMAT_VERSION: u64 = 1;
+pub const DOMAIN_TAG: &str = "HMFv1:envelope-signature";
+
+/// A fixed Ed25519 key used to sign vectors.
+pub struct Key {
+    pub key_id: &'static str,
+    pub seed: [u8; 32],
+}
+
+impl Key {
+    pub fn signing_key(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.seed)
+    }
+}
+
+pub fn keys() -> [Key; 2] {
+    [
+        Key {
+            key_id: "device-1:ed25519:v1",
+            seed: std::array::from_fn(|i| i as u8 + 1),
+        },
+        Key {
+            key_id: "warden-1:ed25519:v1",
+            seed: std::array::from_fn(|i| 0xa0 + i as u8),
+        },
+    ]
+}
+
+/// One vector before signing.
+pub struct Case {
+    pub name: &'static str,
+    pub envelope: Envelope,
+}
+
+/// Stable name of the payload variant carried by an envelope.
+pub fn variant(payload: &Payload) -> &'static str {
+    match payload {
+        Payload::Telemetry(t) => match t.payload {
+            None => "telemetry",
+            Some(TelemetryPayload::Birth(_)) => "telemetry.birth",
+            Some(TelemetryPayload::Heartbeat(_)) => "telemetry.heartbeat",
+            Some(TelemetryPayload::Death(_)) => "telemetry.death",
+            Some(TelemetryPayload::State(_)) => "telemetry.state",
+            Some(TelemetryPayload::Alarm(_)) => "telemetry.alarm",
+            Some(TelemetryPayload::Observation(_)) => "telemetry.observation",
+        },
+        Payload::Command(c) => match c.payload {
+            None => "command",
+            Some(CommandPayload::Request(_)) => "command.request",
+            Some(CommandPayload::Ack(_)) => "command.ack",
+            Some(CommandPayload::Result(_)) => "command.result",
+        },
+        Payload::Config(c) => match c.payload {
+            None => "config",
+            Some(ConfigPayload::Query(_)) => "config.query",
+            Some(ConfigPayload::Snapshot(_)) => "config.snapshot",
+            Some(ConfigPayload::Update(_)) => "config.update",
+            Some(ConfigPayload::Ack(_)) => "config.ack",
+            Some(ConfigPayload::Result(_)) => "config.result",
+        },
+        Payload::Engineering(e) => match e.payload {
+            None => "engineering",
+            Some(EngineeringPayload::Request(_)) => "engineering.request",
+            Some(EngineeringPayload::Ack(_)) => "engineering.ack",
+            Some(EngineeringPayload::Result(_)) => "engineering.result",
+        },
+    }
+}
+
+fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn case(
+    n: u64,
+    sender: &str,
+    key: &str,
+    profile: DeliveryProfile,
+    payload: Payload,
+    auth_context: &[u8],
+) -> Envelope {
+    let mut env = EnvelopeBuilder::new()
+        .with_sender_id(sender)
+        .with_sender_instance(&format!("{sender}-boot-7"))
+        .with_counter(n)
+        .with_ttl_ms(5_000)
+        .with_delivery_profile(profile)
+        .with_payload(payload)
+        .with_security(SigAlg::Ed25519, key, auth_context.to_vec(), Vec::new())
+        .build();
+    env.transaction_id = TransactionId::new(format!("{n:032x}"));
+    env.idempotency_key = IdempotencyKey::new(format!("idem-{n:04}"));
+    env
+}
+
+fn telemetry(payload: TelemetryPayload) -> Payload {
+    Payload::Telemetry(Telemetry {
+        payload: Some(payload),
+    })
+}
+
+fn command(payload: CommandPayload) -> Payload {
+    Payload::Command(Command {
+        payload: Some(payload),
+    })
+}
+
+fn config(payload: ConfigPayload) -> Payload {
+    Payload::Config(Config {
+        payload: Some(payload),
+    })
+}
+
+fn engineering(payload: EngineeringPayload) -> Payload {
+    Payload::Engineering(Engineering {
+        payload: Some(payload),
+    })
+}
+
+/// Every payload variant, each wrapped in a deterministic envelope.
+pub fn cases() -> Vec<Case> {
+    const DEVICE: &str = "device-1";
+    const WARDEN: &str = "warden-1";
+    const DEVICE_KEY: &str = "device-1:ed25519:v1";
+    const WARDEN_KEY: &str = "warden-1:ed25519:v1";
+    use DeliveryProfile::*;
+
+    let item = |key: &str, value: Option<StateValue>, unit: &str, quality| StateItem {
+        key: key.to_string(),
+        value,
+        unit: unit.to_string(),
+        quality,
+    };
+
+    let mut cases = vec![
+        Case {
+            name: "telemetry.birth",
+            envelope: case(
+                1,
+                DEVICE,
+                DEVICE_KEY,
+                AtLeastOnce,
+                telemetry(TelemetryPayload::Birth(LifecycleBirth {
+                    vendor: "Acme".to_string(),
+                    model: "PLC-9".to_string(),
+                    serial: "SN-0001".to_string(),
+                    hw_rev: "B".to_string(),
+                    fw_rev: "1.4.2".to_string(),
+                    uptime_ms: 1_200,
+                    capabilities: vec!["state".to_string(), "command".to_string()],
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "telemetry.heartbeat",
+            envelope: case(
+                2,
+                DEVICE,
+                DEVICE_KEY,
+                BestEffort,
+                telemetry(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+                    uptime_ms: 61_200,
+                    health: Health::Degraded,
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "telemetry.death",
+            envelope: case(
+                3,
+                DEVICE,
+                DEVICE_KEY,
+                AtLeastOnce,
+                telemetry(TelemetryPayload::Death(LifecycleDeath {
+                    reason: DeathReason::GracefulShutdown,
+                    uptime_ms: 86_400_000,
+                    detail: "operator shutdown".to_string(),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "telemetry.state",
+            envelope: case(
+                4,
+                DEVICE,
+                DEVICE_KEY,
+                BestEffort,
+                telemetry(TelemetryPayload::State(StateUpdate {
+                    local_seq: 42,
+                    items: vec![
+                        item("temp", Some(StateValue::F64(21.5)), "°C", Quality::Good),
+                        item("offset", Some(StateValue::I64(-40)), "", Quality::Good),
+                        item(
+                            "total",
+                            Some(StateValue::U64(u64::MAX - 1)),
+                            "Wh",
+                            Quality::Uncertain,
+                        ),
+                        item("running", Some(StateValue::B(true)), "", Quality::Good),
+                        item(
+                            "mode",
+                            Some(StateValue::S("auto".to_string())),
+                            "",
+                            Quality::Stale,
+                        ),
+                        item(
+                            "raw",
+                            Some(StateValue::Blob(vec![0x00, 0xff, 0x10])),
+                            "",
+                            Quality::Bad,
+                        ),
+                        item("pending", None, "", Quality::Unspecified),
+                    ],
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "telemetry.alarm",
+            envelope: case(
+                5,
+                DEVICE,
+                DEVICE_KEY,
+                AtLeastOnce,
+                telemetry(TelemetryPayload::Alarm(Alarm {
+                    severity: AlarmSeverity::Critical,
+                    code: "TEMP_HIGH".to_string(),
+                    summary: "Temperature high".to_string(),
+                    detail: "temp exceeded 90 for 30s".to_string(),
+                    related_key: "temp".to_string(),
+                    recommended_action: "inspect cooling".to_string(),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "telemetry.observation",
+            envelope: case(
+                6,
+                DEVICE,
+                DEVICE_KEY,
+                BestEffort,
+                telemetry(TelemetryPayload::Observation(Observation {
+                    subject: Some(Subject {
+                        subject_id: "device-9".to_string(),
+                    }),
+                    r#type: ObservationType::UnexpectedDeviceSeen,
+                    confidence_pct: 80,
+                    summary: "unknown MAC on segment".to_string(),
+                    detail: String::new(),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "command.request",
+            envelope: case(
+                7,
+                WARDEN,
+                WARDEN_KEY,
+                CriticalExec,
+                command(CommandPayload::Request(CommandRequest {
+                    request_id: "req-1".to_string(),
+                    command: "open".to_string(),
+                    target: "valve-1".to_string(),
+                    params: params(&[("speed", "slow"), ("position", "50")]),
+                    blob: vec![1, 2, 3],
+                    requires_confirmation: true,
+                })),
+                b"operator=alice;role=engineer",
+            ),
+        },
+        Case {
+            name: "command.ack",
+            envelope: case(
+                8,
+                DEVICE,
+                DEVICE_KEY,
+                CriticalExec,
+                command(CommandPayload::Ack(Ack {
+                    status: AckStatus::Received,
+                    detail: "req-1".to_string(),
+                    reason: RejectReason::Unspecified,
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "command.result",
+            envelope: case(
+                9,
+                DEVICE,
+                DEVICE_KEY,
+                CriticalExec,
+                command(CommandPayload::Result(OpResult {
+                    status: ResultStatus::Completed,
+                    detail: "req-1".to_string(),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "config.query",
+            envelope: case(
+                10,
+                WARDEN,
+                WARDEN_KEY,
+                AtLeastOnce,
+                config(ConfigPayload::Query(ConfigQuery {
+                    keys: vec!["setpoint".to_string(), "deadband".to_string()],
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "config.snapshot",
+            envelope: case(
+                11,
+                DEVICE,
+                DEVICE_KEY,
+                AtLeastOnce,
+                config(ConfigPayload::Snapshot(ConfigSnapshot {
+                    config_version: 7,
+                    params: params(&[("setpoint", "21.5"), ("deadband", "0.5")]),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "config.update",
+            envelope: case(
+                12,
+                WARDEN,
+                WARDEN_KEY,
+                OrderedReliable,
+                config(ConfigPayload::Update(ConfigUpdate {
+                    update_id: "upd-1".to_string(),
+                    strict: true,
+                    params: params(&[("setpoint", "22.0")]),
+                    expected_version: Some(7),
+                })),
+                b"operator=alice;role=engineer",
+            ),
+        },
+        Case {
+            name: "config.ack",
+            envelope: case(
+                13,
+                DEVICE,
+                DEVICE_KEY,
+                OrderedReliable,
+                config(ConfigPayload::Ack(Ack {
+                    status: AckStatus::Rejected,
+                    detail: "version mismatch".to_string(),
+                    reason: RejectReason::Validation,
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "config.result",
+            envelope: case(
+                14,
+                DEVICE,
+                DEVICE_KEY,
+                OrderedReliable,
+                config(ConfigPayload::Result(OpResult {
+                    status: ResultStatus::Applied,
+                    detail: "upd-1".to_string(),
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "engineering.request",
+            envelope: case(
+                15,
+                WARDEN,
+                WARDEN_KEY,
+                CriticalExec,
+                engineering(EngineeringPayload::Request(EngineeringRequest {
+                    request_id: "eng-1".to_string(),
+                    action: "firmware.stage".to_string(),
+                    target: "device-1".to_string(),
+                    params: params(&[("version", "1.5.0")]),
+                    blob: vec![0xde, 0xad, 0xbe, 0xef],
+                    requires_confirmation: true,
+                })),
+                b"operator=bob;role=engineer;ticket=CHG-1",
+            ),
+        },
+        Case {
+            name: "engineering.ack",
+            envelope: case(
+                16,
+                DEVICE,
+                DEVICE_KEY,
+                CriticalExec,
+                engineering(EngineeringPayload::Ack(Ack {
+                    status: AckStatus::Received,
+                    detail: "eng-1".to_string(),
+                    reason: RejectReason::Unspecified,
+                })),
+                &[],
+            ),
+        },
+        Case {
+            name: "engineering.result",
+            envelope: case(
+                17,
+                DEVICE,
+                DEVICE_KEY,
+                CriticalExec,
+                engineering(EngineeringPayload::Result(EngineeringResult {
+                    status: ResultStatus::Failed,
+                    detail: "checksum mismatch".to_string(),
+                    outputs: params(&[("stage", "verify")]),
+                    blob: Vec::new(),
+                })),
+                &[],
+            ),
+        },
+    ];
+
+    // Routing hints are optional; exercise them on one vector.
+    let routed = &mut cases[6].envelope;
+    routed.topic = "site-a/line-2/valve-1".to_string();
+    routed.target = "device-1".to_string();
+    routed.scope = "line-2".to_string();
+
+    cases
+}
+
+/// Signs every case and renders the complete vector file.
+pub fn generate() -> Value {
+    let keys = keys();
+    let vectors = cases()
+        .into_iter()
+        .map(|Case { name, mut envelope }| {
+            let key = keys
+                .iter()
+                .find(|k| k.key_id == envelope.key_id)
+                .expect("case signed with an unknown key");
+            sign_envelope_ed25519(&mut envelope, key.key_id, &key.signing_key())
+                .expect("case has a payload");
+            let payload = envelope.payload.as_ref().expect("case has a payload");
+            json!({
+                "name": name,
+                "envelope": envelope_to_json(&envelope),
+                "canonical_payload_bytes": hex(&canonical_payload_bytes(payload)),
+                "payload_hash": hex(&payload_hash(payload)),
+                "envelope_signing_bytes": hex(&envelope_signing_bytes(&envelope).expect("signed above")),
+                "signature": hex(&envelope.signature),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "format": FORMAT,
+        "format_version": FORMAT_VERSION,
+        "domain_tag": DOMAIN_TAG,
+        "keys": keys
+            .iter()
+            .map(|k| json!({
+                "key_id": k.key_id,
+                "seed": hex(&k.seed),
+                "public_key": hex(k.signing_key().verifying_key().as_bytes()),
+            }))
+            .collect::<Vec<_>>(),
+        "vectors": vectors,
+    })
+}
+
+pub fn render(file: &Value) -> String {
+    let mut out = serde_json::to_string_pretty(file).expect("vectors serialize");
+    out.push('\n');
+    out
+}
+
+// ---------- hex ----------
+
+pub fn hex(bytes: &[u8]) -> String {
+    hmf_core::hex::encode(bytes)
+}
+
+pub fn unhex(s: &str) -> Vec<u8> {
+    hmf_core::hex::decode(s).expect("invalid hex")
+}
+
+// ---------- envelope -> json ----------
+
+fn map_json(map: &BTreeMap<String, String>) -> Value {
+    Value::Object(
+        map.iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect(),
+    )
+}
+
+fn oneof(name: &str, value: Value) -> Value {
+    let mut m = Map::new();
+    m.insert(name.to_string(), value);
+    Value::Object(m)
+}
+
+pub fn envelope_to_json(env: &Envelope) -> Value {
+    json!({
+        "proto_ver": env.proto_ver,
+        "msg_class": env.msg_class.to_i32(),
+        "sender_id": env.sender_id.as_str(),
+        "sender_instance": env.sender_instance.as_str(),
+        "counter": env.counter.to_string(),
+        "ttl_ms": env.ttl_ms,
+        "transaction_id": env.transaction_id.as_str(),
+        "idempotency_key": env.idempotency_key.as_str(),
+        "delivery_profile": env.delivery_profile.to_i32(),
+        "topic": env.topic,
+        "target": env.target,
+        "scope": env.scope,
+        "payload": env.payload.as_ref().map(payload_to_json),
+        "sig_alg": env.sig_alg.to_i32(),
+        "key_id": env.key_id,
+        "auth_context": hex(&env.auth_context),
+    })
+}
+
+fn ack_json(a: &Ack) -> Value {
+    json!({ "status": a.status.to_i32(), "detail": a.detail, "reason": a.reason.to_i32() })
+}
+
+fn op_result_json(r: &OpResult) -> Value {
+    json!({ "status": r.status.to_i32(), "detail": r.detail })
+}
+
+fn state_value_json(v: &StateValue) -> Value {
+    match v {
+        StateValue::F64(v) => oneof("f64", json!(v)),
+        StateValue::I64(v) => oneof("i64", json!(v.to_string())),
+        StateValue::U64(v) => oneof("u64", json!(v.to_string())),
+        StateValue::B(v) => oneof("b", json!(v)),
+        StateValue::S(v) => oneof("s", json!(v)),
+        StateValue::Blob(v) => oneof("blob", json!(hex(v))),
+    }
+}
+
+fn telemetry_json(t: &TelemetryPayload) -> Value {
+    match t {
+        TelemetryPayload::Birth(b) => oneof(
+            "birth",
+            json!({
+                "vendor": b.vendor,
+                "model": b.model,
+                "serial": b.serial,
+                "hw_rev": b.hw_rev,
+                "fw_rev": b.fw_rev,
+                "uptime_ms": b.uptime_ms.to_string(),
+                "capabilities": b.capabilities,
+            }),
+        ),
+        TelemetryPayload::Heartbeat(h) => oneof(
+            "heartbeat",
+            json!({
+                "uptime_ms": h.uptime_ms.to_string(),
+                "health": h.health.to_i32(),
+            }),
+        ),
+        TelemetryPayload::Death(d) => oneof(
+            "death",
+            json!({
+                "reason": d.reason.to_i32(),
+                "uptime_ms": d.uptime_ms.to_string(),
+                "detail": d.detail,
+            }),
+        ),
+        TelemetryPayload::State(s) => oneof(
+            "state",
+            json!({
+                "local_seq": s.local_seq.to_string(),
+                "items": s.items.iter().map(|it| json!({
+                    "key": it.key,
+                    "value": it.value.as_ref().map(state_value_json),
+                    "unit": it.unit,
+                    "quality": it.quality.to_i32(),
+                })).collect::<Vec<_>>(),
+            }),
+        ),
+        TelemetryPayload::Alarm(a) => oneof(
+            "alarm",
+            json!({
+                "severity": a.severity.to_i32(),
+                "code": a.code,
+                "summary": a.summary,
+                "detail": a.detail,
+                "related_key": a.related_key,
+                "recommended_action": a.recommended_action,
+            }),
+        ),
+        TelemetryPayload::Observation(o) => oneof(
+            "observation",
+            json!({
+                "subject": o.subject.as_ref().map(|s| json!({ "subject_id": s.subject_id })),
+                "type": o.r#type.to_i32(),
+                "confidence_pct": o.confidence_pct,
+                "summary": o.summary,
+                "detail": o.detail,
+            }),
+        ),
+    }
+}
+
+fn payload_to_json(payload: &Payload) -> Value {
+    match payload {
+        Payload::Telemetry(t) => oneof("telemetry", json!(t.payload.as_ref().map(telemetry_json))),
+        Payload::Command(c) => oneof(
+            "command",
+            json!(c.payload.as_ref().map(|p| match p {
+                CommandPayload::Request(r) => oneof(
+                    "request",
+                    json!({
+                        "request_id": r.request_id,
+                        "command": r.command,
+                        "target": r.target,
+                        "params": map_json(&r.params),
+                        "blob": hex(&r.blob),
+                        "requires_confirmation": r.requires_confirmation,
+                    }),
+                ),
+                CommandPayload::Ack(a) => oneof("ack", ack_json(a)),
+                CommandPayload::Result(r) => oneof("result", op_result_json(r)),
+            })),
+        ),
+        Payload::Config(c) => oneof(
+            "config",
+            json!(c.payload.as_ref().map(|p| match p {
+                ConfigPayload::Query(q) => oneof("query", json!({ "keys": q.keys })),
+                ConfigPayload::Snapshot(s) => oneof(
+                    "snapshot",
+                    json!({
+                        "config_version": s.config_version.to_string(),
+                        "params": map_json(&s.params),
+                    }),
+                ),
+                ConfigPayload::Update(u) => oneof(
+                    "update",
+                    json!({
+                        "update_id": u.update_id,
+                        "strict": u.strict,
+                        "params": map_json(&u.params),
+                        "expected_version": u.expected_version.map(|v| v.to_string()),
+                    }),
+                ),
+                ConfigPayload::Ack(a) => oneof("ack", ack_json(a)),
+                ConfigPayload::Result(r) => oneof("result", op_result_json(r)),
+            })),
+        ),
+        Payload::Engineering(e) => oneof(
+            "engineering",
+            json!(e.payload.as_ref().map(|p| match p {
+                EngineeringPayload::Request(r) => oneof(
+                    "request",
+                    json!({
+                        "request_id": r.request_id,
+                        "action": r.action,
+                        "target": r.target,
+                        "params": map_json(&r.params),
+                        "blob": hex(&r.blob),
+                        "requires_confirmation": r.requires_confirmation,
+                    }),
+                ),
+                EngineeringPayload::Ack(a) => oneof("ack", ack_json(a)),
+                EngineeringPayload::Result(r) => oneof(
+                    "result",
+                    json!({
+                        "status": r.status.to_i32(),
+                        "detail": r.detail,
+                        "outputs": map_json(&r.outputs),
+                        "blob": hex(&r.blob),
+                    }),
+                ),
+            })),
+        ),
+    }
+}
+
+// ---------- json -> envelope ----------
+
+fn field<'a>(v: &'a Value, name: &str) -> &'a Value {
+    v.get(name)
+        .unwrap_or_else(|| panic!("missing field `{name}` in {v}"))
+}
+
+fn string(v: &Value, name: &str) -> String {
+    field(v, name)
+        .as_str()
+        .unwrap_or_else(|| panic!("`{name}` must be a string"))
+        .to_string()
+}
+
+fn bytes(v: &Value, name: &str) -> Vec<u8> {
+    unhex(&string(v, name))
+}
+
+fn uint64(v: &Value, name: &str) -> u64 {
+    string(v, name)
+        .parse()
+        .unwrap_or_else(|_| panic!("`{name}` must be a decimal u64 string"))
+}
+
+fn uint32(v: &Value, name: &str) -> u32 {
+    field(v, name)
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .unwrap_or_else(|| panic!("`{name}` must be a u32"))
+}
+
+fn int32(v: &Value, name: &str) -> i32 {
+    field(v, name)
+        .as_i64()
+        .and_then(|n| i32::try_from(n).ok())
+        .unwrap_or_else(|| panic!("`{name}` must be an i32"))
+}
+
+fn boolean(v: &Value, name: &str) -> bool {
+    field(v, name)
+        .as_bool()
+        .unwrap_or_else(|| panic!("`{name}` must be a bool"))
+}
+
+fn strings(v: &Value, name: &str) -> Vec<String> {
+    field(v, name)
+        .as_array()
+        .unwrap_or_else(|| panic!("`{name}` must be an array"))
+        .iter()
+        .map(|s| s.as_str().expect("array of strings").to_string())
+        .collect()
+}
+
+fn map(v: &Value, name: &str) -> BTreeMap<String, String> {
+    field(v, name)
+        .as_object()
+        .unwrap_or_else(|| panic!("`{name}` must be an object"))
+        .iter()
+        .map(|(k, v)| (k.clone(), v.as_str().expect("map of strings").to_string()))
+        .collect()
+}
+
+/// Splits a `{ "variant": {...} }` oneof; `null` means the oneof is unset.
+fn variant_of<'a>(v: &'a Value, name: &str) -> Option<(&'a str, &'a Value)> {
+    oneof_of(field(v, name))
+}
+
+fn oneof_of(v: &Value) -> Option<(&str, &Value)> {
+    if v.is_null() {
+        return None;
+    }
+    let obj = v
+        .as_object()
+        .filter(|o| o.len() == 1)
+        .unwrap_or_else(|| panic!("oneof must be an object with exactly one key: {v}"));
+    obj.iter().next().map(|(k, v)| (k.as_str(), v))
+}
+
+pub fn envelope_from_json(v: &Value) -> Envelope {
+    Envelope {
+        proto_ver: uint32(v, "proto_ver"),
+        msg_class: MsgClass::from_i32(int32(v, "msg_class")),
+        sender_id: hmf_core::ids::DeviceId::new(string(v, "sender_id")),
+        sender_instance: hmf_core::ids::InstanceId::new(string(v, "sender_instance")),
+        counter: uint64(v, "counter"),
+        ttl_ms: uint32(v, "ttl_ms"),
+        transaction_id: TransactionId::new(string(v, "transaction_id")),
+        idempotency_key: IdempotencyKey::new(string(v, "idempotency_key")),
+        delivery_profile: DeliveryProfile::from_i32(int32(v, "delivery_profile")),
+        topic: string(v, "topic"),
+        target: string(v, "target"),
+        scope: string(v, "scope"),
+        payload: variant_of(v, "payload").map(|(k, p)| payload_from_json(k, p)),
+        sig_alg: SigAlg::from_i32(int32(v, "sig_alg")),
+        signature: Vec::new(),
+        key_id: string(v, "key_id"),
+        auth_context: bytes(v, "auth_context"),
+    }
+}
+
+fn ack(v: &Value) -> Ack {
+    Ack {
+        status: AckStatus::from_i32(int32(v, "status")),
+        detail: string(v, "detail"),
+        reason: RejectReason::from_i32(int32(v, "reason")),
+    }
+}
+
+fn op_result(v: &Value) -> OpResult {
+    OpResult {
+        status: ResultStatus::from_i32(int32(v, "status")),
+        detail: string(v, "detail"),
+    }
+}
+
+fn state_value(kind: &str, v: &Value) -> StateValue {
+    match kind {
+        "f64" => StateValue::F64(v.as_f64().expect("f64 value must be a number")),
+        "i64" => StateValue::I64(
+            v.as_str()
+                .and_then(|s| s.parse().ok())
+                .expect("i64 value must be a decimal string"),
+        ),
+        "u64" => StateValue::U64(
+            v.as_str()
+                .and_then(|s| s.parse().ok())
+                .expect("u64 value must be a decimal string"),
+        ),
+        "b" => StateValue::B(v.as_bool().expect("b value must be a bool")),
+        "s" => StateValue::S(v.as_str().expect("s value must be a string").to_string()),
+        "blob" => StateValue::Blob(unhex(v.as_str().expect("blob value must be hex"))),
+        other => panic!("unknown state value kind `{other}`"),
+    }
+}
+
+fn telemetry_from_json(kind: &str, v: &Value) -> TelemetryPayload {
+    match kind {
+        "birth" => TelemetryPayload::Birth(LifecycleBirth {
+            vendor: string(v, "vendor"),
+            model: string(v, "model"),
+            serial: string(v, "serial"),
+            hw_rev: string(v, "hw_rev"),
+            fw_rev: string(v, "fw_rev"),
+            uptime_ms: uint64(v, "uptime_ms"),
+            capabilities: strings(v, "capabilities"),
+        }),
+        "heartbeat" => TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: uint64(v, "uptime_ms"),
+            health: Health::from_i32(int32(v, "health")),
+        }),
+        "death" => TelemetryPayload::Death(LifecycleDeath {
+            reason: DeathReason::from_i32(int32(v, "reason")),
+            uptime_ms: uint64(v, "uptime_ms"),
+            detail: string(v, "detail"),
+        }),
+        "state" => TelemetryPayload::State(StateUpdate {
+            local_seq: uint64(v, "local_seq"),
+            items: field(v, "items")
+                .as_array()
+                .expect("`items` must be an array")
+                .iter()
+                .map(|it| StateItem {
+                    key: string(it, "key"),
+                    value: variant_of(it, "value").map(|(k, v)| state_value(k, v)),
+                    unit: string(it, "unit"),
+                    quality: Quality::from_i32(int32(it, "quality")),
+                })
+                .collect(),
+        }),
+        "alarm" => TelemetryPayload::Alarm(Alarm {
+            severity: AlarmSeverity::from_i32(int32(v, "severity")),
+            code: string(v, "code"),
+            summary: string(v, "summary"),
+            detail: string(v, "detail"),
+            related_key: string(v, "related_key"),
+            recommended_action: string(v, "recommended_action"),
+        }),
+        "observation" => TelemetryPayload::Observation(Observation {
+            subject: Some(field(v, "subject"))
+                .filter(|s| !s.is_null())
+                .map(|s| Subject {
+                    subject_id: string(s, "subject_id"),
+                }),
+            r#type: ObservationType::from_i32(int32(v, "type")),
+            confidence_pct: uint32(v, "confidence_pct"),
+            summary: string(v, "summary"),
+            detail: string(v, "detail"),
+        }),
+        other => panic!("unknown telemetry variant `{other}`"),
+    }
+}
+
+fn payload_from_json(kind: &str, v: &Value) -> Payload {
+    match kind {
+        "telemetry" => Payload::Telemetry(Telemetry {
+            payload: oneof_of(v).map(|(k, v)| telemetry_from_json(k, v)),
+        }),
+        "command" => Payload::Command(Command {
+            payload: oneof_of(v).map(|(k, v)| match k {
+                "request" => CommandPayload::Request(CommandRequest {
+                    request_id: string(v, "request_id"),
+                    command: string(v, "command"),
+                    target: string(v, "target"),
+                    params: map(v, "params"),
+                    blob: bytes(v, "blob"),
+                    requires_confirmation: boolean(v, "requires_confirmation"),
+                }),
+                "ack" => CommandPayload::Ack(ack(v)),
+                "result" => CommandPayload::Result(op_result(v)),
+                other => panic!("unknown command variant `{other}`"),
+            }),
+        }),
+        "config" => Payload::Config(Config {
+            payload: oneof_of(v).map(|(k, v)| match k {
+                "query" => ConfigPayload::Query(ConfigQuery {
+                    keys: strings(v, "keys"),
+                }),
+                "snapshot" => ConfigPayload::Snapshot(ConfigSnapshot {
+                    config_version: uint64(v, "config_version"),
+                    params: map(v, "params"),
+                }),
+                "update" => ConfigPayload::Update(ConfigUpdate {
+                    update_id: string(v, "update_id"),
+                    strict: boolean(v, "strict"),
+                    params: map(v, "params"),
+                    expected_version: Some(field(v, "expected_version"))
+                        .filter(|e| !e.is_null())
+                        .map(|_| uint64(v, "expected_version")),
+                }),
+                "ack" => ConfigPayload::Ack(ack(v)),
+                "result" => ConfigPayload::Result(op_result(v)),
+                other => panic!("unknown config variant `{other}`"),
+            }),
+        }),
+        "engineering" => Payload::Engineering(Engineering {
+            payload: oneof_of(v).map(|(k, v)| match k {
+                "request" => EngineeringPayload::Request(EngineeringRequest {
+                    request_id: string(v, "request_id"),
+                    action: string(v, "action"),
+                    target: string(v, "target"),
+                    params: map(v, "params"),
+                    blob: bytes(v, "blob"),
+                    requires_confirmation: boolean(v, "requires_confirmation"),
+                }),
+                "ack" => EngineeringPayload::Ack(ack(v)),
+                "result" => EngineeringPayload::Result(EngineeringResult {
+                    status: ResultStatus::from_i32(int32(v, "status")),
+                    detail: string(v, "detail"),
+                    outputs: map(v, "outputs"),
+                    blob: bytes(v, "blob"),
+                }),
+                other => panic!("unknown engineering variant `{other}`"),
+            }),
+        }),
+        other => panic!("unknown payload `{other}`"),
+    }
+}
@@ -0,0 +1,336 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::{
+    Engineering, EngineeringPayload, EngineeringRequest, EngineeringResult, Payload, ResultStatus,
+};
+use hmf_core::ids::DeviceId;
+use hmf_core::runtime::Runtime;
+use hmf_core::runtime::engineering::{
+    ArtifactStage, MemoryStage, TransferManager, action, transfer_requests,
+};
+use hmf_core::runtime::handler::{Authorizer, VerifiedContext};
+use hmf_core::trust::TrustRegistry;
+
+const ARTIFACT: &[u8] = b"0123456789";
+const TARGET: &str = "plc-1";
+
+struct AllowAll;
+
+impl Authorizer for AllowAll {
+    fn authorize(&self, _ctx: &VerifiedContext, _payload: &Payload) -> bool {
+        true
+    }
+}
+
+/// A [`MemoryStage`] the test can inspect after handing it to the runtime.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<MemoryStage>>);
+
+impl ArtifactStage for Shared {
+    fn begin(&mut self, transfer_id: &str, target: &str, len: u64) -> Result<(), String> {
+        self.0.borrow_mut().begin(transfer_id, target, len)
+    }
+
+    fn write(&mut self, transfer_id: &str, offset: u64, data: &[u8]) -> Result<(), String> {
+        self.0.borrow_mut().write(transfer_id, offset, data)
+    }
+
+    fn activate(&mut self, transfer_id: &str, target: &str) -> Result<(), String> {
+        self.0.borrow_mut().activate(transfer_id, target)
+    }
+
+    fn discard(&mut self, transfer_id: &str) {
+        self.0.borrow_mut().discard(transfer_id);
+    }
+}
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn endpoint(name: &str, seed: u8) -> EndpointContext {
+    EndpointContext::new(
+        DeviceId::new(name),
+        &format!("{name}:ed25519:v1"),
+        key(seed),
+    )
+}
+
+fn device(
+    configure: impl FnOnce(TransferManager<Shared>) -> TransferManager<Shared>,
+) -> (Runtime, Shared) {
+    let mut registry = TrustRegistry::new();
+    for (name, seed) in [("warden-1", 1), ("warden-2", 3)] {
+        registry
+            .approve(
+                &format!("{name}:ed25519:v1"),
+                DeviceId::new(name),
+                key(seed).verifying_key(),
+            )
+            .unwrap();
+    }
+    let stage = Shared::default();
+    let runtime = Runtime::new(endpoint("device-1", 2), registry, AllowAll)
+        .with_engineering_handler(configure(TransferManager::new(stage.clone())));
+    (runtime, stage)
+}
+
+/// Sends `req` from `sender` and returns the device's `EngineeringResult`.
+fn call(
+    device: &mut Runtime,
+    sender: &mut EndpointContext,
+    req: &EngineeringRequest,
+    at: Instant,
+) -> EngineeringResult {
+    let payload = Payload::Engineering(Engineering {
+        payload: Some(EngineeringPayload::Request(req.clone())),
+    });
+    let env = sender
+        .send(payload, Routing::new("eng", "device-1", "line-2"))
+        .unwrap();
+    let accepted = device.process(env, at).unwrap();
+    match accepted.responses[1].payload.as_ref() {
+        Some(Payload::Engineering(Engineering {
+            payload: Some(EngineeringPayload::Result(result)),
+        })) => result.clone(),
+        other => panic!("expected an engineering result, got {other:?}"),
+    }
+}
+
+fn request(transfer_id: &str, action: &str) -> EngineeringRequest {
+    EngineeringRequest {
+        request_id: format!("{transfer_id}:{action}"),
+        action: action.to_string(),
+        target: TARGET.to_string(),
+        params: BTreeMap::from([("transfer_id".to_string(), transfer_id.to_string())]),
+        blob: Vec::new(),
+        requires_confirmation: false,
+    }
+}
+
+/// `transfer.begin`, the three chunks of [`ARTIFACT`] and `transfer.commit`.
+fn requests(transfer_id: &str) -> Vec<EngineeringRequest> {
+    transfer_requests(transfer_id, TARGET, ARTIFACT, 4)
+}
+
+fn next_offset(result: &EngineeringResult) -> Option<&str> {
+    result.outputs.get("next_offset").map(String::as_str)
+}
+
+#[test]
+fn completed_transfers_are_activated() {
+    let (mut device, stage) = device(|m| m);
+    let mut warden = endpoint("warden-1", 1);
+    let now = Instant::now();
+
+    let [begin, chunks @ .., commit] = &requests("t1")[..] else {
+        unreachable!()
+    };
+    assert_eq!(
+        call(&mut device, &mut warden, begin, now).status,
+        ResultStatus::Completed
+    );
+    for chunk in chunks {
+        let result = call(&mut device, &mut warden, chunk, now);
+        assert_eq!(result.status, ResultStatus::Completed, "{}", result.detail);
+        assert!(stage.0.borrow().active(TARGET).is_none());
+    }
+
+    let result = call(&mut device, &mut warden, commit, now);
+    assert_eq!(result.status, ResultStatus::Applied);
+    assert_eq!(next_offset(&result), Some("10"));
+    assert_eq!(stage.0.borrow().active(TARGET), Some(ARTIFACT));
+
+    let status = call(
+        &mut device,
+        &mut warden,
+        &request("t1", action::STATUS),
+        now,
+    );
+    assert_eq!(status.detail, "unknown transfer_id");
+}
+
+#[test]
+fn hash_mismatches_are_rejected_and_never_activated() {
+    let (mut device, stage) = device(|m| m);
+    let mut warden = endpoint("warden-1", 1);
+    let now = Instant::now();
+    let reqs = requests("t1");
+
+    // The signed begin fixes the hash of a different artifact.
+    let mut begin = reqs[0].clone();
+    begin.params = transfer_requests("t1", TARGET, b"9876543210", 4)[0]
+        .params
+        .clone();
+    call(&mut device, &mut warden, &begin, now);
+
+    let mut tampered = reqs[1].clone();
+    tampered.blob[0] ^= 1;
+    let result = call(&mut device, &mut warden, &tampered, now);
+    assert_eq!(result.status, ResultStatus::Rejected);
+    assert_eq!(result.detail, "chunk_sha256 mismatch");
+
+    for chunk in &reqs[1..4] {
+        call(&mut device, &mut warden, chunk, now);
+    }
+    let result = call(&mut device, &mut warden, &reqs[4], now);
+    assert_eq!(result.status, ResultStatus::Rejected);
+    assert!(result.detail.starts_with("artifact_sha256 mismatch"));
+    assert!(stage.0.borrow().active(TARGET).is_none());
+
+    let status = call(
+        &mut device,
+        &mut warden,
+        &request("t1", action::STATUS),
+        now,
+    );
+    assert_eq!(status.detail, "unknown transfer_id");
+}
+
+#[test]
+fn chunks_must_arrive_in_order_and_duplicates_are_acknowledged() {
+    let (mut device, stage) = device(|m| m);
+    let mut warden = endpoint("warden-1", 1);
+    let now = Instant::now();
+    let reqs = requests("t1");
+
+    call(&mut device, &mut warden, &reqs[0], now);
+    let early = call(&mut device, &mut warden, &reqs[2], now);
+    assert_eq!(early.status, ResultStatus::Rejected);
+    assert_eq!(early.detail, "expected offset 0");
+
+    assert_eq!(
+        next_offset(&call(&mut device, &mut warden, &reqs[1], now)),
+        Some("4")
+    );
+    let duplicate = call(&mut device, &mut warden, &reqs[1], now);
+    assert_eq!(duplicate.status, ResultStatus::Completed);
+    assert_eq!(duplicate.detail, "duplicate chunk");
+    assert_eq!(next_offset(&duplicate), Some("4"));
+
+    // Different bytes at an accepted offset are refused.
+    let conflicting = transfer_requests("t1", TARGET, b"abcd", 4).remove(1);
+    let result = call(&mut device, &mut warden, &conflicting, now);
+    assert_eq!(result.status, ResultStatus::Rejected);
+    assert_eq!(result.detail, "chunk conflicts with received data");
+
+    for req in &reqs[2..] {
+        call(&mut device, &mut warden, req, now);
+    }
+    assert_eq!(stage.0.borrow().active(TARGET), Some(ARTIFACT));
+}
+
+#[test]
+fn commit_before_completion_keeps_the_transfer() {
+    let (mut device, stage) = device(|m| m);
+    let mut warden = endpoint("warden-1", 1);
+    let now = Instant::now();
+    let reqs = requests("t1");
+
+    call(&mut device, &mut warden, &reqs[0], now);
+    call(&mut device, &mut warden, &reqs[1], now);
+    let early = call(&mut device, &mut warden, &reqs[4], now);
+    assert_eq!(early.status, ResultStatus::Rejected);
+    assert_eq!(early.detail, "artifact incomplete");
+    assert_eq!(next_offset(&early), Some("4"));
+    assert!(stage.0.borrow().active(TARGET).is_none());
+
+    let status = call(
+        &mut device,
+        &mut warden,
+        &request("t1", action::STATUS),
+        now,
+    );
+    assert_eq!(next_offset(&status), Some("4"));
+
+    for req in &reqs[2..] {
+        call(&mut device, &mut warden, req, now);
+    }
+    assert_eq!(stage.0.borrow().active(TARGET), Some(ARTIFACT));
+}
+
+#[test]
+fn transfers_belong_to_the_sender_that_began_them() {
+    let (mut device, stage) = device(|m| m);
+    let mut owner = endpoint("warden-1", 1);
+    let mut other = endpoint("warden-2", 3);
+    let now = Instant::now();
+    let reqs = requests("t1");
+
+    call(&mut device, &mut owner, &reqs[0], now);
+    for action in [action::STATUS, action::ABORT] {
+        let result = call(&mut device, &mut other, &request("t1", action), now);
+        assert_eq!(result.status, ResultStatus::Rejected);
+        assert_eq!(result.detail, "transfer belongs to another sender");
+    }
+    let result = call(&mut device, &mut other, &reqs[1], now);
+    assert_eq!(result.detail, "transfer belongs to another sender");
+
+    for req in &reqs[1..] {
+        call(&mut device, &mut owner, req, now);
+    }
+    assert_eq!(stage.0.borrow().active(TARGET), Some(ARTIFACT));
+}
+
+#[test]
+fn transfers_in_flight_are_capped_per_sender_and_overall() {
+    let (mut device, _stage) = device(|m| m.with_max_transfers(3));
+    let mut first = endpoint("warden-1", 1);
+    let mut second = endpoint("warden-2", 3);
+    let now = Instant::now();
+    let begin = |id: &str| requests(id).remove(0);
+
+    call(&mut device, &mut first, &begin("a1"), now);
+    call(&mut device, &mut first, &begin("a2"), now);
+    let refused = call(&mut device, &mut first, &begin("a3"), now);
+    assert_eq!(refused.status, ResultStatus::Rejected);
+    assert_eq!(
+        refused.detail,
+        "too many transfers in flight for this sender"
+    );
+
+    call(&mut device, &mut second, &begin("b1"), now);
+    let refused = call(&mut device, &mut second, &begin("b2"), now);
+    assert_eq!(refused.detail, "too many transfers in flight");
+
+    call(&mut device, &mut first, &request("a1", action::ABORT), now);
+    let accepted = call(&mut device, &mut second, &begin("b2"), now);
+    assert_eq!(accepted.status, ResultStatus::Completed);
+}
+
+#[test]
+fn idle_transfers_expire() {
+    let timeout = Duration::from_secs(60);
+    let (mut device, _stage) = device(|m| {
+        m.with_idle_timeout(timeout)
+            .with_max_transfers_per_sender(1)
+    });
+    let mut warden = endpoint("warden-1", 1);
+    let start = Instant::now();
+    let reqs = requests("t1");
+
+    call(&mut device, &mut warden, &reqs[0], start);
+    // Each request from the owner restarts the idle timeout.
+    let at = start + timeout / 2;
+    assert_eq!(
+        call(&mut device, &mut warden, &reqs[1], at).status,
+        ResultStatus::Completed
+    );
+    let at = at + timeout - Duration::from_secs(1);
+    let status = call(&mut device, &mut warden, &request("t1", action::STATUS), at);
+    assert_eq!(next_offset(&status), Some("4"));
+
+    let at = at + timeout;
+    let status = call(&mut device, &mut warden, &request("t1", action::STATUS), at);
+    assert_eq!(status.status, ResultStatus::Rejected);
+    assert_eq!(status.detail, "unknown transfer_id");
+
+    // The expired transfer no longer counts against the sender.
+    let restarted = call(&mut device, &mut warden, &reqs[0], at);
+    assert_eq!(restarted.status, ResultStatus::Completed);
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+use hmf_core::Envelope;
+use hmf_core::correlation::{Event, Outcome, PendingRequests};
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    Command, CommandPayload, CommandRequest, OpResult, Payload, ResultStatus, VerifiedEnvelope,
+};
+use hmf_core::error::{IngressError, TrustError, ValidateError, VerifyError};
+use hmf_core::ids::DeviceId;
+use hmf_core::runtime::Runtime;
+use hmf_core::runtime::handler::{Authorizer, CommandHandler, VerifiedContext};
+use hmf_core::trust::TrustRegistry;
+
+struct AllowAll;
+
+impl Authorizer for AllowAll {
+    fn authorize(&self, _ctx: &VerifiedContext, _payload: &Payload) -> bool {
+        true
+    }
+}
+
+struct Complete;
+
+impl CommandHandler for Complete {
+    fn handle_command(&mut self, ctx: &VerifiedContext, req: &CommandRequest) -> OpResult {
+        assert_eq!(ctx.envelope().transaction_id, *ctx.transaction_id());
+        OpResult {
+            status: ResultStatus::Completed,
+            detail: req.request_id.clone(),
+        }
+    }
+}
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn endpoint(name: &str, seed: u8) -> EndpointContext {
+    EndpointContext::new(
+        DeviceId::new(name),
+        &format!("{name}:ed25519:v1"),
+        key(seed),
+    )
+}
+
+fn registry(name: &str, seed: u8) -> TrustRegistry {
+    let mut registry = TrustRegistry::new();
+    registry
+        .approve(
+            &format!("{name}:ed25519:v1"),
+            DeviceId::new(name),
+            key(seed).verifying_key(),
+        )
+        .unwrap();
+    registry
+}
+
+fn request() -> Payload {
+    Payload::Command(Command {
+        payload: Some(CommandPayload::Request(CommandRequest {
+            request_id: "req-1".to_string(),
+            command: "open".to_string(),
+            target: "valve-1".to_string(),
+            params: BTreeMap::new(),
+            blob: Vec::new(),
+            requires_confirmation: false,
+        })),
+    })
+}
+
+fn signed_request() -> Envelope {
+    endpoint("warden-1", 1)
+        .send(request(), Routing::new("cmd", "device-1", "line-2"))
+        .unwrap()
+}
+
+#[test]
+fn verify_accepts_an_approved_signature() {
+    let env = signed_request();
+    let verified = VerifiedEnvelope::verify(
+        env.clone(),
+        &registry("warden-1", 1),
+        &DecodeLimits::default(),
+    )
+    .unwrap();
+
+    assert_eq!(verified.envelope(), &env);
+    assert_eq!(Some(verified.payload()), env.payload.as_ref());
+    assert_eq!(verified.into_envelope(), env);
+}
+
+#[test]
+fn verify_rejects_tampering_unknown_keys_and_bad_structure() {
+    let limits = DecodeLimits::default();
+    let registry = registry("warden-1", 1);
+
+    let mut tampered = signed_request();
+    tampered.scope = "line-3".to_string();
+    assert!(matches!(
+        VerifiedEnvelope::verify(tampered, &registry, &limits),
+        Err(IngressError::Signature(VerifyError::BadSignature))
+    ));
+
+    let unknown = endpoint("rogue-1", 9)
+        .send(request(), Routing::new("cmd", "device-1", "line-2"))
+        .unwrap();
+    assert!(matches!(
+        VerifiedEnvelope::verify(unknown, &registry, &limits),
+        Err(IngressError::Trust(TrustError::UnknownKeyId { .. }))
+    ));
+
+    let mut unsigned = signed_request();
+    unsigned.signature.clear();
+    assert!(matches!(
+        VerifiedEnvelope::verify(unsigned, &registry, &limits),
+        Err(IngressError::Validate(
+            ValidateError::BadSignatureLen { .. }
+        ))
+    ));
+}
+
+#[test]
+fn accepted_responses_resolve_pending_requests() {
+    let mut warden = Runtime::new(endpoint("warden-1", 1), registry("device-1", 2), AllowAll);
+    let mut device = Runtime::new(endpoint("device-1", 2), registry("warden-1", 1), AllowAll)
+        .with_command_handler(Complete);
+
+    let request = warden
+        .endpoint_mut()
+        .send(request(), Routing::new("cmd", "device-1", "line-2"))
+        .unwrap();
+    let mut pending = PendingRequests::new();
+    pending
+        .track(
+            &request,
+            DeviceId::new("device-1"),
+            Instant::now(),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+    let accepted = device.process(request, Instant::now()).unwrap();
+    assert_eq!(accepted.responses.len(), 2);
+
+    let events = accepted
+        .responses
+        .into_iter()
+        .map(|response| {
+            let accepted = warden.process(response, Instant::now()).unwrap();
+            assert!(accepted.responses.is_empty());
+            pending.on_response(&accepted.envelope).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    assert!(matches!(events[0], Event::Acknowledged { .. }));
+    let Event::Resolved(resolution) = &events[1] else {
+        panic!("expected a resolution, got {:?}", events[1]);
+    };
+    assert_eq!(
+        resolution.outcome,
+        Outcome::Completed(OpResult {
+            status: ResultStatus::Completed,
+            detail: "req-1".to_string(),
+        })
+    );
+    assert!(pending.is_empty());
+}
@@ -0,0 +1,87 @@
+use ed25519_dalek::SigningKey;
+use hmf_core::Envelope;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::sign::{sign_envelope_ed25519, verify_envelope_ed25519};
+use hmf_core::envelope::signing_bytes::envelope_signing_bytes;
+use hmf_core::envelope::{
+    Health, LifecycleHeartbeat, Payload, SigAlg, Telemetry, TelemetryPayload,
+};
+use hmf_core::error::{ValidateError, VerifyError};
+use hmf_core::ids::DeviceId;
+
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&[3; 32])
+}
+
+fn signed() -> Envelope {
+    let payload = Payload::Telemetry(Telemetry {
+        payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 1_000,
+            health: Health::Ok,
+        })),
+    });
+    EndpointContext::new(DeviceId::new("device-1"), "device-1:ed25519:v1", key())
+        .send(payload, Routing::new("hb", "warden", "site"))
+        .unwrap()
+}
+
+fn verify(env: &Envelope) -> Result<(), VerifyError> {
+    verify_envelope_ed25519(env, &key().verifying_key())
+}
+
+#[test]
+fn valid_signature_verifies() {
+    assert_eq!(verify(&signed()), Ok(()));
+}
+
+#[test]
+fn each_failure_has_its_own_reason() {
+    let mut env = signed();
+    env.sig_alg = SigAlg::Unknown(7);
+    assert_eq!(
+        verify(&env),
+        Err(VerifyError::UnsupportedAlgorithm {
+            got: SigAlg::Unknown(7)
+        })
+    );
+
+    let mut env = signed();
+    env.signature.pop();
+    assert_eq!(
+        verify(&env),
+        Err(VerifyError::BadSignatureLen {
+            expected: 64,
+            got: 63
+        })
+    );
+
+    let mut env = signed();
+    env.payload = None;
+    assert_eq!(verify(&env), Err(VerifyError::MissingPayload));
+
+    // S = L + S' is the classic malleated form of a valid signature.
+    let mut env = signed();
+    env.signature[63] |= 0xf0;
+    assert!(matches!(
+        verify(&env),
+        Err(VerifyError::MalformedSignature { .. })
+    ));
+
+    let mut env = signed();
+    env.signature[32] ^= 1;
+    assert_eq!(verify(&env), Err(VerifyError::BadSignature));
+}
+
+#[test]
+fn payload_less_envelopes_are_errors_not_panics() {
+    let mut env = signed();
+    env.payload = None;
+    assert!(matches!(
+        envelope_signing_bytes(&env),
+        Err(ValidateError::MissingPayload)
+    ));
+    assert!(matches!(
+        sign_envelope_ed25519(&mut env, "device-1:ed25519:v1", &key()),
+        Err(ValidateError::MissingPayload)
+    ));
+}
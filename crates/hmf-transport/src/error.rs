@@ -1,3 +1,7 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use hmf_core::ids::DeviceId;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,6 +17,81 @@ pub enum TransportError {
 
     #[error(transparent)]
     Validate(#[from] hmf_core::error::ValidateError),
+
+    #[error("tls error: {0}")]
+    Tls(#[from] rustls::Error),
+
+    #[error("peer negotiated {negotiated}; only TLS 1.3 is permitted")]
+    TlsVersion { negotiated: String },
+
+    #[error("invalid server name: {0}")]
+    InvalidServerName(String),
+
+    #[error("pem error: {0}")]
+    Pem(String),
+
+    #[error("invalid peer verifier: {0}")]
+    Verifier(String),
+
+    #[error("peer transport key is not pinned")]
+    UnpinnedPeer,
+
+    #[error("peer key is pinned to {pinned}, envelope claims sender {sender_id}")]
+    PeerMismatch {
+        pinned: DeviceId,
+        sender_id: DeviceId,
+    },
+
+    #[error("stream bound to {expected:?} carried {got:?}")]
+    StreamMismatch {
+        expected: crate::transport::StreamKey,
+        got: crate::transport::StreamKey,
+    },
+
+    #[error("connection limit reached ({max})")]
+    ConnectionLimit { max: usize },
+
+    #[error("connection limit for peer {peer} reached ({max})")]
+    PeerConnectionLimit { peer: IpAddr, max: usize },
+
+    #[error("peer {peer} exceeded its frame rate")]
+    RateLimited { peer: IpAddr },
+
+    #[error("more than {max} frames in flight on one connection")]
+    InFlightExceeded { max: usize },
+
+    #[error("no record started within {after:?}")]
+    IdleTimeout { after: Duration },
+
+    #[error("record not completed within {after:?}")]
+    ReadTimeout { after: Duration },
+
+    #[error("TLS handshake not completed within {after:?}")]
+    HandshakeTimeout { after: Duration },
+
+    #[cfg(feature = "quic")]
+    #[error("invalid quic configuration: {0}")]
+    QuicConfig(String),
+
+    #[cfg(feature = "quic")]
+    #[error("quic connect error: {0}")]
+    QuicConnect(#[from] quinn::ConnectError),
+
+    #[cfg(feature = "quic")]
+    #[error("quic connection error: {0}")]
+    QuicConnection(#[from] quinn::ConnectionError),
+
+    #[cfg(feature = "quic")]
+    #[error("quic write error: {0}")]
+    QuicWrite(#[from] quinn::WriteError),
+
+    #[cfg(feature = "quic")]
+    #[error("quic read error: {0}")]
+    QuicRead(#[from] quinn::ReadError),
+
+    #[cfg(feature = "quic")]
+    #[error("quic stream closed: {0}")]
+    QuicStreamClosed(#[from] quinn::ClosedStream),
 }
 
 #[derive(Debug, Error)]
@@ -25,4 +104,16 @@ pub enum RecordError {
 
     #[error("truncated record: expected {expected} bytes, got {got}")]
     Truncated { expected: usize, got: usize },
+
+    #[error("not an HMF record: bad magic {got:02x?}")]
+    BadMagic { got: [u8; 4] },
+
+    #[error("record header checksum mismatch: expected {expected:08x}, got {got:08x}")]
+    HeaderChecksum { expected: u32, got: u32 },
+
+    #[error("unsupported record format version {got}")]
+    UnsupportedVersion { got: u8 },
+
+    #[error("unknown record flags {flags:#04x}")]
+    UnknownFlags { flags: u8 },
 }
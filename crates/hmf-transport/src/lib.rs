@@ -1,3 +1,4 @@
 pub mod error;
+pub mod limits;
 pub mod record;
 pub mod transport;
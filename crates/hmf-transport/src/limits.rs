@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use hmf_core::envelope::limits::DecodeLimits;
+
+use crate::error::TransportError;
+
+pub const DEFAULT_MAX_CONNECTIONS: usize = 1024;
+pub const DEFAULT_MAX_CONNECTIONS_PER_PEER: usize = 8;
+pub const DEFAULT_FRAMES_PER_SEC: u32 = 50;
+pub const DEFAULT_FRAME_BURST: u32 = 100;
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 32;
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport hardening limits (transport.md, "Transport hardening
+/// requirements"; envelope.md, "Size limits").
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    max_connections: usize,
+    max_connections_per_peer: usize,
+    frames_per_sec: u32,
+    frame_burst: u32,
+    max_in_flight: usize,
+    idle_timeout: Duration,
+    read_timeout: Duration,
+    decode: DecodeLimits,
+    strict_decode: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            max_connections_per_peer: DEFAULT_MAX_CONNECTIONS_PER_PEER,
+            frames_per_sec: DEFAULT_FRAMES_PER_SEC,
+            frame_burst: DEFAULT_FRAME_BURST,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            decode: DecodeLimits::default(),
+            strict_decode: false,
+        }
+    }
+}
+
+impl Limits {
+    /// Open connections across all peers.
+    pub fn with_max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    /// Open connections from one peer address.
+    pub fn with_max_connections_per_peer(mut self, max: usize) -> Self {
+        self.max_connections_per_peer = max;
+        self
+    }
+
+    /// Sustained frame rate and burst allowed per peer address.
+    pub fn with_frame_rate(mut self, frames_per_sec: u32, burst: u32) -> Self {
+        self.frames_per_sec = frames_per_sec;
+        self.frame_burst = burst;
+        self
+    }
+
+    /// Frames read from one connection and not yet released by the consumer.
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = max;
+        self
+    }
+
+    /// Longest wait for the next record to start.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Longest time a started record may take to complete.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Field limits applied while decoding each received envelope.
+    ///
+    /// Applied by the async listener and codec, by blocking adapters wrapped
+    /// in [`Limited`](crate::transport::Limited) and by QUIC receivers built
+    /// with them; the runtime's own [`DecodeLimits`] still apply.
+    pub fn with_decode_limits(mut self, limits: DecodeLimits) -> Self {
+        self.decode = limits;
+        self
+    }
+
+    /// Refuses envelopes that are not canonically encoded or carry unknown
+    /// fields, instead of silently dropping what the decoder does not know.
+    ///
+    /// Applied wherever the decode limits are.
+    pub fn with_strict_decode(mut self, strict: bool) -> Self {
+        self.strict_decode = strict;
+        self
+    }
+
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    pub fn max_connections_per_peer(&self) -> usize {
+        self.max_connections_per_peer
+    }
+
+    pub fn frames_per_sec(&self) -> u32 {
+        self.frames_per_sec
+    }
+
+    pub fn frame_burst(&self) -> u32 {
+        self.frame_burst
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        self.read_timeout
+    }
+
+    pub fn decode_limits(&self) -> &DecodeLimits {
+        &self.decode
+    }
+
+    pub fn strict_decode(&self) -> bool {
+        self.strict_decode
+    }
+}
+
+/// Token bucket; time is supplied by the caller.
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket holding `burst` tokens, refilled at `per_sec`.
+    pub fn new(per_sec: u32, burst: u32, now: Instant) -> Self {
+        Self {
+            capacity: f64::from(burst),
+            tokens: f64::from(burst),
+            refill_per_sec: f64::from(per_sec),
+            last: now,
+        }
+    }
+
+    /// Takes one token if available.
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the bucket has refilled to its burst by `now`, making it
+    /// indistinguishable from a new one.
+    pub fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens + elapsed * self.refill_per_sec >= self.capacity
+    }
+}
+
+#[derive(Debug)]
+struct Peer {
+    connections: usize,
+    frames: TokenBucket,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    connections: usize,
+    peers: HashMap<IpAddr, Peer>,
+}
+
+/// Connection counts and frame budgets per peer address, shared by all
+/// connections of one listener.
+///
+/// A peer's frame budget outlives its connections, so reconnecting does not
+/// restore spent frames. The entry of a peer with no connections is dropped
+/// once its budget has refilled, since a new entry would start identical.
+#[derive(Clone, Debug)]
+pub struct PeerTable {
+    limits: Limits,
+    table: Arc<Mutex<Table>>,
+}
+
+impl PeerTable {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            table: Arc::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Open connections across all peers.
+    pub fn connections(&self) -> usize {
+        self.lock().connections
+    }
+
+    /// Peer addresses with open connections or a partly spent frame budget.
+    pub fn tracked_peers(&self) -> usize {
+        self.lock().peers.len()
+    }
+
+    /// Admits a new connection from `peer`; the slot is held until the permit drops.
+    ///
+    /// # Errors
+    ///
+    /// [`TransportError::ConnectionLimit`] or
+    /// [`TransportError::PeerConnectionLimit`] when a cap is reached.
+    pub fn admit(&self, peer: IpAddr, now: Instant) -> Result<PeerPermit, TransportError> {
+        let mut table = self.lock();
+        table
+            .peers
+            .retain(|_, p| p.connections > 0 || !p.frames.is_full(now));
+        if table.connections >= self.limits.max_connections {
+            return Err(TransportError::ConnectionLimit {
+                max: self.limits.max_connections,
+            });
+        }
+        let entry = table.peers.entry(peer).or_insert_with(|| Peer {
+            connections: 0,
+            frames: TokenBucket::new(self.limits.frames_per_sec, self.limits.frame_burst, now),
+        });
+        if entry.connections >= self.limits.max_connections_per_peer {
+            return Err(TransportError::PeerConnectionLimit {
+                peer,
+                max: self.limits.max_connections_per_peer,
+            });
+        }
+        entry.connections += 1;
+        table.connections += 1;
+        Ok(PeerPermit {
+            peer,
+            table: self.clone(),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Table> {
+        self.table.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// An admitted connection. Dropping it frees the connection slot.
+#[derive(Debug)]
+pub struct PeerPermit {
+    peer: IpAddr,
+    table: PeerTable,
+}
+
+impl PeerPermit {
+    pub fn peer(&self) -> IpAddr {
+        self.peer
+    }
+
+    /// Charges one frame to the peer's shared rate budget.
+    ///
+    /// # Errors
+    ///
+    /// [`TransportError::RateLimited`] when the budget is exhausted.
+    pub fn take_frame(&self, now: Instant) -> Result<(), TransportError> {
+        let mut table = self.table.lock();
+        let admitted = table
+            .peers
+            .get_mut(&self.peer)
+            .is_some_and(|p| p.frames.try_take(now));
+        if admitted {
+            Ok(())
+        } else {
+            Err(TransportError::RateLimited { peer: self.peer })
+        }
+    }
+}
+
+impl Drop for PeerPermit {
+    fn drop(&mut self) {
+        let mut table = self.table.lock();
+        table.connections -= 1;
+        // The frame budget stays until it refills; see `PeerTable::admit`.
+        if let Some(peer) = table.peers.get_mut(&self.peer) {
+            peer.connections -= 1;
+        }
+    }
+}
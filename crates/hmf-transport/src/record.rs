@@ -1,11 +1,29 @@
-use std::io::Read;
+use std::io::{self, ErrorKind, Read};
 
 use crate::error::RecordError;
 
 pub const MAX_RECORD_LEN: usize = 1024 * 1024; // 1 MiB
 
+/// Magic bytes opening every record header.
+pub const RECORD_MAGIC: [u8; 4] = *b"HMFR";
+
+/// Record format version written by this implementation.
+///
+/// Version 1 was the bare `[u32_be length]` prefix and is not accepted.
+pub const RECORD_VERSION: u8 = 2;
+
+/// No flags are defined yet; receivers reject any set bit.
+pub const RECORD_FLAGS_NONE: u8 = 0;
+
+/// Length of the record header preceding the payload.
+///
+/// Format: `[magic:4][version:1][flags:1][u32_be length][u32_be crc32 of the preceding 10 bytes]`
+pub const RECORD_HEADER_LEN: usize = 14;
+
+const CRC_OFFSET: usize = RECORD_HEADER_LEN - 4;
+
 /// Encode a payload into an HMF Record.
-/// Format: [u32_be length][payload bytes]
+/// Format: `[header][payload bytes]`, see [`RECORD_HEADER_LEN`].
 pub fn record_encode(payload: &[u8]) -> Result<Vec<u8>, RecordError> {
     let len = payload.len();
 
@@ -23,34 +41,198 @@ pub fn record_encode(payload: &[u8]) -> Result<Vec<u8>, RecordError> {
         });
     }
 
-    let mut out = Vec::with_capacity(4 + len);
+    let mut out = Vec::with_capacity(RECORD_HEADER_LEN + len);
+    out.extend_from_slice(&RECORD_MAGIC);
+    out.push(RECORD_VERSION);
+    out.push(RECORD_FLAGS_NONE);
     out.extend_from_slice(&(len as u32).to_be_bytes());
+    let crc = crc32fast::hash(&out[..CRC_OFFSET]);
+    out.extend_from_slice(&crc.to_be_bytes());
     out.extend_from_slice(payload);
     Ok(out)
 }
 
-/// Decode one HMF Record from a stream.
-/// Reads exactly: [u32_be length][payload bytes]
-pub fn record_decode<R: Read>(reader: &mut R, max_len: usize) -> Result<Vec<u8>, RecordError> {
-    let mut len_buf = [0u8; 4];
+/// Parses a record header into the payload length, enforcing `max_len`.
+///
+/// Magic and checksum are checked before any other field is trusted, so a
+/// desynchronised stream or a non-HMF peer fails here rather than in decode.
+pub fn record_payload_len(
+    header: &[u8; RECORD_HEADER_LEN],
+    max_len: usize,
+) -> Result<usize, RecordError> {
+    let magic = [header[0], header[1], header[2], header[3]];
+    if magic != RECORD_MAGIC {
+        return Err(RecordError::BadMagic { got: magic });
+    }
+
+    let expected = crc32fast::hash(&header[..CRC_OFFSET]);
+    let got = u32::from_be_bytes([header[10], header[11], header[12], header[13]]);
+    if got != expected {
+        return Err(RecordError::HeaderChecksum { expected, got });
+    }
 
-    reader.read_exact(&mut len_buf)?;
+    if header[4] != RECORD_VERSION {
+        return Err(RecordError::UnsupportedVersion { got: header[4] });
+    }
+    if header[5] != RECORD_FLAGS_NONE {
+        return Err(RecordError::UnknownFlags { flags: header[5] });
+    }
 
-    let len = u32::from_be_bytes(len_buf) as usize;
+    let len = u32::from_be_bytes([header[6], header[7], header[8], header[9]]) as usize;
     if len > max_len {
         return Err(RecordError::TooLarge { len, max: max_len });
     }
+    Ok(len)
+}
+
+/// Offset of the first valid record header in `buf`, if any.
+///
+/// For receivers that choose to resynchronise after garbage rather than close;
+/// stream transports in this crate close instead.
+pub fn record_resync(buf: &[u8], max_len: usize) -> Option<usize> {
+    buf.windows(RECORD_HEADER_LEN).position(|w| {
+        w.starts_with(&RECORD_MAGIC)
+            && w.first_chunk::<RECORD_HEADER_LEN>()
+                .is_some_and(|h| record_payload_len(h, max_len).is_ok())
+    })
+}
+
+/// Decode one HMF Record from a stream.
+/// Reads exactly: `[header][payload bytes]`
+///
+/// A stream that ends before the first header byte yields `Io(UnexpectedEof)`;
+/// one that ends later yields [`RecordError::Truncated`] with the bytes actually
+/// read of the header or payload. Progress is lost on `WouldBlock`; non-blocking
+/// readers should use [`RecordDecoder`].
+pub fn record_decode<R: Read>(reader: &mut R, max_len: usize) -> Result<Vec<u8>, RecordError> {
+    let mut header = [0u8; RECORD_HEADER_LEN];
+
+    match read_full(reader, &mut header)? {
+        0 => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+        RECORD_HEADER_LEN => {}
+        got => {
+            return Err(RecordError::Truncated {
+                expected: RECORD_HEADER_LEN,
+                got,
+            });
+        }
+    }
+
+    let len = record_payload_len(&header, max_len)?;
 
     let mut payload = vec![0u8; len];
-    reader.read_exact(&mut payload).map_err(|e| {
-        if e.kind() == std::io::ErrorKind::UnexpectedEof {
-            RecordError::Truncated {
+    let got = read_full(reader, &mut payload)?;
+    if got < len {
+        return Err(RecordError::Truncated { expected: len, got });
+    }
+    Ok(payload)
+}
+
+/// Reads until `buf` is full or the stream ends, returning the bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, RecordError> {
+    let mut got = 0;
+    while got < buf.len() {
+        match reader.read(&mut buf[got..]) {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(got)
+}
+
+/// The error for a stream that ended with `buf` holding a partial record.
+pub(crate) fn record_truncated(buf: &[u8], max_len: usize) -> RecordError {
+    match buf.first_chunk::<RECORD_HEADER_LEN>() {
+        Some(header) => match record_payload_len(header, max_len) {
+            Ok(len) => RecordError::Truncated {
                 expected: len,
-                got: 0,
+                got: buf.len() - RECORD_HEADER_LEN,
+            },
+            Err(e) => e,
+        },
+        None => RecordError::Truncated {
+            expected: RECORD_HEADER_LEN,
+            got: buf.len(),
+        },
+    }
+}
+
+/// Incremental record decoder for non-blocking readers and arbitrary byte slices.
+///
+/// Bytes are buffered until a whole record is present, so no progress is lost
+/// when a read returns `WouldBlock`. After a header error the stream cannot be
+/// trusted and the decoder should be dropped along with the connection.
+#[derive(Debug)]
+pub struct RecordDecoder {
+    buf: Vec<u8>,
+    max_len: usize,
+}
+
+impl RecordDecoder {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            max_len,
+        }
+    }
+
+    /// Bytes received but not yet returned as part of a record.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Appends `bytes` to the buffer.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Takes the next complete record payload, or `None` if more bytes are needed.
+    pub fn next_record(&mut self) -> Result<Option<Vec<u8>>, RecordError> {
+        let Some(header) = self.buf.first_chunk::<RECORD_HEADER_LEN>() else {
+            return Ok(None);
+        };
+        let len = record_payload_len(header, self.max_len)?;
+        let total = RECORD_HEADER_LEN + len;
+        if self.buf.len() < total {
+            self.buf.reserve(total - self.buf.len());
+            return Ok(None);
+        }
+
+        let payload = self.buf[RECORD_HEADER_LEN..total].to_vec();
+        self.buf.drain(..total);
+        Ok(Some(payload))
+    }
+
+    /// Signals end of input: fails with the exact truncation if a partial record remains.
+    pub fn finish(&self) -> Result<(), RecordError> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        Err(record_truncated(&self.buf, self.max_len))
+    }
+
+    /// Reads from `reader` until a record is complete.
+    ///
+    /// `Ok(None)` means the stream ended on a record boundary. `WouldBlock` and
+    /// other I/O errors are returned as `RecordError::Io` with everything read so
+    /// far kept; call again once the reader is ready.
+    pub fn read_from<R: Read>(&mut self, reader: &mut R) -> Result<Option<Vec<u8>>, RecordError> {
+        let mut chunk = [0u8; 8 * 1024];
+        loop {
+            if let Some(payload) = self.next_record()? {
+                return Ok(Some(payload));
+            }
+            match reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.finish()?;
+                    return Ok(None);
+                }
+                Ok(n) => self.feed(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
             }
-        } else {
-            RecordError::Io(e)
         }
-    })?;
-    Ok(payload)
+    }
 }
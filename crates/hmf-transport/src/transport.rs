@@ -1 +1,261 @@
+/// Async record codec for tokio streams.
+#[cfg(feature = "async")]
+pub mod codec;
+/// In-process connection pairs carrying encoded records, for tests and
+/// single-process deployments.
+pub mod memory;
+/// QUIC (TLS 1.3) with one stream per sender stream and message class.
+#[cfg(feature = "quic")]
+pub mod quic;
+/// Async listener serving each connection on its own task.
+#[cfg(feature = "async")]
+pub mod server;
+/// Plaintext TCP records. Carries no confidentiality or channel integrity and
+/// is not a compliant transport profile (transport.md); local development only.
 pub mod tcp;
+/// TLS 1.3 over TCP.
+pub mod tls;
+/// Unix domain socket records, for co-located processes.
+#[cfg(unix)]
+pub mod unix;
+
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use hmf_core::envelope::{Envelope, MsgClass, envelope_validate, envelope_validate_with};
+use hmf_core::ids::{DeviceId, InstanceId};
+use hmf_wire_proto::wire::protobuf::{
+    envelope_decode_strict_with, envelope_decode_with, envelope_encode,
+};
+
+use crate::error::{RecordError, TransportError};
+use crate::limits::Limits;
+use crate::record::{MAX_RECORD_LEN, record_decode, record_encode};
+use tls::SpkiHash;
+
+/// A connected link that carries framed envelopes in both directions.
+///
+/// Adapters validate on both sides of the wire; they never verify signatures
+/// or authorize. Every received envelope still goes through the runtime.
+pub trait Connection {
+    /// Validates, encodes and sends `env`.
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError>;
+
+    /// Blocks for the next envelope; `Ok(None)` once the peer has closed.
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError>;
+
+    /// Network address of the peer, where the adapter has one.
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// A [`Connection`] that decodes each record as it reads it, so the caller
+/// chooses the decode settings.
+///
+/// [`Connection::recv`] decodes under the default [`Limits`].
+pub trait RecvWith: Connection {
+    /// Blocks for the next envelope, decoded under `limits`.
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError>;
+}
+
+/// The accepting side of an adapter.
+pub trait Transport {
+    type Conn: Connection;
+
+    /// Blocks until a peer connects and the adapter's handshake completes.
+    fn accept(&mut self) -> Result<Self::Conn, TransportError>;
+}
+
+/// A blocking adapter, or one of its connections, that decodes every received
+/// envelope under its own [`Limits`] instead of the defaults.
+///
+/// Only the decode settings apply; connection caps, frame rates and timeouts
+/// are enforced by the async listener. QUIC decodes on its stream tasks and
+/// takes its limits at construction instead (see `quic::QuicListener::with_limits`).
+#[derive(Debug)]
+pub struct Limited<T> {
+    inner: T,
+    limits: Limits,
+}
+
+impl<T> Limited<T> {
+    pub fn new(inner: T, limits: Limits) -> Self {
+        Self { inner, limits }
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<C: RecvWith> Connection for Limited<C> {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        self.inner.send(env)
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        self.inner.recv_with(&self.limits)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+impl<T> Transport for Limited<T>
+where
+    T: Transport,
+    T::Conn: RecvWith,
+{
+    type Conn = Limited<T::Conn>;
+
+    fn accept(&mut self) -> Result<Limited<T::Conn>, TransportError> {
+        Ok(Limited::new(self.inner.accept()?, self.limits.clone()))
+    }
+}
+
+/// Ordering domain of an envelope: one `(sender_id, sender_instance)` stream
+/// per message class.
+///
+/// Envelopes sharing a key stay in order; a multiplexing transport may deliver
+/// different keys independently so one cannot stall another.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StreamKey {
+    pub sender_id: DeviceId,
+    pub sender_instance: InstanceId,
+    pub msg_class: MsgClass,
+}
+
+impl StreamKey {
+    pub fn of(env: &Envelope) -> Self {
+        Self {
+            sender_id: env.sender_id.clone(),
+            sender_instance: env.sender_instance.clone(),
+            msg_class: env.msg_class.clone(),
+        }
+    }
+}
+
+/// The transport's view of who sent a record.
+///
+/// Informational only: it may narrow what a connection carries (see
+/// [`tls::SpkiPins`]) but never stands in for the envelope signature.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Peer {
+    /// Network address, where the adapter has one.
+    pub addr: Option<SocketAddr>,
+    /// SHA-256 of the peer certificate's SPKI on a TLS or QUIC link that presented one.
+    pub spki_sha256: Option<SpkiHash>,
+}
+
+impl From<SocketAddr> for Peer {
+    fn from(addr: SocketAddr) -> Self {
+        Self {
+            addr: Some(addr),
+            spki_sha256: None,
+        }
+    }
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.addr {
+            Some(addr) => write!(f, "{addr}"),
+            None => f.write_str("local peer"),
+        }
+    }
+}
+
+/// A received envelope with what the transport observed while reading it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inbound {
+    pub envelope: Envelope,
+    /// Receiver-local monotonic time the record was read in full; the first
+    /// observation used for freshness (REQ-TTL-002).
+    pub first_observed: Instant,
+    pub peer: Peer,
+    /// Size of the encoded envelope, excluding the record header.
+    pub encoded_len: usize,
+}
+
+/// Validates, encodes and frames `env` as one record.
+pub(crate) fn encode_envelope(env: &Envelope) -> Result<Vec<u8>, TransportError> {
+    envelope_validate(env)?;
+    let payload = envelope_encode(env)?;
+    Ok(record_encode(&payload)?)
+}
+
+/// Decodes and validates one record payload under `limits`.
+pub(crate) fn decode_envelope(payload: &[u8], limits: &Limits) -> Result<Envelope, TransportError> {
+    let env = if limits.strict_decode() {
+        envelope_decode_strict_with(payload, limits.decode_limits())?
+    } else {
+        envelope_decode_with(payload, limits.decode_limits())?
+    };
+    envelope_validate_with(&env, limits.decode_limits())?;
+    Ok(env)
+}
+
+/// Decodes one record payload that has just been read in full from `peer`.
+pub(crate) fn decode_inbound(
+    payload: &[u8],
+    peer: Peer,
+    limits: &Limits,
+) -> Result<Inbound, TransportError> {
+    let first_observed = Instant::now();
+    Ok(Inbound {
+        envelope: decode_envelope(payload, limits)?,
+        first_observed,
+        peer,
+        encoded_len: payload.len(),
+    })
+}
+
+/// Validates, encodes and frames `env` onto `stream`.
+pub(crate) fn write_envelope<W: Write>(
+    stream: &mut W,
+    env: &Envelope,
+) -> Result<(), TransportError> {
+    let record = encode_envelope(env)?;
+    stream.write_all(&record)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Reads one framed envelope from `stream` and decodes it under `limits`;
+/// `Ok(None)` when the peer closed.
+pub(crate) fn read_envelope<R: Read>(
+    stream: &mut R,
+    peer: Peer,
+    limits: &Limits,
+) -> Result<Option<Inbound>, TransportError> {
+    let record_bytes = match record_decode(stream, MAX_RECORD_LEN) {
+        Ok(b) => b,
+        Err(RecordError::Io(e)) if is_closed(e.kind()) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    decode_inbound(&record_bytes, peer, limits).map(Some)
+}
+
+fn is_closed(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+    )
+}
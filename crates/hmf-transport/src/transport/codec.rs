@@ -0,0 +1,82 @@
+use bytes::{BufMut, BytesMut};
+use hmf_core::envelope::Envelope;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::error::TransportError;
+use crate::limits::Limits;
+use crate::record::{MAX_RECORD_LEN, RECORD_HEADER_LEN, record_payload_len, record_truncated};
+use crate::transport::{Inbound, Peer, decode_inbound, encode_envelope};
+
+/// Frames envelopes as records, for use with `tokio_util::codec::Framed`.
+///
+/// Same wire format and validation as the blocking adapters, under the
+/// decode settings of the codec's [`Limits`]. Decoded envelopes are stamped
+/// with the codec's [`Peer`].
+#[derive(Clone, Debug, Default)]
+pub struct EnvelopeCodec {
+    peer: Peer,
+    limits: Limits,
+}
+
+impl EnvelopeCodec {
+    pub fn new(peer: Peer) -> Self {
+        Self {
+            peer,
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn peer(&self) -> &Peer {
+        &self.peer
+    }
+}
+
+impl Decoder for EnvelopeCodec {
+    type Item = Inbound;
+    type Error = TransportError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Inbound>, TransportError> {
+        let Some(header) = src.first_chunk::<RECORD_HEADER_LEN>() else {
+            return Ok(None);
+        };
+        let len = record_payload_len(header, MAX_RECORD_LEN)?;
+        let total = RECORD_HEADER_LEN + len;
+        if src.len() < total {
+            src.reserve(total - src.len());
+            return Ok(None);
+        }
+
+        let record = src.split_to(total);
+        decode_inbound(
+            &record[RECORD_HEADER_LEN..],
+            self.peer.clone(),
+            &self.limits,
+        )
+        .map(Some)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Inbound>, TransportError> {
+        if let Some(inbound) = self.decode(src)? {
+            return Ok(Some(inbound));
+        }
+        if src.is_empty() {
+            return Ok(None);
+        }
+        // The peer closed mid-record.
+        Err(record_truncated(src, MAX_RECORD_LEN).into())
+    }
+}
+
+impl Encoder<Envelope> for EnvelopeCodec {
+    type Error = TransportError;
+
+    fn encode(&mut self, env: Envelope, dst: &mut BytesMut) -> Result<(), TransportError> {
+        dst.put_slice(&encode_envelope(&env)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{Error, ErrorKind};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use hmf_core::envelope::Envelope;
+
+use crate::error::TransportError;
+use crate::limits::Limits;
+use crate::transport::{
+    Connection, Inbound, Peer, RecvWith, Transport, encode_envelope, read_envelope,
+};
+
+/// One end of an in-process link.
+///
+/// Envelopes cross as framed records, so both ends run the same validation
+/// and size checks as a network adapter.
+#[derive(Debug)]
+pub struct MemoryConnection {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+}
+
+/// Creates two connected ends.
+pub fn pair() -> (MemoryConnection, MemoryConnection) {
+    let (a_tx, b_rx) = channel();
+    let (b_tx, a_rx) = channel();
+    (
+        MemoryConnection { tx: a_tx, rx: a_rx },
+        MemoryConnection { tx: b_tx, rx: b_rx },
+    )
+}
+
+impl Connection for MemoryConnection {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        let record = encode_envelope(env)?;
+        self.tx
+            .send(record)
+            .map_err(|_| Error::from(ErrorKind::BrokenPipe).into())
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        self.recv_with(&Limits::default())
+    }
+}
+
+impl RecvWith for MemoryConnection {
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError> {
+        match self.rx.recv() {
+            Ok(record) => read_envelope(&mut record.as_slice(), Peer::default(), limits),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Accepting side of in-process links; peers connect through a [`MemoryConnector`].
+#[derive(Debug)]
+pub struct MemoryListener {
+    incoming: Receiver<MemoryConnection>,
+    connector: MemoryConnector,
+}
+
+/// Cloneable handle that opens links to a [`MemoryListener`].
+#[derive(Clone, Debug)]
+pub struct MemoryConnector {
+    incoming: Sender<MemoryConnection>,
+}
+
+impl MemoryListener {
+    pub fn new() -> Self {
+        let (incoming_tx, incoming) = channel();
+        Self {
+            incoming,
+            connector: MemoryConnector {
+                incoming: incoming_tx,
+            },
+        }
+    }
+
+    pub fn connector(&self) -> MemoryConnector {
+        self.connector.clone()
+    }
+}
+
+impl Default for MemoryListener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryConnector {
+    /// Opens a link; the other end is handed to the listener's next `accept`.
+    pub fn connect(&self) -> Result<MemoryConnection, TransportError> {
+        let (local, remote) = pair();
+        self.incoming
+            .send(remote)
+            .map_err(|_| Error::from(ErrorKind::ConnectionRefused))?;
+        Ok(local)
+    }
+}
+
+impl Transport for MemoryListener {
+    type Conn = MemoryConnection;
+
+    fn accept(&mut self) -> Result<MemoryConnection, TransportError> {
+        // The listener holds a connector itself, so this never disconnects.
+        self.incoming
+            .recv()
+            .map_err(|_| Error::from(ErrorKind::ConnectionAborted).into())
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use hmf_core::envelope::Envelope;
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+use quinn::{
+    Connection, ConnectionError, Endpoint, ReadError, ReadExactError, RecvStream, SendStream,
+};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+
+use crate::error::{RecordError, TransportError};
+use crate::limits::Limits;
+use crate::record::{MAX_RECORD_LEN, RECORD_HEADER_LEN, record_payload_len};
+use crate::transport::tls::{SpkiHash, spki_sha256};
+use crate::transport::{Inbound, Peer, StreamKey, Transport, decode_inbound, encode_envelope};
+
+/// Envelopes buffered between stream readers and [`QuicReceiver::recv`].
+pub const RECV_QUEUE_DEPTH: usize = 64;
+
+/// Wraps a TLS 1.3 server configuration from [`super::tls`] for QUIC.
+///
+/// Both server-auth-only and mutual configurations are accepted; QUIC never
+/// negotiates anything older than TLS 1.3.
+pub fn server_config(
+    tls: Arc<rustls::ServerConfig>,
+) -> Result<quinn::ServerConfig, TransportError> {
+    let crypto =
+        QuicServerConfig::try_from(tls).map_err(|e| TransportError::QuicConfig(e.to_string()))?;
+    Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+/// Wraps a TLS 1.3 client configuration from [`super::tls`] for QUIC.
+pub fn client_config(
+    tls: Arc<rustls::ClientConfig>,
+) -> Result<quinn::ClientConfig, TransportError> {
+    let crypto =
+        QuicClientConfig::try_from(tls).map_err(|e| TransportError::QuicConfig(e.to_string()))?;
+    Ok(quinn::ClientConfig::new(Arc::new(crypto)))
+}
+
+/// Binds a server endpoint on `addr`. Must be called within a tokio runtime.
+pub fn listen(addr: SocketAddr, config: quinn::ServerConfig) -> Result<Endpoint, TransportError> {
+    Ok(Endpoint::server(config, addr)?)
+}
+
+/// Waits for the next incoming connection and completes its handshake.
+///
+/// Returns `Ok(None)` once the endpoint is closed.
+pub async fn accept(endpoint: &Endpoint) -> Result<Option<Connection>, TransportError> {
+    match endpoint.accept().await {
+        Some(incoming) => Ok(Some(incoming.await?)),
+        None => Ok(None),
+    }
+}
+
+/// Connects to `server_name` at `addr` from an ephemeral local port.
+pub async fn connect(
+    addr: SocketAddr,
+    server_name: &str,
+    config: quinn::ClientConfig,
+) -> Result<Connection, TransportError> {
+    let bind: SocketAddr = if addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let endpoint = Endpoint::client(bind)?;
+    Ok(endpoint.connect_with(config, addr, server_name)?.await?)
+}
+
+/// SHA-256 of the peer's end-entity SPKI, for checks against
+/// [`super::tls::SpkiPins`].
+pub fn peer_spki_sha256(conn: &Connection) -> Option<SpkiHash> {
+    let certs = conn
+        .peer_identity()?
+        .downcast::<Vec<rustls::pki_types::CertificateDer<'static>>>()
+        .ok()?;
+    spki_sha256(certs.first()?).ok()
+}
+
+/// Sends envelopes over a QUIC connection, one unidirectional stream per
+/// [`StreamKey`].
+///
+/// A stalled `Engineering` transfer only blocks its own stream, so heartbeats
+/// and commands keep flowing (transport.md). Clones share the same streams
+/// and may send from separate tasks.
+#[derive(Clone, Debug)]
+pub struct QuicSender {
+    conn: Connection,
+    streams: Arc<Mutex<HashMap<StreamKey, Arc<tokio::sync::Mutex<SendStream>>>>>,
+}
+
+impl QuicSender {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn,
+            streams: Arc::default(),
+        }
+    }
+
+    /// Number of streams opened so far.
+    pub fn stream_count(&self) -> usize {
+        self.lock_streams().len()
+    }
+
+    /// Validates, frames and writes `env` on the stream for its key.
+    pub async fn send(&self, env: &Envelope) -> Result<(), TransportError> {
+        let record = encode_envelope(env)?;
+        let key = StreamKey::of(env);
+
+        let existing = self.lock_streams().get(&key).cloned();
+        let stream = match existing {
+            Some(stream) => stream,
+            None => {
+                let opened = Arc::new(tokio::sync::Mutex::new(self.conn.open_uni().await?));
+                // A concurrent send may have opened the same key meanwhile;
+                // keep the first so ordering within the key holds.
+                self.lock_streams().entry(key).or_insert(opened).clone()
+            }
+        };
+
+        stream.lock().await.write_all(&record).await?;
+        Ok(())
+    }
+
+    /// Finishes every open stream and waits until the peer has received all
+    /// records sent on them.
+    pub async fn finish(&self) -> Result<(), TransportError> {
+        let streams: Vec<_> = self.lock_streams().drain().map(|(_, s)| s).collect();
+        for stream in streams {
+            let mut stream = stream.lock().await;
+            stream.finish()?;
+            // Resolves once the peer acknowledged everything, or stopped reading.
+            let _ = stream.stopped().await;
+        }
+        Ok(())
+    }
+
+    fn lock_streams(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<StreamKey, Arc<tokio::sync::Mutex<SendStream>>>> {
+        self.streams.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Receives envelopes from every stream of a QUIC connection.
+///
+/// Each stream is read by its own task, so a partially delivered record on
+/// one stream never delays records on another. A stream is bound to the
+/// [`StreamKey`] of its first envelope; any other key on it is refused with
+/// [`TransportError::StreamMismatch`] and the stream is stopped.
+///
+/// Records are decoded on the stream tasks, so the decode settings are fixed
+/// when the receiver is started.
+#[derive(Debug)]
+pub struct QuicReceiver {
+    rx: mpsc::Receiver<Result<Inbound, TransportError>>,
+}
+
+impl QuicReceiver {
+    /// Starts reading `conn` under the default [`Limits`]. Must be called
+    /// within a tokio runtime.
+    pub fn new(conn: Connection) -> Self {
+        Self::limited(conn, Limits::default())
+    }
+
+    /// Starts reading `conn`, decoding every envelope under `limits`. Must be
+    /// called within a tokio runtime.
+    pub fn limited(conn: Connection, limits: Limits) -> Self {
+        let (tx, rx) = mpsc::channel(RECV_QUEUE_DEPTH);
+        let peer = Peer {
+            addr: Some(conn.remote_address()),
+            spki_sha256: peer_spki_sha256(&conn),
+        };
+        tokio::spawn(accept_streams(conn, peer, limits, tx));
+        Self { rx }
+    }
+
+    /// Next envelope from any stream; `None` once the connection is closed
+    /// and every stream has drained.
+    pub async fn recv(&mut self) -> Option<Result<Inbound, TransportError>> {
+        self.rx.recv().await
+    }
+}
+
+/// Blocking [`super::Connection`] over QUIC, driven by a tokio runtime.
+///
+/// Lets blocking callers share code with the other adapters. Must not be
+/// used from a thread owned by `handle`'s runtime.
+///
+/// Dropping it delivers what was sent and closes the connection, so the peer
+/// sees end-of-stream as it would on TCP. Both happen on a task spawned onto
+/// `handle`, so dropping never blocks and is safe inside the runtime.
+#[derive(Debug)]
+pub struct QuicConnection {
+    handle: Handle,
+    conn: Connection,
+    sender: QuicSender,
+    receiver: QuicReceiver,
+}
+
+impl QuicConnection {
+    pub fn new(handle: Handle, conn: Connection) -> Self {
+        Self::limited(handle, conn, Limits::default())
+    }
+
+    /// Like [`QuicConnection::new`], decoding every received envelope under `limits`.
+    pub fn limited(handle: Handle, conn: Connection, limits: Limits) -> Self {
+        let _guard = handle.enter();
+        Self {
+            sender: QuicSender::new(conn.clone()),
+            receiver: QuicReceiver::limited(conn.clone(), limits),
+            conn,
+            handle,
+        }
+    }
+}
+
+impl Drop for QuicConnection {
+    fn drop(&mut self) {
+        let sender = self.sender.clone();
+        let conn = self.conn.clone();
+        self.handle.spawn(async move {
+            let _ = sender.finish().await;
+            conn.close(0u32.into(), b"");
+        });
+    }
+}
+
+impl super::Connection for QuicConnection {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        self.handle.block_on(self.sender.send(env))
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        self.handle.block_on(self.receiver.recv()).transpose()
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        Some(self.conn.remote_address())
+    }
+}
+
+/// Blocking [`Transport`] over a QUIC server endpoint.
+#[derive(Debug)]
+pub struct QuicListener {
+    handle: Handle,
+    endpoint: Endpoint,
+    limits: Limits,
+}
+
+impl QuicListener {
+    pub fn new(handle: Handle, endpoint: Endpoint) -> Self {
+        Self {
+            handle,
+            endpoint,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Decode settings for envelopes received on accepted connections.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        Ok(self.endpoint.local_addr()?)
+    }
+}
+
+impl Transport for QuicListener {
+    type Conn = QuicConnection;
+
+    fn accept(&mut self) -> Result<QuicConnection, TransportError> {
+        let conn = self
+            .handle
+            .block_on(accept(&self.endpoint))?
+            .ok_or(ConnectionError::LocallyClosed)?;
+        Ok(QuicConnection::limited(
+            self.handle.clone(),
+            conn,
+            self.limits.clone(),
+        ))
+    }
+}
+
+async fn accept_streams(
+    conn: Connection,
+    peer: Peer,
+    limits: Limits,
+    tx: mpsc::Sender<Result<Inbound, TransportError>>,
+) {
+    loop {
+        match conn.accept_uni().await {
+            Ok(stream) => {
+                tokio::spawn(read_stream(
+                    stream,
+                    peer.clone(),
+                    limits.clone(),
+                    tx.clone(),
+                ));
+            }
+            Err(ConnectionError::ApplicationClosed(_) | ConnectionError::LocallyClosed) => return,
+            Err(e) => {
+                let _ = tx.send(Err(e.into())).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn read_stream(
+    mut stream: RecvStream,
+    peer: Peer,
+    limits: Limits,
+    tx: mpsc::Sender<Result<Inbound, TransportError>>,
+) {
+    let mut bound: Option<StreamKey> = None;
+    loop {
+        let inbound = match read_record(&mut stream, &peer, &limits).await {
+            Ok(Some(inbound)) => inbound,
+            Ok(None) => return,
+            Err(e) => {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+        };
+
+        let key = StreamKey::of(&inbound.envelope);
+        match &bound {
+            None => bound = Some(key),
+            Some(expected) if *expected != key => {
+                let _ = stream.stop(0u32.into());
+                let _ = tx
+                    .send(Err(TransportError::StreamMismatch {
+                        expected: expected.clone(),
+                        got: key,
+                    }))
+                    .await;
+                return;
+            }
+            Some(_) => {}
+        }
+
+        if tx.send(Ok(inbound)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads one record and decodes it under `limits`; `Ok(None)` when the
+/// stream finished on a record boundary.
+async fn read_record(
+    stream: &mut RecvStream,
+    peer: &Peer,
+    limits: &Limits,
+) -> Result<Option<Inbound>, TransportError> {
+    let mut header = [0u8; RECORD_HEADER_LEN];
+    match stream.read_exact(&mut header).await {
+        Ok(()) => {}
+        Err(ReadExactError::FinishedEarly(0)) => return Ok(None),
+        // A peer that closes the connection between records is done, not faulty.
+        Err(ReadExactError::ReadError(ReadError::ConnectionLost(
+            ConnectionError::ApplicationClosed(_) | ConnectionError::LocallyClosed,
+        ))) => return Ok(None),
+        Err(e) => return Err(read_error(e, RECORD_HEADER_LEN)),
+    }
+
+    let len = record_payload_len(&header, MAX_RECORD_LEN)?;
+    let mut payload = vec![0u8; len];
+    stream
+        .read_exact(&mut payload)
+        .await
+        .map_err(|e| read_error(e, len))?;
+    decode_inbound(&payload, peer.clone(), limits).map(Some)
+}
+
+fn read_error(e: ReadExactError, expected: usize) -> TransportError {
+    match e {
+        ReadExactError::FinishedEarly(got) => RecordError::Truncated { expected, got }.into(),
+        ReadExactError::ReadError(e) => e.into(),
+    }
+}
@@ -0,0 +1,219 @@
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::BytesMut;
+use futures_util::SinkExt;
+use hmf_core::envelope::Envelope;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
+use tokio_util::codec::{Decoder, FramedWrite};
+
+use crate::error::TransportError;
+use crate::limits::{Limits, PeerPermit, PeerTable};
+use crate::transport::codec::EnvelopeCodec;
+use crate::transport::{Inbound, Peer};
+
+/// Responses buffered per connection before [`Replies::send`] waits.
+pub const REPLY_QUEUE_DEPTH: usize = 16;
+
+/// First and longest pause after a failed `accept`, such as when the process
+/// is out of file descriptors.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// An envelope read by a connection task.
+#[derive(Debug)]
+pub struct Incoming {
+    pub inbound: Inbound,
+    replies: Replies,
+    // Counts against the connection's in-flight cap until dropped.
+    _in_flight: OwnedSemaphorePermit,
+}
+
+impl Incoming {
+    /// Route back to the connection this envelope arrived on.
+    pub fn replies(&self) -> &Replies {
+        &self.replies
+    }
+}
+
+/// Sends envelopes back on one connection.
+#[derive(Clone, Debug)]
+pub struct Replies {
+    tx: mpsc::Sender<Envelope>,
+}
+
+impl Replies {
+    /// Queues `env` for the connection's writer.
+    ///
+    /// # Errors
+    ///
+    /// Fails with a broken-pipe I/O error once the connection has gone away.
+    pub async fn send(&self, env: Envelope) -> Result<(), TransportError> {
+        self.tx
+            .send(env)
+            .await
+            .map_err(|_| Error::from(ErrorKind::BrokenPipe).into())
+    }
+}
+
+/// TCP listener that serves every connection on its own task.
+///
+/// Connection tasks only frame and validate; envelopes are handed to a single
+/// consumer over a channel, so the runtime's replay and config state stay
+/// single-writer while one slow peer cannot stall the others.
+///
+/// Every connection is subject to [`Limits`]; a peer over a connection cap is
+/// disconnected as soon as it is accepted.
+#[derive(Debug)]
+pub struct AsyncListener {
+    listener: TcpListener,
+    peers: PeerTable,
+}
+
+impl AsyncListener {
+    pub async fn bind(addr: SocketAddr) -> Result<Self, TransportError> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            peers: PeerTable::new(Limits::default()),
+        })
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.peers = PeerTable::new(limits);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accepts connections until `incoming` is closed.
+    ///
+    /// A failing connection ends only its own task. A failed `accept` is
+    /// logged and retried after a backoff of up to one second; a peer over a
+    /// connection cap is logged and disconnected.
+    pub async fn run(self, incoming: mpsc::Sender<Incoming>) -> Result<(), TransportError> {
+        let mut backoff = ACCEPT_BACKOFF_MIN;
+        loop {
+            let accepted = tokio::select! {
+                accepted = self.listener.accept() => accepted,
+                () = incoming.closed() => return Ok(()),
+            };
+            let (sock, peer_addr) = match accepted {
+                Ok(accepted) => {
+                    backoff = ACCEPT_BACKOFF_MIN;
+                    accepted
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, retry_in = ?backoff, "accept failed");
+                    tokio::select! {
+                        () = tokio::time::sleep(backoff) => {}
+                        () = incoming.closed() => return Ok(()),
+                    }
+                    backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                    continue;
+                }
+            };
+            let permit = match self.peers.admit(peer_addr.ip(), Instant::now()) {
+                Ok(permit) => permit,
+                Err(e) => {
+                    tracing::warn!(peer = %peer_addr, error = %e, "connection refused");
+                    continue;
+                }
+            };
+            tokio::spawn(serve_connection(
+                sock,
+                Peer::from(peer_addr),
+                permit,
+                self.peers.limits().clone(),
+                incoming.clone(),
+            ));
+        }
+    }
+}
+
+/// Serves one established stream until the peer closes it, a record fails or
+/// a limit is hit.
+///
+/// Usable directly for streams accepted elsewhere, such as TLS, once the peer
+/// has been admitted to a [`PeerTable`].
+pub async fn serve_connection<S>(
+    stream: S,
+    peer: Peer,
+    permit: PeerPermit,
+    limits: Limits,
+    incoming: mpsc::Sender<Incoming>,
+) -> Result<(), TransportError>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (mut reader, writer) = tokio::io::split(stream);
+    let (tx, mut rx) = mpsc::channel::<Envelope>(REPLY_QUEUE_DEPTH);
+    tokio::spawn(async move {
+        let mut sink = FramedWrite::new(writer, EnvelopeCodec::default());
+        while let Some(env) = rx.recv().await {
+            if sink.send(env).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    let replies = Replies { tx };
+    let in_flight = Arc::new(Semaphore::new(limits.max_in_flight()));
+    let mut codec = EnvelopeCodec::new(peer).with_limits(limits.clone());
+    let mut buf = BytesMut::new();
+    let mut idle_since = Instant::now();
+    let mut record_started: Option<Instant> = None;
+
+    loop {
+        while let Some(inbound) = codec.decode(&mut buf)? {
+            permit.take_frame(inbound.first_observed)?;
+            let in_flight = in_flight.clone().try_acquire_owned().map_err(|_| {
+                TransportError::InFlightExceeded {
+                    max: limits.max_in_flight(),
+                }
+            })?;
+            let item = Incoming {
+                inbound,
+                replies: replies.clone(),
+                _in_flight: in_flight,
+            };
+            if incoming.send(item).await.is_err() {
+                return Ok(());
+            }
+            idle_since = Instant::now();
+            // Bytes left over belong to a record that has already started.
+            record_started = (!buf.is_empty()).then_some(idle_since);
+        }
+
+        // Slow-loris defence: a started record must complete within the read
+        // timeout, however slowly its bytes trickle in.
+        let (deadline, expired) = match record_started {
+            Some(started) => (
+                started + limits.read_timeout(),
+                TransportError::ReadTimeout {
+                    after: limits.read_timeout(),
+                },
+            ),
+            None => (
+                idle_since + limits.idle_timeout(),
+                TransportError::IdleTimeout {
+                    after: limits.idle_timeout(),
+                },
+            ),
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let read = match tokio::time::timeout(remaining, reader.read_buf(&mut buf)).await {
+            Ok(read) => read?,
+            Err(_) => return Err(expired),
+        };
+        if read == 0 {
+            return codec.decode_eof(&mut buf).map(|_| ());
+        }
+        record_started.get_or_insert_with(Instant::now);
+    }
+}
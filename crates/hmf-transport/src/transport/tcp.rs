@@ -1,35 +1,54 @@
-use std::io::{ErrorKind, Write};
-use std::net::TcpStream;
+use std::net::{SocketAddr, TcpListener, TcpStream};
 
-use hmf_core::envelope::{Envelope, envelope_validate};
-use hmf_wire_proto::wire::protobuf::{envelope_decode, envelope_encode};
+use hmf_core::envelope::Envelope;
 
-use crate::error::{RecordError, TransportError};
-use crate::record::{MAX_RECORD_LEN, record_decode, record_encode};
+use crate::error::TransportError;
+use crate::limits::Limits;
+use crate::transport::{
+    Connection, Inbound, Peer, RecvWith, Transport, read_envelope, write_envelope,
+};
 
 pub fn write_record(stream: &mut TcpStream, env: &Envelope) -> Result<(), TransportError> {
-    envelope_validate(env)?;
-    let payload = envelope_encode(env)?;
-    let record = record_encode(&payload)?;
-    stream.write_all(&record)?;
-    Ok(())
+    write_envelope(stream, env)
 }
 
-pub fn read_record(stream: &mut TcpStream) -> Result<Option<Envelope>, TransportError> {
-    let record_bytes = match record_decode(stream, MAX_RECORD_LEN) {
-        Ok(b) => b,
-        Err(RecordError::Io(e)) if is_closed(e.kind()) => return Ok(None),
-        Err(e) => return Err(e.into()),
-    };
+pub fn read_record(stream: &mut TcpStream) -> Result<Option<Inbound>, TransportError> {
+    read_record_with(stream, &Limits::default())
+}
+
+/// Reads one record and decodes it under `limits`.
+pub fn read_record_with(
+    stream: &mut TcpStream,
+    limits: &Limits,
+) -> Result<Option<Inbound>, TransportError> {
+    let peer = stream.peer_addr().map(Peer::from).unwrap_or_default();
+    read_envelope(stream, peer, limits)
+}
+
+impl Connection for TcpStream {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        write_record(self, env)
+    }
 
-    let env = envelope_decode(&record_bytes)?;
-    envelope_validate(&env)?;
-    Ok(Some(env))
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        read_record(self)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        TcpStream::peer_addr(self).ok()
+    }
+}
+
+impl RecvWith for TcpStream {
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError> {
+        read_record_with(self, limits)
+    }
 }
 
-fn is_closed(kind: ErrorKind) -> bool {
-    matches!(
-        kind,
-        ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
-    )
+impl Transport for TcpListener {
+    type Conn = TcpStream;
+
+    fn accept(&mut self) -> Result<TcpStream, TransportError> {
+        Ok(TcpListener::accept(self)?.0)
+    }
 }
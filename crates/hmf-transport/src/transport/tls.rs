@@ -0,0 +1,671 @@
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hmf_core::envelope::Envelope;
+use hmf_core::ids::DeviceId;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, WebPkiSupportedAlgorithms};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ParsedCertificate, WebPkiClientVerifier};
+use rustls::{
+    CertificateError, ClientConfig, ClientConnection, ConnectionCommon, DigitallySignedStruct,
+    DistinguishedName, ProtocolVersion, RootCertStore, ServerConfig, ServerConnection, SideData,
+    SignatureScheme, StreamOwned,
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::TransportError;
+use crate::limits::Limits;
+use crate::transport::{
+    Connection, Inbound, Peer, RecvWith, Transport, read_envelope, write_envelope,
+};
+
+/// Server side of an established TLS 1.3 connection.
+///
+/// Accepted under a pinned configuration, it only yields envelopes whose
+/// `sender_id` is the identity pinned to the client key.
+#[derive(Debug)]
+pub struct TlsServerStream {
+    stream: StreamOwned<ServerConnection, TcpStream>,
+    pins: Option<SpkiPins>,
+}
+
+impl TlsServerStream {
+    pub fn connection(&self) -> &ServerConnection {
+        &self.stream.conn
+    }
+}
+
+impl Read for TlsServerStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for TlsServerStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Client side of an established TLS 1.3 connection.
+pub type TlsClientStream = StreamOwned<ClientConnection, TcpStream>;
+
+mod sealed {
+    use std::net::SocketAddr;
+
+    use rustls::pki_types::CertificateDer;
+
+    use super::SpkiPins;
+
+    pub trait Sealed {
+        fn peer_end_entity(&self) -> Option<&CertificateDer<'static>>;
+        fn peer_socket_addr(&self) -> Option<SocketAddr>;
+        /// Pins every received envelope is checked against.
+        fn sender_pins(&self) -> Option<&SpkiPins>;
+    }
+
+    impl Sealed for super::TlsServerStream {
+        fn peer_end_entity(&self) -> Option<&CertificateDer<'static>> {
+            self.stream.conn.peer_certificates()?.first()
+        }
+
+        fn peer_socket_addr(&self) -> Option<SocketAddr> {
+            self.stream.sock.peer_addr().ok()
+        }
+
+        fn sender_pins(&self) -> Option<&SpkiPins> {
+            self.pins.as_ref()
+        }
+    }
+
+    impl Sealed for super::TlsClientStream {
+        fn peer_end_entity(&self) -> Option<&CertificateDer<'static>> {
+            self.conn.peer_certificates()?.first()
+        }
+
+        fn peer_socket_addr(&self) -> Option<SocketAddr> {
+            self.sock.peer_addr().ok()
+        }
+
+        fn sender_pins(&self) -> Option<&SpkiPins> {
+            None
+        }
+    }
+}
+
+/// A stream that has completed a TLS 1.3 handshake.
+///
+/// Sealed: records can only be exchanged over streams produced by [`accept`]
+/// or [`connect`], never over a bare socket.
+pub trait TlsStream: sealed::Sealed + Read + Write {
+    /// SHA-256 of the peer's end-entity SPKI, if the peer presented a certificate.
+    fn peer_spki_sha256(&self) -> Option<SpkiHash> {
+        spki_sha256(self.peer_end_entity()?).ok()
+    }
+
+    /// Peer address and transport identity, as stamped on each [`Inbound`].
+    fn peer(&self) -> Peer {
+        Peer {
+            addr: self.peer_socket_addr(),
+            spki_sha256: self.peer_spki_sha256(),
+        }
+    }
+}
+
+impl TlsStream for TlsServerStream {}
+impl TlsStream for TlsClientStream {}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// SHA-256 digest of a DER-encoded SubjectPublicKeyInfo.
+pub type SpkiHash = [u8; 32];
+
+/// Computes the pin for `cert`: the SHA-256 of its SubjectPublicKeyInfo.
+pub fn spki_sha256(cert: &CertificateDer<'_>) -> Result<SpkiHash, TransportError> {
+    let parsed = ParsedCertificate::try_from(cert)?;
+    Ok(Sha256::digest(parsed.subject_public_key_info().as_ref()).into())
+}
+
+/// Transport keys pinned to the enrolled identity that owns them.
+///
+/// A pin binds one SPKI to one `DeviceId`. It authenticates the connection
+/// only; it never authorizes an envelope (transport.md, "Mutual
+/// authentication").
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpkiPins {
+    pins: BTreeMap<SpkiHash, DeviceId>,
+}
+
+impl SpkiPins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pins `spki_sha256` to `identity`, replacing any earlier binding of that key.
+    pub fn with_pin(mut self, identity: DeviceId, spki_sha256: SpkiHash) -> Self {
+        self.pins.insert(spki_sha256, identity);
+        self
+    }
+
+    /// Pins the key carried by `cert` to `identity`.
+    pub fn with_certificate(
+        self,
+        identity: DeviceId,
+        cert: &CertificateDer<'_>,
+    ) -> Result<Self, TransportError> {
+        Ok(self.with_pin(identity, spki_sha256(cert)?))
+    }
+
+    /// The identity a pinned key is bound to.
+    pub fn identity(&self, spki_sha256: &SpkiHash) -> Option<&DeviceId> {
+        self.pins.get(spki_sha256)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// Checks that `env` claims the identity pinned to the connection's peer key.
+    ///
+    /// Server streams accepted under a pinned [`ServerTls`] apply this to every
+    /// envelope they read. It only narrows what a pinned connection may carry:
+    /// the envelope must still pass signature verification in the runtime; a
+    /// matching pin is never a substitute for it.
+    ///
+    /// # Errors
+    ///
+    /// [`TransportError::UnpinnedPeer`] if the peer key is not pinned, or
+    /// [`TransportError::PeerMismatch`] if `env.sender_id` differs from the
+    /// pinned identity.
+    pub fn check_sender(&self, peer: &SpkiHash, env: &Envelope) -> Result<(), TransportError> {
+        let identity = self.identity(peer).ok_or(TransportError::UnpinnedPeer)?;
+        if *identity != env.sender_id {
+            return Err(TransportError::PeerMismatch {
+                pinned: identity.clone(),
+                sender_id: env.sender_id.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// How a peer's transport identity is authenticated.
+///
+/// Both modes are deployment-defined options (transport.md, "Mutual
+/// authentication"). Neither confers HMF authority: envelopes are verified
+/// against the trust registry regardless of mode.
+#[derive(Clone, Debug)]
+pub enum PeerTrust {
+    /// The peer certificate must chain to a site CA in the store.
+    SiteCa(Arc<RootCertStore>),
+    /// The peer end-entity key must be pinned. Issuers and validity periods
+    /// are not consulted.
+    ///
+    /// A client only accepts a server key pinned to the identity it connects
+    /// to: the server name passed to [`connect`] is the expected `DeviceId`.
+    /// A server accepts any pinned client key; the pinned identity then bounds
+    /// what the connection may carry (see [`SpkiPins::check_sender`]).
+    Pinned(SpkiPins),
+}
+
+/// A TLS 1.3 server configuration and, for pinned clients, the pins its
+/// verifier enforces.
+///
+/// Keeping the pins with the configuration lets every stream accepted under
+/// it refuse envelopes from identities other than the pinned one. QUIC takes
+/// only the rustls configuration, via [`ServerTls::config`].
+#[derive(Clone, Debug)]
+pub struct ServerTls {
+    config: Arc<ServerConfig>,
+    pins: Option<SpkiPins>,
+}
+
+impl ServerTls {
+    pub fn config(&self) -> &Arc<ServerConfig> {
+        &self.config
+    }
+
+    /// The pins client keys are bound to, if clients are pinned.
+    pub fn pins(&self) -> Option<&SpkiPins> {
+        self.pins.as_ref()
+    }
+}
+
+impl From<Arc<ServerConfig>> for ServerTls {
+    fn from(config: Arc<ServerConfig>) -> Self {
+        Self { config, pins: None }
+    }
+}
+
+/// Builds a server configuration that only speaks TLS 1.3.
+pub fn server_config(
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> Result<Arc<ServerConfig>, TransportError> {
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)?;
+    Ok(Arc::new(config))
+}
+
+/// Builds a client configuration that only speaks TLS 1.3 and trusts `roots`.
+pub fn client_config(roots: RootCertStore) -> Result<Arc<ClientConfig>, TransportError> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Builds a TLS 1.3 server configuration that requires client certificates
+/// authenticated according to `clients`.
+pub fn mutual_server_config(
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    clients: PeerTrust,
+) -> Result<ServerTls, TransportError> {
+    let provider = provider();
+    let (verifier, pins): (Arc<dyn ClientCertVerifier>, _) = match clients {
+        PeerTrust::SiteCa(roots) => (
+            WebPkiClientVerifier::builder_with_provider(roots, provider.clone())
+                .build()
+                .map_err(|e| TransportError::Verifier(e.to_string()))?,
+            None,
+        ),
+        PeerTrust::Pinned(pins) => (
+            Arc::new(PinnedVerifier::new(pins.clone(), &provider)?),
+            Some(pins),
+        ),
+    };
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(cert_chain, key)?;
+    Ok(ServerTls {
+        config: Arc::new(config),
+        pins,
+    })
+}
+
+/// Builds a TLS 1.3 client configuration that presents `cert_chain` and
+/// authenticates the server according to `server`.
+pub fn mutual_client_config(
+    server: PeerTrust,
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> Result<Arc<ClientConfig>, TransportError> {
+    let provider = provider();
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&[&rustls::version::TLS13])?;
+    let config = match server {
+        PeerTrust::SiteCa(roots) => builder
+            .with_root_certificates(roots)
+            .with_client_auth_cert(cert_chain, key)?,
+        PeerTrust::Pinned(pins) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedVerifier::new(pins, &provider)?))
+            .with_client_auth_cert(cert_chain, key)?,
+    };
+    Ok(Arc::new(config))
+}
+
+/// Accepts a peer whose end-entity SPKI is pinned; a server must be pinned to
+/// the identity named by the client.
+#[derive(Debug)]
+struct PinnedVerifier {
+    pins: SpkiPins,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl PinnedVerifier {
+    fn new(pins: SpkiPins, provider: &CryptoProvider) -> Result<Self, TransportError> {
+        if pins.is_empty() {
+            return Err(TransportError::Verifier("no pinned keys".to_string()));
+        }
+        Ok(Self {
+            pins,
+            algorithms: provider.signature_verification_algorithms,
+        })
+    }
+
+    /// The identity `end_entity`'s key is pinned to.
+    fn identity(&self, end_entity: &CertificateDer<'_>) -> Result<&DeviceId, rustls::Error> {
+        let pin = spki_sha256(end_entity)
+            .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        self.pins
+            .identity(&pin)
+            .ok_or(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let pinned = self.identity(end_entity)?;
+        let expected = match server_name {
+            ServerName::DnsName(name) => name.as_ref(),
+            _ => "",
+        };
+        if pinned.as_str() != expected {
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName,
+            ));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    // Unreachable with TLS 1.3-only configs; kept correct rather than permissive.
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+impl ClientCertVerifier for PinnedVerifier {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.identity(end_entity)?;
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        ServerCertVerifier::verify_tls12_signature(self, message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        ServerCertVerifier::verify_tls13_signature(self, message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// Loads every certificate from a PEM file.
+pub fn load_certs<P: AsRef<Path>>(path: P) -> Result<Vec<CertificateDer<'static>>, TransportError> {
+    CertificateDer::pem_file_iter(path)
+        .map_err(|e| TransportError::Pem(e.to_string()))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| TransportError::Pem(e.to_string()))
+}
+
+/// Loads the first private key from a PEM file.
+pub fn load_private_key<P: AsRef<Path>>(path: P) -> Result<PrivateKeyDer<'static>, TransportError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|e| TransportError::Pem(e.to_string()))
+}
+
+/// Completes the server handshake on an accepted socket.
+///
+/// # Errors
+///
+/// Fails if the handshake fails or did not negotiate TLS 1.3. There is no
+/// plaintext fallback: on error the socket is dropped.
+pub fn accept(
+    sock: TcpStream,
+    config: impl Into<ServerTls>,
+) -> Result<TlsServerStream, TransportError> {
+    accept_within(sock, &config.into(), None)
+}
+
+fn accept_within(
+    sock: TcpStream,
+    config: &ServerTls,
+    timeout: Option<Duration>,
+) -> Result<TlsServerStream, TransportError> {
+    let conn = ServerConnection::new(config.config.clone())?;
+    let mut stream = StreamOwned::new(conn, sock);
+    finish_handshake(&mut stream.conn, &mut stream.sock, timeout)?;
+    Ok(TlsServerStream {
+        stream,
+        pins: config.pins.clone(),
+    })
+}
+
+/// Completes the client handshake with `server_name` on a connected socket.
+///
+/// With [`PeerTrust::Pinned`], `server_name` is the `DeviceId` the server key
+/// must be pinned to.
+///
+/// # Errors
+///
+/// Fails if `server_name` is invalid, the handshake fails or the peer did
+/// not negotiate TLS 1.3.
+pub fn connect(
+    sock: TcpStream,
+    server_name: &str,
+    config: Arc<ClientConfig>,
+) -> Result<TlsClientStream, TransportError> {
+    let name = ServerName::try_from(server_name.to_string())
+        .map_err(|_| TransportError::InvalidServerName(server_name.to_string()))?;
+    let conn = ClientConnection::new(config, name)?;
+    let mut stream = StreamOwned::new(conn, sock);
+    finish_handshake(&mut stream.conn, &mut stream.sock, None)?;
+    Ok(stream)
+}
+
+/// Drives the handshake to completion, within `timeout` overall if given.
+fn finish_handshake<S: SideData>(
+    conn: &mut ConnectionCommon<S>,
+    sock: &mut TcpStream,
+    timeout: Option<Duration>,
+) -> Result<(), TransportError> {
+    let deadline = timeout.map(|after| (Instant::now() + after, after));
+    while conn.is_handshaking() {
+        if let Some((deadline, after)) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(TransportError::HandshakeTimeout { after });
+            }
+            sock.set_read_timeout(Some(remaining))?;
+            sock.set_write_timeout(Some(remaining))?;
+        }
+        match conn.complete_io(sock) {
+            Ok(_) => {}
+            // The socket timed out; the deadline check above reports it.
+            Err(e)
+                if deadline.is_some()
+                    && matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(tls_io_error(e)),
+        }
+    }
+    if deadline.is_some() {
+        sock.set_read_timeout(None)?;
+        sock.set_write_timeout(None)?;
+    }
+    // The configs only enable TLS 1.3; this re-checks the outcome so that a
+    // misconfigured caller can never end up on an older version.
+    match conn.protocol_version() {
+        Some(ProtocolVersion::TLSv1_3) => Ok(()),
+        other => Err(TransportError::TlsVersion {
+            negotiated: format!("{other:?}"),
+        }),
+    }
+}
+
+/// Surfaces TLS failures reported through `std::io::Error` as [`TransportError::Tls`].
+fn tls_io_error(e: std::io::Error) -> TransportError {
+    match e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+    {
+        Some(tls) => TransportError::Tls(tls.clone()),
+        None => TransportError::Io(e),
+    }
+}
+
+pub fn write_record<S: TlsStream>(stream: &mut S, env: &Envelope) -> Result<(), TransportError> {
+    write_envelope(stream, env)
+}
+
+pub fn read_record<S: TlsStream>(stream: &mut S) -> Result<Option<Inbound>, TransportError> {
+    read_record_with(stream, &Limits::default())
+}
+
+/// Reads one record and decodes it under `limits`.
+///
+/// # Errors
+///
+/// On a stream accepted under pinned clients, also
+/// [`TransportError::UnpinnedPeer`] or [`TransportError::PeerMismatch`] if
+/// the envelope does not come from the identity pinned to the peer key.
+pub fn read_record_with<S: TlsStream>(
+    stream: &mut S,
+    limits: &Limits,
+) -> Result<Option<Inbound>, TransportError> {
+    let peer = stream.peer();
+    let inbound = read_envelope(stream, peer, limits)?;
+    if let (Some(inbound), Some(pins)) = (&inbound, stream.sender_pins()) {
+        let spki = inbound
+            .peer
+            .spki_sha256
+            .as_ref()
+            .ok_or(TransportError::UnpinnedPeer)?;
+        pins.check_sender(spki, &inbound.envelope)?;
+    }
+    Ok(inbound)
+}
+
+impl RecvWith for TlsServerStream {
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError> {
+        read_record_with(self, limits)
+    }
+}
+
+impl Connection for TlsServerStream {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        write_record(self, env)
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        read_record(self)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.stream.sock.peer_addr().ok()
+    }
+}
+
+impl RecvWith for TlsClientStream {
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError> {
+        read_record_with(self, limits)
+    }
+}
+
+impl Connection for TlsClientStream {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        write_record(self, env)
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        read_record(self)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.sock.peer_addr().ok()
+    }
+}
+
+/// Default bound on a [`TlsListener`] handshake.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A TCP listener that completes a TLS 1.3 handshake on every accepted socket.
+///
+/// A peer whose handshake fails or does not complete within the handshake
+/// timeout is logged and dropped; [`Transport::accept`] then waits for the
+/// next one instead of returning an error.
+#[derive(Debug)]
+pub struct TlsListener {
+    listener: TcpListener,
+    config: ServerTls,
+    handshake_timeout: Duration,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, config: impl Into<ServerTls>) -> Self {
+        Self {
+            listener,
+            config: config.into(),
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+        }
+    }
+
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        Ok(self.listener.local_addr()?)
+    }
+}
+
+impl Transport for TlsListener {
+    type Conn = TlsServerStream;
+
+    fn accept(&mut self) -> Result<TlsServerStream, TransportError> {
+        loop {
+            let (sock, addr) = self.listener.accept()?;
+            match accept_within(sock, &self.config, Some(self.handshake_timeout)) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => tracing::warn!(peer = %addr, error = %e, "TLS handshake failed"),
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use hmf_core::envelope::Envelope;
+
+use crate::error::TransportError;
+use crate::limits::Limits;
+use crate::transport::{
+    Connection, Inbound, Peer, RecvWith, Transport, read_envelope, write_envelope,
+};
+
+pub fn write_record(stream: &mut UnixStream, env: &Envelope) -> Result<(), TransportError> {
+    write_envelope(stream, env)
+}
+
+pub fn read_record(stream: &mut UnixStream) -> Result<Option<Inbound>, TransportError> {
+    read_record_with(stream, &Limits::default())
+}
+
+/// Reads one record and decodes it under `limits`.
+pub fn read_record_with(
+    stream: &mut UnixStream,
+    limits: &Limits,
+) -> Result<Option<Inbound>, TransportError> {
+    read_envelope(stream, Peer::default(), limits)
+}
+
+impl Connection for UnixStream {
+    fn send(&mut self, env: &Envelope) -> Result<(), TransportError> {
+        write_record(self, env)
+    }
+
+    fn recv(&mut self) -> Result<Option<Inbound>, TransportError> {
+        read_record(self)
+    }
+}
+
+impl RecvWith for UnixStream {
+    fn recv_with(&mut self, limits: &Limits) -> Result<Option<Inbound>, TransportError> {
+        read_record_with(self, limits)
+    }
+}
+
+impl Transport for UnixListener {
+    type Conn = UnixStream;
+
+    fn accept(&mut self) -> Result<UnixStream, TransportError> {
+        Ok(UnixListener::accept(self)?.0)
+    }
+}
@@ -0,0 +1,241 @@
+//! The same exchange, written once against `Connection`, over every adapter.
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Instant;
+
+use ed25519_dalek::SigningKey;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    Envelope, Health, LifecycleHeartbeat, Payload, Telemetry, TelemetryPayload,
+};
+use hmf_core::ids::DeviceId;
+use hmf_transport::error::TransportError;
+use hmf_transport::limits::Limits;
+use hmf_transport::transport::memory::{self, MemoryListener};
+use hmf_transport::transport::tls::{self, TlsListener};
+use hmf_transport::transport::{Connection, Limited, Transport};
+use hmf_wire_proto::wire::protobuf::envelope_encode;
+use rcgen::{CertificateParams, KeyPair};
+use rustls::RootCertStore;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+fn heartbeat() -> Envelope {
+    let mut endpoint = EndpointContext::new(
+        DeviceId::new("device-1"),
+        "device-1:ed25519:v1",
+        SigningKey::from_bytes(&[7u8; 32]),
+    );
+    let telemetry = Telemetry {
+        payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 42,
+            health: Health::Ok,
+        })),
+    };
+    endpoint
+        .send(
+            Payload::Telemetry(telemetry),
+            Routing::new(
+                "zone:demo",
+                "site-warden",
+                "hmf/telemetry/lifecycle_heartbeat",
+            ),
+        )
+        .unwrap()
+}
+
+fn server_identity() -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .self_signed(&key)
+        .unwrap();
+    (
+        cert.der().clone(),
+        PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+    )
+}
+
+fn roots(cert: &CertificateDer<'static>) -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.clone()).unwrap();
+    roots
+}
+
+fn accept_in_background<T>(mut transport: T) -> thread::JoinHandle<T::Conn>
+where
+    T: Transport + Send + 'static,
+    T::Conn: Send + 'static,
+{
+    thread::spawn(move || transport.accept().unwrap())
+}
+
+/// Round-trips an envelope, then checks that closing the client ends the server.
+fn exchange<C: Connection, S: Connection>(mut client: C, mut server: S) {
+    let sent = heartbeat();
+    let sent_at = Instant::now();
+    client.send(&sent).unwrap();
+    let inbound = server.recv().unwrap().unwrap();
+    assert_eq!(inbound.envelope, sent);
+    assert_eq!(inbound.peer.addr, server.peer_addr());
+    assert_eq!(inbound.encoded_len, envelope_encode(&sent).unwrap().len());
+    assert!(inbound.first_observed >= sent_at);
+
+    server.send(&sent).unwrap();
+    assert_eq!(client.recv().unwrap().unwrap().envelope, sent);
+
+    drop(client);
+    assert_eq!(server.recv().unwrap(), None);
+}
+
+/// Decode limits the heartbeat's topic and scope are too long for.
+fn tight() -> Limits {
+    Limits::default().with_decode_limits(DecodeLimits::default().with_max_string_len(16))
+}
+
+/// Checks that the server refuses an envelope its decode limits do not allow.
+fn refused<C: Connection, S: Connection>(mut client: C, mut server: S) {
+    client.send(&heartbeat()).unwrap();
+    assert!(matches!(server.recv(), Err(TransportError::Wire(_))));
+}
+
+#[test]
+fn memory_pair() {
+    let (client, server) = memory::pair();
+    exchange(client, server);
+}
+
+#[test]
+fn memory_listener() {
+    let mut listener = MemoryListener::new();
+    let client = listener.connector().connect().unwrap();
+    let server = listener.accept().unwrap();
+    exchange(client, server);
+}
+
+#[test]
+fn memory_listener_applies_its_limits() {
+    let mut listener = Limited::new(MemoryListener::new(), tight());
+    let client = listener.get_ref().connector().connect().unwrap();
+    let server = listener.accept().unwrap();
+    refused(client, server);
+}
+
+#[test]
+fn tcp() {
+    let mut listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let server = Transport::accept(&mut listener).unwrap();
+    assert_eq!(
+        Connection::peer_addr(&server),
+        Some(client.local_addr().unwrap())
+    );
+    exchange(client, server);
+}
+
+#[test]
+fn tcp_listener_applies_its_limits() {
+    let mut listener = Limited::new(TcpListener::bind("127.0.0.1:0").unwrap(), tight());
+    let client = TcpStream::connect(listener.get_ref().local_addr().unwrap()).unwrap();
+    let server = listener.accept().unwrap();
+    refused(client, server);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket() {
+    use std::os::unix::net::UnixStream;
+
+    let (client, server) = UnixStream::pair().unwrap();
+    exchange(client, server);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket_applies_its_limits() {
+    use std::os::unix::net::UnixStream;
+
+    let (client, server) = UnixStream::pair().unwrap();
+    refused(client, Limited::new(server, tight()));
+}
+
+fn tls_listener() -> (TlsListener, CertificateDer<'static>) {
+    let (cert, key) = server_identity();
+    let listener = TlsListener::new(
+        TcpListener::bind("127.0.0.1:0").unwrap(),
+        tls::server_config(vec![cert.clone()], key).unwrap(),
+    );
+    (listener, cert)
+}
+
+fn tls_client(addr: SocketAddr, cert: &CertificateDer<'static>) -> tls::TlsClientStream {
+    tls::connect(
+        TcpStream::connect(addr).unwrap(),
+        "localhost",
+        tls::client_config(roots(cert)).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn tls() {
+    let (listener, cert) = tls_listener();
+    let addr = listener.local_addr().unwrap();
+    let server = accept_in_background(listener);
+    exchange(tls_client(addr, &cert), server.join().unwrap());
+}
+
+#[test]
+fn tls_listener_applies_its_limits() {
+    let (listener, cert) = tls_listener();
+    let addr = listener.local_addr().unwrap();
+    let server = accept_in_background(Limited::new(listener, tight()));
+    refused(tls_client(addr, &cert), server.join().unwrap());
+}
+
+/// Connects a blocking QUIC client to a listener built with `limits`.
+#[cfg(feature = "quic")]
+fn quic_pair(
+    rt: &tokio::runtime::Runtime,
+    limits: Limits,
+) -> (
+    hmf_transport::transport::quic::QuicConnection,
+    hmf_transport::transport::quic::QuicConnection,
+) {
+    use hmf_transport::transport::quic::{self, QuicConnection, QuicListener};
+
+    let (cert, key) = server_identity();
+    let server_config =
+        quic::server_config(tls::server_config(vec![cert.clone()], key).unwrap()).unwrap();
+    let endpoint = {
+        let _guard = rt.enter();
+        quic::listen("127.0.0.1:0".parse().unwrap(), server_config).unwrap()
+    };
+    let listener = QuicListener::new(rt.handle().clone(), endpoint).with_limits(limits);
+    let addr = listener.local_addr().unwrap();
+    let server = accept_in_background(listener);
+
+    let client_config = quic::client_config(tls::client_config(roots(&cert)).unwrap()).unwrap();
+    let conn = rt
+        .block_on(quic::connect(addr, "localhost", client_config))
+        .unwrap();
+    let client = QuicConnection::new(rt.handle().clone(), conn);
+    (client, server.join().unwrap())
+}
+
+#[cfg(feature = "quic")]
+#[test]
+fn quic() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let (client, server) = quic_pair(&rt, Limits::default());
+    exchange(client, server);
+}
+
+#[cfg(feature = "quic")]
+#[test]
+fn quic_listener_applies_its_limits() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let (client, server) = quic_pair(&rt, tight());
+    refused(client, server);
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "async")]
+
+use std::time::{Duration, Instant};
+
+use bytes::BytesMut;
+use ed25519_dalek::SigningKey;
+use futures_util::{SinkExt, StreamExt};
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    Envelope, Health, LifecycleHeartbeat, Payload, Telemetry, TelemetryPayload,
+};
+use hmf_core::ids::DeviceId;
+use hmf_transport::error::{RecordError, TransportError};
+use hmf_transport::limits::Limits;
+use hmf_transport::record::{RECORD_HEADER_LEN, record_encode};
+use hmf_transport::transport::codec::EnvelopeCodec;
+use hmf_transport::transport::server::{AsyncListener, Incoming};
+use hmf_wire_proto::wire::protobuf::envelope_encode;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tokio_util::codec::{Decoder, Framed};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn heartbeat(sender_id: &str) -> Envelope {
+    let mut endpoint = EndpointContext::new(
+        DeviceId::new(sender_id),
+        &format!("{sender_id}:ed25519:v1"),
+        SigningKey::from_bytes(&[7u8; 32]),
+    );
+    let telemetry = Telemetry {
+        payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 42,
+            health: Health::Ok,
+        })),
+    };
+    endpoint
+        .send(
+            Payload::Telemetry(telemetry),
+            Routing::new(
+                "zone:demo",
+                "site-warden",
+                "hmf/telemetry/lifecycle_heartbeat",
+            ),
+        )
+        .unwrap()
+}
+
+fn record(env: &Envelope) -> Vec<u8> {
+    record_encode(&envelope_encode(env).unwrap()).unwrap()
+}
+
+async fn start() -> (std::net::SocketAddr, mpsc::Receiver<Incoming>) {
+    // Every test client shares the loopback address.
+    let listener = AsyncListener::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap()
+        .with_limits(Limits::default().with_max_connections_per_peer(64));
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(listener.run(tx));
+    (addr, rx)
+}
+
+#[tokio::test]
+async fn stalled_peer_does_not_block_other_connections() {
+    let (addr, mut rx) = start().await;
+
+    // A slow device that sends part of a record and then stalls.
+    let mut stalled = TcpStream::connect(addr).await.unwrap();
+    let partial = record(&heartbeat("device-9"));
+    stalled.write_all(&partial[..6]).await.unwrap();
+
+    let mut client = Framed::new(
+        TcpStream::connect(addr).await.unwrap(),
+        EnvelopeCodec::default(),
+    );
+    let sent = heartbeat("device-1");
+    let sent_at = Instant::now();
+    client.send(sent.clone()).await.unwrap();
+
+    let incoming = timeout(WAIT, rx.recv()).await.unwrap().unwrap();
+    let inbound = &incoming.inbound;
+    assert_eq!(inbound.envelope, sent);
+    assert_eq!(
+        inbound.peer.addr,
+        Some(client.get_ref().local_addr().unwrap())
+    );
+    assert_eq!(inbound.encoded_len, record(&sent).len() - RECORD_HEADER_LEN);
+    assert!(inbound.first_observed >= sent_at);
+    incoming.replies().send(sent.clone()).await.unwrap();
+
+    let echoed = timeout(WAIT, client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(echoed.envelope, sent);
+}
+
+#[tokio::test]
+async fn serves_many_connections_concurrently() {
+    let (addr, mut rx) = start().await;
+
+    let mut clients = Vec::new();
+    for i in 0..32 {
+        let mut client = Framed::new(
+            TcpStream::connect(addr).await.unwrap(),
+            EnvelopeCodec::default(),
+        );
+        client
+            .send(heartbeat(&format!("device-{i}")))
+            .await
+            .unwrap();
+        clients.push(client);
+    }
+
+    let mut senders = Vec::new();
+    for _ in 0..32 {
+        let incoming = timeout(WAIT, rx.recv()).await.unwrap().unwrap();
+        incoming
+            .replies()
+            .send(incoming.inbound.envelope.clone())
+            .await
+            .unwrap();
+        senders.push(incoming.inbound.envelope.sender_id.to_string());
+    }
+    senders.sort();
+    senders.dedup();
+    assert_eq!(senders.len(), 32);
+
+    for (i, client) in clients.iter_mut().enumerate() {
+        let echoed = timeout(WAIT, client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(echoed.envelope.sender_id.as_str(), format!("device-{i}"));
+    }
+}
+
+#[tokio::test]
+async fn listener_applies_its_decode_limits() {
+    let listener = AsyncListener::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap()
+        .with_limits(
+            Limits::default().with_decode_limits(DecodeLimits::default().with_max_string_len(16)),
+        );
+    let addr = listener.local_addr().unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(listener.run(tx));
+
+    // The heartbeat's topic is longer than the listener allows.
+    let mut client = Framed::new(
+        TcpStream::connect(addr).await.unwrap(),
+        EnvelopeCodec::default(),
+    );
+    client.send(heartbeat("device-1")).await.unwrap();
+    assert!(timeout(WAIT, client.next()).await.unwrap().is_none());
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn strict_codec_refuses_unknown_fields() {
+    let sent = heartbeat("device-1");
+    let mut payload = envelope_encode(&sent).unwrap();
+    // Field 99, varint 1: unknown to the v1 schema.
+    payload.extend_from_slice(&[0x98, 0x06, 0x01]);
+    let bytes = record_encode(&payload).unwrap();
+
+    let mut lenient = EnvelopeCodec::default();
+    let decoded = lenient.decode(&mut BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(decoded.map(|inbound| inbound.envelope), Some(sent));
+
+    let mut strict =
+        EnvelopeCodec::default().with_limits(Limits::default().with_strict_decode(true));
+    assert!(matches!(
+        strict.decode(&mut BytesMut::from(&bytes[..])),
+        Err(TransportError::Wire(_))
+    ));
+}
+
+#[test]
+fn codec_waits_for_complete_record_and_reports_truncation() {
+    let sent = heartbeat("device-1");
+    let bytes = record(&sent);
+    let mut codec = EnvelopeCodec::default();
+    let mut buf = BytesMut::new();
+
+    for (i, b) in bytes.iter().enumerate() {
+        buf.extend_from_slice(&[*b]);
+        let decoded = codec.decode(&mut buf).unwrap();
+        if i + 1 < bytes.len() {
+            assert!(decoded.is_none());
+        } else {
+            assert_eq!(decoded.map(|inbound| inbound.envelope), Some(sent.clone()));
+        }
+    }
+    assert!(buf.is_empty());
+
+    buf.extend_from_slice(&bytes[..RECORD_HEADER_LEN + 6]);
+    match codec.decode_eof(&mut buf) {
+        Err(TransportError::Record(RecordError::Truncated { expected, got })) => {
+            assert_eq!(expected, bytes.len() - RECORD_HEADER_LEN);
+            assert_eq!(got, 6);
+        }
+        other => panic!("expected truncation, got {other:?}"),
+    }
+}
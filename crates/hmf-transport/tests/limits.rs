@@ -0,0 +1,266 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+use hmf_transport::error::TransportError;
+use hmf_transport::limits::{Limits, PeerTable, TokenBucket};
+
+const PEER: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+const OTHER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+#[test]
+fn token_bucket_allows_burst_then_refills() {
+    let t0 = Instant::now();
+    let mut bucket = TokenBucket::new(2, 3, t0);
+
+    assert!(bucket.try_take(t0));
+    assert!(bucket.try_take(t0));
+    assert!(bucket.try_take(t0));
+    assert!(!bucket.try_take(t0));
+
+    assert!(bucket.try_take(t0 + Duration::from_millis(500)));
+    assert!(!bucket.try_take(t0 + Duration::from_millis(500)));
+    // Refill never exceeds the burst.
+    let later = t0 + Duration::from_secs(60);
+    for _ in 0..3 {
+        assert!(bucket.try_take(later));
+    }
+    assert!(!bucket.try_take(later));
+}
+
+#[test]
+fn peer_and_global_connection_caps() {
+    let now = Instant::now();
+    let peers = PeerTable::new(
+        Limits::default()
+            .with_max_connections(3)
+            .with_max_connections_per_peer(2),
+    );
+
+    let a = peers.admit(PEER, now).unwrap();
+    let _b = peers.admit(PEER, now).unwrap();
+    assert!(matches!(
+        peers.admit(PEER, now),
+        Err(TransportError::PeerConnectionLimit { peer: PEER, max: 2 })
+    ));
+
+    let _c = peers.admit(OTHER, now).unwrap();
+    assert!(matches!(
+        peers.admit(OTHER, now),
+        Err(TransportError::ConnectionLimit { max: 3 })
+    ));
+
+    drop(a);
+    assert_eq!(peers.connections(), 2);
+    peers.admit(PEER, now).unwrap();
+}
+
+#[test]
+fn frame_budget_is_shared_by_a_peers_connections() {
+    let now = Instant::now();
+    let peers = PeerTable::new(Limits::default().with_frame_rate(1, 2));
+
+    let a = peers.admit(PEER, now).unwrap();
+    let b = peers.admit(PEER, now).unwrap();
+    let other = peers.admit(OTHER, now).unwrap();
+
+    a.take_frame(now).unwrap();
+    b.take_frame(now).unwrap();
+    assert!(matches!(
+        a.take_frame(now),
+        Err(TransportError::RateLimited { peer: PEER })
+    ));
+    other.take_frame(now).unwrap();
+}
+
+#[test]
+fn reconnecting_does_not_restore_the_frame_budget() {
+    let now = Instant::now();
+    let peers = PeerTable::new(Limits::default().with_frame_rate(1, 2));
+
+    let first = peers.admit(PEER, now).unwrap();
+    first.take_frame(now).unwrap();
+    first.take_frame(now).unwrap();
+    drop(first);
+
+    let second = peers.admit(PEER, now).unwrap();
+    assert!(matches!(
+        second.take_frame(now),
+        Err(TransportError::RateLimited { peer: PEER })
+    ));
+    drop(second);
+    assert_eq!(peers.tracked_peers(), 1);
+
+    // Once the budget has refilled the idle entry is dropped.
+    let later = now + Duration::from_secs(2);
+    let _other = peers.admit(OTHER, later).unwrap();
+    assert_eq!(peers.tracked_peers(), 1);
+    let third = peers.admit(PEER, later).unwrap();
+    third.take_frame(later).unwrap();
+    third.take_frame(later).unwrap();
+    assert!(third.take_frame(later).is_err());
+}
+
+#[cfg(feature = "async")]
+mod listener {
+    use std::net::SocketAddr;
+
+    use ed25519_dalek::SigningKey;
+    use hmf_core::endpoint::{EndpointContext, Routing};
+    use hmf_core::envelope::{
+        Envelope, Health, LifecycleHeartbeat, Payload, Telemetry, TelemetryPayload,
+    };
+    use hmf_core::ids::DeviceId;
+    use hmf_transport::record::record_encode;
+    use hmf_transport::transport::server::{AsyncListener, Incoming, serve_connection};
+    use hmf_wire_proto::wire::protobuf::envelope_encode;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+    use tokio::time::{sleep, timeout};
+
+    use super::*;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn heartbeats(n: usize) -> Vec<Envelope> {
+        let mut endpoint = EndpointContext::new(
+            DeviceId::new("device-1"),
+            "device-1:ed25519:v1",
+            SigningKey::from_bytes(&[7u8; 32]),
+        );
+        (0..n)
+            .map(|_| {
+                let telemetry = Telemetry {
+                    payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+                        uptime_ms: 42,
+                        health: Health::Ok,
+                    })),
+                };
+                endpoint
+                    .send(
+                        Payload::Telemetry(telemetry),
+                        Routing::new(
+                            "zone:demo",
+                            "site-warden",
+                            "hmf/telemetry/lifecycle_heartbeat",
+                        ),
+                    )
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn record(env: &Envelope) -> Vec<u8> {
+        record_encode(&envelope_encode(env).unwrap()).unwrap()
+    }
+
+    /// Serves the far end of an in-memory stream under `limits`.
+    fn serve(
+        limits: Limits,
+    ) -> (
+        DuplexStream,
+        mpsc::Receiver<Incoming>,
+        JoinHandle<Result<(), TransportError>>,
+    ) {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let (tx, rx) = mpsc::channel(64);
+        let peers = PeerTable::new(limits.clone());
+        let permit = peers.admit(PEER, Instant::now()).unwrap();
+        let addr = SocketAddr::new(PEER, 4000);
+        let task = tokio::spawn(serve_connection(server, addr.into(), permit, limits, tx));
+        (client, rx, task)
+    }
+
+    #[tokio::test]
+    async fn silent_connection_hits_idle_timeout() {
+        let limits = Limits::default().with_idle_timeout(Duration::from_millis(50));
+        let (_client, _rx, task) = serve(limits);
+
+        let result = timeout(WAIT, task).await.unwrap().unwrap();
+        assert!(matches!(result, Err(TransportError::IdleTimeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn trickled_record_hits_read_timeout() {
+        let limits = Limits::default()
+            .with_idle_timeout(Duration::from_secs(30))
+            .with_read_timeout(Duration::from_millis(150));
+        let (mut client, _rx, task) = serve(limits);
+
+        // One byte every 40 ms keeps the socket busy but never completes a record.
+        let bytes = record(&heartbeats(1)[0]);
+        let writer = tokio::spawn(async move {
+            for b in bytes {
+                if client.write_all(&[b]).await.is_err() {
+                    return;
+                }
+                sleep(Duration::from_millis(40)).await;
+            }
+        });
+
+        let result = timeout(WAIT, task).await.unwrap().unwrap();
+        assert!(matches!(result, Err(TransportError::ReadTimeout { .. })));
+        writer.abort();
+    }
+
+    #[tokio::test]
+    async fn frames_over_rate_are_refused() {
+        let (mut client, mut rx, task) = serve(Limits::default().with_frame_rate(1, 2));
+        for env in heartbeats(3) {
+            client.write_all(&record(&env)).await.unwrap();
+        }
+
+        let result = timeout(WAIT, task).await.unwrap().unwrap();
+        assert!(matches!(result, Err(TransportError::RateLimited { .. })));
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn unreleased_frames_cap_in_flight() {
+        let (mut client, mut rx, task) = serve(Limits::default().with_max_in_flight(2));
+        for env in heartbeats(2) {
+            client.write_all(&record(&env)).await.unwrap();
+        }
+        // Held by the consumer, so both stay in flight.
+        let held = [rx.recv().await.unwrap(), rx.recv().await.unwrap()];
+
+        let more = heartbeats(3);
+        client.write_all(&record(&more[2])).await.unwrap();
+        let result = timeout(WAIT, task).await.unwrap().unwrap();
+        assert!(matches!(
+            result,
+            Err(TransportError::InFlightExceeded { max: 2 })
+        ));
+        drop(held);
+    }
+
+    #[tokio::test]
+    async fn released_frames_free_in_flight_slots() {
+        let (mut client, mut rx, task) = serve(Limits::default().with_max_in_flight(1));
+        for env in heartbeats(3) {
+            client.write_all(&record(&env)).await.unwrap();
+            drop(timeout(WAIT, rx.recv()).await.unwrap().unwrap());
+        }
+        drop(client);
+        assert!(timeout(WAIT, task).await.unwrap().unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn listener_disconnects_peer_over_connection_cap() {
+        let listener = AsyncListener::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap()
+            .with_limits(Limits::default().with_max_connections_per_peer(1));
+        let addr = listener.local_addr().unwrap();
+        let (tx, _rx) = mpsc::channel(8);
+        tokio::spawn(listener.run(tx));
+
+        let _first = TcpStream::connect(addr).await.unwrap();
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let mut byte = [0u8; 1];
+        let read = timeout(WAIT, second.read(&mut byte)).await.unwrap();
+        assert!(matches!(read, Ok(0) | Err(_)));
+    }
+}
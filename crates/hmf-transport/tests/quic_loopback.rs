@@ -0,0 +1,228 @@
+#![cfg(feature = "quic")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use ed25519_dalek::SigningKey;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::{
+    Engineering, EngineeringPayload, EngineeringRequest, Envelope, Health, LifecycleHeartbeat,
+    Payload, Telemetry, TelemetryPayload,
+};
+use hmf_core::ids::DeviceId;
+use hmf_transport::error::TransportError;
+use hmf_transport::limits::Limits;
+use hmf_transport::record::record_encode;
+use hmf_transport::transport::{quic, tls};
+use hmf_wire_proto::wire::protobuf::envelope_encode;
+use quinn::{Connection, Endpoint};
+use rcgen::{CertificateParams, KeyPair};
+use rustls::RootCertStore;
+use rustls::pki_types::PrivatePkcs8KeyDer;
+use tokio::time::timeout;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+struct Link {
+    _server: Endpoint,
+    client: Connection,
+    server: Connection,
+}
+
+async fn link() -> Link {
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .self_signed(&key)
+        .unwrap();
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.der().clone()).unwrap();
+
+    let server_tls = tls::server_config(
+        vec![cert.der().clone()],
+        PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+    )
+    .unwrap();
+    let endpoint = quic::listen(
+        "127.0.0.1:0".parse().unwrap(),
+        quic::server_config(server_tls).unwrap(),
+    )
+    .unwrap();
+    let addr: SocketAddr = endpoint.local_addr().unwrap();
+
+    let client_config = quic::client_config(tls::client_config(roots).unwrap()).unwrap();
+    let (client, server) = tokio::join!(
+        quic::connect(addr, "localhost", client_config),
+        quic::accept(&endpoint)
+    );
+    Link {
+        _server: endpoint,
+        client: client.unwrap(),
+        server: server.unwrap().unwrap(),
+    }
+}
+
+fn device() -> EndpointContext {
+    EndpointContext::new(
+        DeviceId::new("device-1"),
+        "device-1:ed25519:v1",
+        SigningKey::from_bytes(&[7u8; 32]),
+    )
+}
+
+fn heartbeat(endpoint: &mut EndpointContext) -> Envelope {
+    let telemetry = Telemetry {
+        payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 42,
+            health: Health::Ok,
+        })),
+    };
+    endpoint
+        .send(
+            Payload::Telemetry(telemetry),
+            Routing::new(
+                "zone:demo",
+                "site-warden",
+                "hmf/telemetry/lifecycle_heartbeat",
+            ),
+        )
+        .unwrap()
+}
+
+fn engineering(endpoint: &mut EndpointContext) -> Envelope {
+    let request = EngineeringRequest {
+        request_id: "eng-1".to_string(),
+        action: "transfer.status".to_string(),
+        target: "firmware".to_string(),
+        params: [("transfer_id".to_string(), "t-1".to_string())].into(),
+        blob: Vec::new(),
+        requires_confirmation: false,
+    };
+    endpoint
+        .send(
+            Payload::Engineering(Engineering {
+                payload: Some(EngineeringPayload::Request(request)),
+            }),
+            Routing::new("zone:demo", "site-warden", "hmf/engineering/request"),
+        )
+        .unwrap()
+}
+
+fn raw_record(env: &Envelope) -> Vec<u8> {
+    record_encode(&envelope_encode(env).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn message_classes_use_separate_streams() {
+    let link = link().await;
+    let mut device = device();
+    let sender = quic::QuicSender::new(link.client.clone());
+    let mut receiver = quic::QuicReceiver::new(link.server.clone());
+
+    let hb1 = heartbeat(&mut device);
+    let eng = engineering(&mut device);
+    let hb2 = heartbeat(&mut device);
+    for env in [&hb1, &eng, &hb2] {
+        sender.send(env).await.unwrap();
+    }
+    assert_eq!(sender.stream_count(), 2);
+
+    let mut got = Vec::new();
+    for _ in 0..3 {
+        got.push(
+            timeout(WAIT, receiver.recv())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap()
+                .envelope,
+        );
+    }
+    let heartbeats: Vec<_> = got.iter().filter(|e| e.payload == hb1.payload).collect();
+    assert_eq!(heartbeats, vec![&hb1, &hb2]);
+    assert!(got.contains(&eng));
+}
+
+#[tokio::test]
+async fn stalled_engineering_stream_does_not_block_heartbeats() {
+    let link = link().await;
+    let mut device = device();
+    let mut receiver = quic::QuicReceiver::new(link.server.clone());
+
+    // Half of an engineering record, never completed.
+    let record = raw_record(&engineering(&mut device));
+    let mut stalled = link.client.open_uni().await.unwrap();
+    stalled
+        .write_all(&record[..record.len() / 2])
+        .await
+        .unwrap();
+
+    let sender = quic::QuicSender::new(link.client.clone());
+    let hb = heartbeat(&mut device);
+    sender.send(&hb).await.unwrap();
+
+    let got = timeout(WAIT, receiver.recv())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(got.envelope, hb);
+    assert_eq!(got.peer.addr, Some(link.server.remote_address()));
+}
+
+#[tokio::test]
+async fn stream_is_bound_to_its_first_key() {
+    let link = link().await;
+    let mut device = device();
+    let mut receiver = quic::QuicReceiver::new(link.server.clone());
+
+    let hb = heartbeat(&mut device);
+    let eng = engineering(&mut device);
+    let mut stream = link.client.open_uni().await.unwrap();
+    stream.write_all(&raw_record(&hb)).await.unwrap();
+    stream.write_all(&raw_record(&eng)).await.unwrap();
+    stream.finish().unwrap();
+
+    let first = timeout(WAIT, receiver.recv()).await.unwrap().unwrap();
+    assert_eq!(first.unwrap().envelope, hb);
+    let second = timeout(WAIT, receiver.recv()).await.unwrap().unwrap();
+    assert!(matches!(second, Err(TransportError::StreamMismatch { .. })));
+}
+
+#[tokio::test]
+async fn dropping_a_blocking_connection_inside_the_runtime_closes_it() {
+    let link = link().await;
+    let mut server = quic::QuicReceiver::new(link.server.clone());
+
+    drop(quic::QuicConnection::new(
+        tokio::runtime::Handle::current(),
+        link.client.clone(),
+    ));
+    assert!(timeout(WAIT, server.recv()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn strict_receiver_refuses_unknown_fields() {
+    let hb = heartbeat(&mut device());
+    let mut payload = envelope_encode(&hb).unwrap();
+    // Field 99, varint 1: unknown to the v1 schema.
+    payload.extend_from_slice(&[0x98, 0x06, 0x01]);
+    let record = record_encode(&payload).unwrap();
+
+    let link = self::link().await;
+    let mut lenient = quic::QuicReceiver::new(link.server.clone());
+    let mut stream = link.client.open_uni().await.unwrap();
+    stream.write_all(&record).await.unwrap();
+    let got = timeout(WAIT, lenient.recv()).await.unwrap().unwrap();
+    assert_eq!(got.unwrap().envelope, hb);
+
+    let link = self::link().await;
+    let mut strict = quic::QuicReceiver::limited(
+        link.server.clone(),
+        Limits::default().with_strict_decode(true),
+    );
+    let mut stream = link.client.open_uni().await.unwrap();
+    stream.write_all(&record).await.unwrap();
+    let got = timeout(WAIT, strict.recv()).await.unwrap().unwrap();
+    assert!(matches!(got, Err(TransportError::Wire(_))));
+}
@@ -0,0 +1,217 @@
+use std::io::{self, ErrorKind, Read};
+
+use hmf_transport::error::RecordError;
+use hmf_transport::record::{
+    MAX_RECORD_LEN, RECORD_HEADER_LEN, RECORD_MAGIC, RecordDecoder, record_decode, record_encode,
+    record_resync,
+};
+
+#[test]
+fn record_round_trips_with_versioned_header() {
+    let record = record_encode(b"hello").unwrap();
+
+    assert_eq!(record.len(), RECORD_HEADER_LEN + 5);
+    assert_eq!(record[..4], RECORD_MAGIC);
+    assert_eq!(record[4], 2);
+    assert_eq!(
+        record_decode(&mut record.as_slice(), MAX_RECORD_LEN).unwrap(),
+        b"hello"
+    );
+}
+
+#[test]
+fn non_hmf_bytes_fail_on_magic() {
+    let garbage = b"GET / HTTP/1.1\r\n\r\n";
+    assert!(matches!(
+        record_decode(&mut garbage.as_slice(), MAX_RECORD_LEN),
+        Err(RecordError::BadMagic { got }) if &got == b"GET "
+    ));
+}
+
+#[test]
+fn corrupted_header_fails_checksum_before_length_is_trusted() {
+    let mut record = record_encode(b"hello").unwrap();
+    record[6] = 0xff;
+    assert!(matches!(
+        record_decode(&mut record.as_slice(), MAX_RECORD_LEN),
+        Err(RecordError::HeaderChecksum { .. })
+    ));
+}
+
+#[test]
+fn unknown_version_and_flags_are_refused() {
+    let reseal = |record: &mut Vec<u8>| {
+        let crc = crc32(&record[..10]);
+        record[10..14].copy_from_slice(&crc.to_be_bytes());
+    };
+
+    let mut record = record_encode(b"hello").unwrap();
+    record[4] = 3;
+    reseal(&mut record);
+    assert!(matches!(
+        record_decode(&mut record.as_slice(), MAX_RECORD_LEN),
+        Err(RecordError::UnsupportedVersion { got: 3 })
+    ));
+
+    let mut record = record_encode(b"hello").unwrap();
+    record[5] = 0x01;
+    reseal(&mut record);
+    assert!(matches!(
+        record_decode(&mut record.as_slice(), MAX_RECORD_LEN),
+        Err(RecordError::UnknownFlags { flags: 0x01 })
+    ));
+}
+
+#[test]
+fn resync_finds_the_next_valid_header() {
+    let record = record_encode(b"hello").unwrap();
+    let mut stream = b"HMFRjunk".to_vec();
+    stream.extend_from_slice(&record);
+
+    let at = record_resync(&stream, MAX_RECORD_LEN).unwrap();
+    assert_eq!(at, 8);
+    assert_eq!(
+        record_decode(&mut &stream[at..], MAX_RECORD_LEN).unwrap(),
+        b"hello"
+    );
+    assert_eq!(record_resync(b"HMFRjunk", MAX_RECORD_LEN), None);
+}
+
+#[test]
+fn truncation_reports_bytes_actually_read() {
+    let record = record_encode(&[7u8; 100]).unwrap();
+
+    assert!(matches!(
+        record_decode(&mut &record[..0], MAX_RECORD_LEN),
+        Err(RecordError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof
+    ));
+    assert!(matches!(
+        record_decode(&mut &record[..5], MAX_RECORD_LEN),
+        Err(RecordError::Truncated {
+            expected: RECORD_HEADER_LEN,
+            got: 5
+        })
+    ));
+    assert!(matches!(
+        record_decode(&mut &record[..RECORD_HEADER_LEN + 42], MAX_RECORD_LEN),
+        Err(RecordError::Truncated {
+            expected: 100,
+            got: 42
+        })
+    ));
+}
+
+#[test]
+fn decoder_reassembles_records_from_any_split() {
+    let mut stream = record_encode(b"first").unwrap();
+    stream.extend_from_slice(&record_encode(&[9u8; 300]).unwrap());
+    stream.extend_from_slice(&record_encode(b"").unwrap());
+
+    for step in [1, 3, 13, 64, stream.len()] {
+        let mut decoder = RecordDecoder::new(MAX_RECORD_LEN);
+        let mut records = Vec::new();
+        for chunk in stream.chunks(step) {
+            decoder.feed(chunk);
+            while let Some(record) = decoder.next_record().unwrap() {
+                records.push(record);
+            }
+        }
+        decoder.finish().unwrap();
+        assert_eq!(records, [b"first".to_vec(), vec![9u8; 300], Vec::new()]);
+    }
+}
+
+#[test]
+fn decoder_reports_exact_truncation_at_end_of_input() {
+    let record = record_encode(&[7u8; 100]).unwrap();
+
+    let mut decoder = RecordDecoder::new(MAX_RECORD_LEN);
+    decoder.feed(&record[..9]);
+    assert!(matches!(
+        decoder.finish(),
+        Err(RecordError::Truncated {
+            expected: RECORD_HEADER_LEN,
+            got: 9
+        })
+    ));
+
+    decoder.feed(&record[9..RECORD_HEADER_LEN + 61]);
+    assert_eq!(decoder.next_record().unwrap(), None);
+    assert!(matches!(
+        decoder.finish(),
+        Err(RecordError::Truncated {
+            expected: 100,
+            got: 61
+        })
+    ));
+}
+
+/// Yields its bytes a few at a time, reporting `WouldBlock` between chunks.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    ready: bool,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.ready = !self.ready;
+        if !self.ready {
+            return Err(ErrorKind::WouldBlock.into());
+        }
+        let n = self.bytes.len().min(buf.len()).min(5);
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn decoder_resumes_after_would_block() {
+    let mut stream = record_encode(b"first").unwrap();
+    stream.extend_from_slice(&record_encode(b"second").unwrap());
+    let mut reader = Trickle {
+        bytes: &stream,
+        ready: true,
+    };
+
+    let mut decoder = RecordDecoder::new(MAX_RECORD_LEN);
+    let mut records = Vec::new();
+    let mut blocked = 0;
+    loop {
+        match decoder.read_from(&mut reader) {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => break,
+            Err(RecordError::Io(e)) if e.kind() == ErrorKind::WouldBlock => blocked += 1,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    assert!(blocked > 2);
+    assert_eq!(records, [b"first".to_vec(), b"second".to_vec()]);
+}
+
+#[test]
+fn decoder_rejects_garbage_headers() {
+    let mut decoder = RecordDecoder::new(MAX_RECORD_LEN);
+    decoder.feed(&[0u8; RECORD_HEADER_LEN]);
+    assert!(matches!(
+        decoder.next_record(),
+        Err(RecordError::BadMagic { .. })
+    ));
+}
+
+/// CRC-32 (IEEE), bitwise, to cross-check the header encoding independently.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
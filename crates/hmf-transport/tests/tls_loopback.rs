@@ -0,0 +1,416 @@
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use ed25519_dalek::SigningKey;
+use hmf_core::endpoint::{EndpointContext, Routing};
+use hmf_core::envelope::{
+    Envelope, Health, LifecycleHeartbeat, Payload, Telemetry, TelemetryPayload,
+};
+use hmf_core::ids::DeviceId;
+use hmf_transport::error::TransportError;
+use hmf_transport::limits::Limits;
+use hmf_transport::record::record_encode;
+use hmf_transport::transport::tls::{self, PeerTrust, SpkiPins, TlsListener, TlsStream};
+use hmf_transport::transport::{Connection, Limited, Transport};
+use hmf_wire_proto::wire::protobuf::envelope_encode;
+use rcgen::{CertificateParams, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore};
+
+struct Pki {
+    ca: CertificateDer<'static>,
+    server_chain: Vec<CertificateDer<'static>>,
+    server_key: PrivateKeyDer<'static>,
+    client_chain: Vec<CertificateDer<'static>>,
+    client_key: PrivateKeyDer<'static>,
+}
+
+fn pki() -> Pki {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+
+    let server_key = KeyPair::generate().unwrap();
+    let server_cert = CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .signed_by(&server_key, &ca_cert, &ca_key)
+        .unwrap();
+
+    let client_key = KeyPair::generate().unwrap();
+    let client_cert = CertificateParams::new(vec!["device-1".to_string()])
+        .unwrap()
+        .signed_by(&client_key, &ca_cert, &ca_key)
+        .unwrap();
+
+    Pki {
+        ca: ca_cert.der().clone(),
+        server_chain: vec![server_cert.der().clone()],
+        server_key: PrivatePkcs8KeyDer::from(server_key.serialize_der()).into(),
+        client_chain: vec![client_cert.der().clone()],
+        client_key: PrivatePkcs8KeyDer::from(client_key.serialize_der()).into(),
+    }
+}
+
+fn roots(pki: &Pki) -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.add(pki.ca.clone()).unwrap();
+    roots
+}
+
+fn heartbeat() -> Envelope {
+    heartbeat_from("device-1")
+}
+
+fn heartbeat_from(sender_id: &str) -> Envelope {
+    let mut endpoint = EndpointContext::new(
+        DeviceId::new(sender_id),
+        &format!("{sender_id}:ed25519:v1"),
+        SigningKey::from_bytes(&[7u8; 32]),
+    );
+    let telemetry = Telemetry {
+        payload: Some(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 42,
+            health: Health::Ok,
+        })),
+    };
+    endpoint
+        .send(
+            Payload::Telemetry(telemetry),
+            Routing::new(
+                "zone:demo",
+                "site-warden",
+                "hmf/telemetry/lifecycle_heartbeat",
+            ),
+        )
+        .unwrap()
+}
+
+fn listen() -> (TcpListener, std::net::SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    (listener, addr)
+}
+
+#[test]
+fn envelope_round_trips_over_tls13() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let mut stream = tls::accept(sock, server_config).unwrap();
+        let inbound = tls::read_record(&mut stream).unwrap().unwrap();
+        tls::write_record(&mut stream, &inbound.envelope).unwrap();
+    });
+
+    let client_config = tls::client_config(roots(&pki)).unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut stream = tls::connect(sock, "localhost", client_config).unwrap();
+    assert_eq!(
+        stream.conn.protocol_version(),
+        Some(rustls::ProtocolVersion::TLSv1_3)
+    );
+
+    let sent = heartbeat();
+    tls::write_record(&mut stream, &sent).unwrap();
+    let echoed = tls::read_record(&mut stream).unwrap().unwrap();
+    assert_eq!(echoed.envelope, sent);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn tls12_client_is_refused() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        tls::accept(sock, server_config)
+    });
+
+    let tls12 =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS12])
+            .unwrap()
+            .with_root_certificates(roots(&pki))
+            .with_no_client_auth();
+    let name = ServerName::try_from("localhost").unwrap();
+    let mut conn = ClientConnection::new(Arc::new(tls12), name).unwrap();
+    let mut sock = TcpStream::connect(addr).unwrap();
+    let mut client_result = Ok((0, 0));
+    while conn.is_handshaking() && client_result.is_ok() {
+        client_result = conn.complete_io(&mut sock);
+    }
+
+    assert!(client_result.is_err());
+    assert!(matches!(
+        server.join().unwrap(),
+        Err(TransportError::Tls(_))
+    ));
+}
+
+#[test]
+fn listener_skips_failed_and_stalled_handshakes() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+    let mut listener = TlsListener::new(listener, server_config)
+        .with_handshake_timeout(Duration::from_millis(200));
+
+    // Queued ahead of the real client: one never speaks, one speaks garbage.
+    let _stalled = TcpStream::connect(addr).unwrap();
+    let mut garbage = TcpStream::connect(addr).unwrap();
+    garbage.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+
+    let server = thread::spawn(move || {
+        let mut stream = listener.accept().unwrap();
+        let inbound = tls::read_record(&mut stream).unwrap().unwrap();
+        tls::write_record(&mut stream, &inbound.envelope).unwrap();
+    });
+
+    let client_config = tls::client_config(roots(&pki)).unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut stream = tls::connect(sock, "localhost", client_config).unwrap();
+    let sent = heartbeat();
+    tls::write_record(&mut stream, &sent).unwrap();
+    assert_eq!(
+        tls::read_record(&mut stream).unwrap().unwrap().envelope,
+        sent
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn strict_listener_refuses_unknown_fields() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+    let mut listener = Limited::new(
+        TlsListener::new(listener, server_config),
+        Limits::default().with_strict_decode(true),
+    );
+
+    let server = thread::spawn(move || {
+        let mut conn = listener.accept().unwrap();
+        let accepted = conn.recv().unwrap().unwrap();
+        (accepted, conn.recv())
+    });
+
+    let client_config = tls::client_config(roots(&pki)).unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut stream = tls::connect(sock, "localhost", client_config).unwrap();
+    let sent = heartbeat();
+    tls::write_record(&mut stream, &sent).unwrap();
+    let mut payload = envelope_encode(&sent).unwrap();
+    // Field 99, varint 1: unknown to the v1 schema.
+    payload.extend_from_slice(&[0x98, 0x06, 0x01]);
+    stream.write_all(&record_encode(&payload).unwrap()).unwrap();
+    stream.flush().unwrap();
+
+    let (accepted, refused) = server.join().unwrap();
+    assert_eq!(accepted.envelope, sent);
+    assert!(matches!(refused, Err(TransportError::Wire(_))));
+}
+
+#[test]
+fn untrusted_server_certificate_is_refused() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let _ = tls::accept(sock, server_config);
+    });
+
+    let unrelated_ca = self::pki();
+    let client_config = tls::client_config(roots(&unrelated_ca)).unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let result = tls::connect(sock, "localhost", client_config);
+    assert!(matches!(result, Err(TransportError::Tls(_))));
+
+    server.join().unwrap();
+}
+
+fn pins(pki: &Pki) -> SpkiPins {
+    SpkiPins::new()
+        .with_certificate(DeviceId::new("site-warden"), &pki.server_chain[0])
+        .unwrap()
+        .with_certificate(DeviceId::new("device-1"), &pki.client_chain[0])
+        .unwrap()
+}
+
+#[test]
+fn site_ca_mutual_tls_authenticates_both_sides() {
+    let pki = pki();
+    let trust = PeerTrust::SiteCa(Arc::new(roots(&pki)));
+    let server_config = tls::mutual_server_config(
+        pki.server_chain.clone(),
+        pki.server_key.clone_key(),
+        trust.clone(),
+    )
+    .unwrap();
+    let (listener, addr) = listen();
+    let client_pin = tls::spki_sha256(&pki.client_chain[0]).unwrap();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let mut stream = tls::accept(sock, server_config).unwrap();
+        assert_eq!(stream.peer_spki_sha256(), Some(client_pin));
+        tls::read_record(&mut stream).unwrap().unwrap()
+    });
+
+    let client_config =
+        tls::mutual_client_config(trust, pki.client_chain.clone(), pki.client_key.clone_key())
+            .unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut stream = tls::connect(sock, "localhost", client_config).unwrap();
+    let sent = heartbeat();
+    tls::write_record(&mut stream, &sent).unwrap();
+
+    let inbound = server.join().unwrap();
+    assert_eq!(inbound.envelope, sent);
+    assert_eq!(inbound.peer.spki_sha256, Some(client_pin));
+}
+
+#[test]
+fn site_ca_mutual_tls_refuses_client_without_certificate() {
+    let pki = pki();
+    let server_config = tls::mutual_server_config(
+        pki.server_chain.clone(),
+        pki.server_key.clone_key(),
+        PeerTrust::SiteCa(Arc::new(roots(&pki))),
+    )
+    .unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        tls::accept(sock, server_config).map(|_| ())
+    });
+
+    let client_config = tls::client_config(roots(&pki)).unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    // TLS 1.3 clients finish before the server judges their certificate, so
+    // the refusal is observed by the server.
+    let _ = tls::connect(sock, "localhost", client_config);
+
+    assert!(matches!(
+        server.join().unwrap(),
+        Err(TransportError::Tls(_))
+    ));
+}
+
+#[test]
+fn pinned_keys_bind_connection_to_enrolled_identity() {
+    let pki = pki();
+    let pins = pins(&pki);
+    let server_config = tls::mutual_server_config(
+        pki.server_chain.clone(),
+        pki.server_key.clone_key(),
+        PeerTrust::Pinned(pins.clone()),
+    )
+    .unwrap();
+    let (listener, addr) = listen();
+
+    // The server enforces the pin on every read, without the caller asking.
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let mut stream = tls::accept(sock, server_config).unwrap();
+        let accepted = tls::read_record(&mut stream).unwrap().unwrap();
+        (accepted, tls::read_record(&mut stream))
+    });
+
+    // Pinned mode ignores issuers; the server name is the pinned identity.
+    let client_config = tls::mutual_client_config(
+        PeerTrust::Pinned(pins),
+        pki.client_chain.clone(),
+        pki.client_key.clone_key(),
+    )
+    .unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut stream = tls::connect(sock, "site-warden", client_config).unwrap();
+    let sent = heartbeat();
+    tls::write_record(&mut stream, &sent).unwrap();
+    tls::write_record(&mut stream, &heartbeat_from("device-2")).unwrap();
+    stream.conn.send_close_notify();
+    stream.flush().unwrap();
+
+    let (accepted, refused) = server.join().unwrap();
+    assert_eq!(accepted.envelope, sent);
+    assert!(matches!(
+        refused,
+        Err(TransportError::PeerMismatch { pinned, sender_id })
+            if pinned.as_str() == "device-1" && sender_id.as_str() == "device-2"
+    ));
+}
+
+#[test]
+fn unpinned_server_key_is_refused() {
+    let pki = pki();
+    let server_config =
+        tls::server_config(pki.server_chain.clone(), pki.server_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let _ = tls::accept(sock, server_config);
+    });
+
+    let other = self::pki();
+    let client_config = tls::mutual_client_config(
+        PeerTrust::Pinned(self::pins(&other)),
+        pki.client_chain.clone(),
+        pki.client_key.clone_key(),
+    )
+    .unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let result = tls::connect(sock, "localhost", client_config);
+    assert!(matches!(result, Err(TransportError::Tls(_))));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn server_key_pinned_to_another_identity_is_refused() {
+    let pki = pki();
+    // The server presents device-1's pinned key instead of the warden's.
+    let server_config =
+        tls::server_config(pki.client_chain.clone(), pki.client_key.clone_key()).unwrap();
+    let (listener, addr) = listen();
+
+    let server = thread::spawn(move || {
+        let (sock, _) = listener.accept().unwrap();
+        let _ = tls::accept(sock, server_config);
+    });
+
+    let client_config = tls::mutual_client_config(
+        PeerTrust::Pinned(pins(&pki)),
+        pki.client_chain.clone(),
+        pki.client_key.clone_key(),
+    )
+    .unwrap();
+    let sock = TcpStream::connect(addr).unwrap();
+    let result = tls::connect(sock, "site-warden", client_config);
+    assert!(matches!(
+        result,
+        Err(TransportError::Tls(rustls::Error::InvalidCertificate(
+            rustls::CertificateError::NotValidForName
+        )))
+    ));
+
+    server.join().unwrap();
+}
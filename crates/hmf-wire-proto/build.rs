@@ -9,7 +9,9 @@ fn main() {
     ];
     let proto_include_dirs = &["proto"];
 
+    // Ordered maps make the encoding canonical, which strict decoding relies on.
     prost_build::Config::new()
+        .btree_map(["."])
         .compile_protos(proto_files, proto_include_dirs)
         .expect("failed to compile protos");
 
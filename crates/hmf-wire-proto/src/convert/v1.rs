@@ -1,17 +1,7 @@
-use std::collections::{BTreeMap, HashMap};
-
 use crate::error::WireError;
 use crate::proto::v1 as proto;
 use hmf_core::envelope as core;
 
-fn map_to_btree(m: HashMap<String, String>) -> BTreeMap<String, String> {
-    m.into_iter().collect()
-}
-
-fn btree_to_hash(m: &BTreeMap<String, String>) -> HashMap<String, String> {
-    m.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
-}
-
 pub fn envelope_proto_to_core(p: proto::Envelope) -> Result<core::Envelope, WireError> {
     Ok(core::Envelope {
         proto_ver: p.proto_ver,
@@ -275,7 +265,7 @@ fn command_payload_proto_to_core(
                 request_id: r.request_id,
                 command: r.command,
                 target: r.target,
-                params: map_to_btree(r.params),
+                params: r.params,
                 blob: r.blob,
                 requires_confirmation: r.requires_confirmation,
             })
@@ -283,6 +273,7 @@ fn command_payload_proto_to_core(
         proto::command::Payload::Ack(a) => core::CommandPayload::Ack(core::Ack {
             status: core::AckStatus::from_i32(a.status),
             detail: a.detail,
+            reason: core::RejectReason::from_i32(a.reason),
         }),
         proto::command::Payload::Result(r) => core::CommandPayload::Result(core::OpResult {
             status: core::ResultStatus::from_i32(r.status),
@@ -298,7 +289,7 @@ fn command_payload_core_to_proto(c: &core::CommandPayload) -> proto::command::Pa
                 request_id: r.request_id.clone(),
                 command: r.command.clone(),
                 target: r.target.clone(),
-                params: btree_to_hash(&r.params),
+                params: r.params.clone(),
                 blob: r.blob.clone(),
                 requires_confirmation: r.requires_confirmation,
             })
@@ -306,6 +297,7 @@ fn command_payload_core_to_proto(c: &core::CommandPayload) -> proto::command::Pa
         core::CommandPayload::Ack(a) => proto::command::Payload::Ack(proto::Ack {
             status: a.status.to_i32(),
             detail: a.detail.clone(),
+            reason: a.reason.to_i32(),
         }),
         core::CommandPayload::Result(r) => proto::command::Payload::Result(proto::Result {
             status: r.status.to_i32(),
@@ -338,17 +330,19 @@ fn config_payload_proto_to_core(
         proto::config::Payload::Snapshot(s) => {
             core::ConfigPayload::Snapshot(core::ConfigSnapshot {
                 config_version: s.config_version,
-                params: map_to_btree(s.params),
+                params: s.params,
             })
         }
         proto::config::Payload::Update(u) => core::ConfigPayload::Update(core::ConfigUpdate {
             update_id: u.update_id,
             strict: u.strict,
-            params: map_to_btree(u.params),
+            params: u.params,
+            expected_version: u.expected_version,
         }),
         proto::config::Payload::Ack(a) => core::ConfigPayload::Ack(core::Ack {
             status: core::AckStatus::from_i32(a.status),
             detail: a.detail,
+            reason: core::RejectReason::from_i32(a.reason),
         }),
         proto::config::Payload::Result(r) => core::ConfigPayload::Result(core::OpResult {
             status: core::ResultStatus::from_i32(r.status),
@@ -365,17 +359,19 @@ fn config_payload_core_to_proto(c: &core::ConfigPayload) -> proto::config::Paylo
         core::ConfigPayload::Snapshot(s) => {
             proto::config::Payload::Snapshot(proto::ConfigSnapshot {
                 config_version: s.config_version,
-                params: btree_to_hash(&s.params),
+                params: s.params.clone(),
             })
         }
         core::ConfigPayload::Update(u) => proto::config::Payload::Update(proto::ConfigUpdate {
             update_id: u.update_id.clone(),
             strict: u.strict,
-            params: btree_to_hash(&u.params),
+            params: u.params.clone(),
+            expected_version: u.expected_version,
         }),
         core::ConfigPayload::Ack(a) => proto::config::Payload::Ack(proto::Ack {
             status: a.status.to_i32(),
             detail: a.detail.clone(),
+            reason: a.reason.to_i32(),
         }),
         core::ConfigPayload::Result(r) => proto::config::Payload::Result(proto::Result {
             status: r.status.to_i32(),
@@ -410,7 +406,7 @@ fn engineering_payload_proto_to_core(
                 request_id: r.request_id,
                 action: r.action,
                 target: r.target,
-                params: map_to_btree(r.params),
+                params: r.params,
                 blob: r.blob,
                 requires_confirmation: r.requires_confirmation,
             })
@@ -418,12 +414,13 @@ fn engineering_payload_proto_to_core(
         proto::engineering::Payload::Ack(a) => core::EngineeringPayload::Ack(core::Ack {
             status: core::AckStatus::from_i32(a.status),
             detail: a.detail,
+            reason: core::RejectReason::from_i32(a.reason),
         }),
         proto::engineering::Payload::Result(r) => {
             core::EngineeringPayload::Result(core::EngineeringResult {
                 status: core::ResultStatus::from_i32(r.status),
                 detail: r.detail,
-                outputs: map_to_btree(r.outputs),
+                outputs: r.outputs,
                 blob: r.blob,
             })
         }
@@ -437,7 +434,7 @@ fn engineering_payload_core_to_proto(c: &core::EngineeringPayload) -> proto::eng
                 request_id: r.request_id.clone(),
                 action: r.action.clone(),
                 target: r.target.clone(),
-                params: btree_to_hash(&r.params),
+                params: r.params.clone(),
                 blob: r.blob.clone(),
                 requires_confirmation: r.requires_confirmation,
             })
@@ -445,12 +442,13 @@ fn engineering_payload_core_to_proto(c: &core::EngineeringPayload) -> proto::eng
         core::EngineeringPayload::Ack(a) => proto::engineering::Payload::Ack(proto::Ack {
             status: a.status.to_i32(),
             detail: a.detail.clone(),
+            reason: a.reason.to_i32(),
         }),
         core::EngineeringPayload::Result(r) => {
             proto::engineering::Payload::Result(proto::EngineeringResult {
                 status: r.status.to_i32(),
                 detail: r.detail.clone(),
-                outputs: btree_to_hash(&r.outputs),
+                outputs: r.outputs.clone(),
                 blob: r.blob.clone(),
             })
         }
@@ -10,4 +10,7 @@ pub enum WireError {
 
     #[error("conversion error: {0}")]
     Convert(String),
+
+    #[error(transparent)]
+    Limit(#[from] hmf_core::error::LimitError),
 }
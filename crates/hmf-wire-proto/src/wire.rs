@@ -1 +1,4 @@
+pub(crate) mod limits;
 pub mod protobuf;
+pub(crate) mod schema;
+pub(crate) mod strict;
@@ -0,0 +1,129 @@
+//! Allocation-free pre-scan of encoded envelopes against [`DecodeLimits`].
+//!
+//! Runs over the raw protobuf bytes before prost materialises anything, so a
+//! record packed with millions of repeated items or map entries is refused
+//! without building them.
+
+use hmf_core::envelope::MsgClass;
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::error::LimitError;
+
+use crate::wire::schema::{Class, ENVELOPE, Kind, MAX_FIELDS, Message, read_varint};
+
+impl Class {
+    fn msg_class(self) -> MsgClass {
+        match self {
+            Class::Telemetry => MsgClass::Telemetry,
+            Class::Command => MsgClass::Command,
+            Class::Config => MsgClass::Config,
+            Class::Engineering => MsgClass::Engineering,
+        }
+    }
+}
+
+pub(crate) enum ScanError {
+    Limit(LimitError),
+    /// Not valid protobuf; left for the decoder to report.
+    Malformed,
+}
+
+impl From<LimitError> for ScanError {
+    fn from(e: LimitError) -> Self {
+        Self::Limit(e)
+    }
+}
+
+/// Checks an encoded `Envelope` against `limits` without allocating.
+pub(crate) fn scan_envelope(bytes: &[u8], limits: &DecodeLimits) -> Result<(), ScanError> {
+    scan(bytes, &ENVELOPE, &MsgClass::Unspecified, limits)
+}
+
+fn scan(
+    mut buf: &[u8],
+    message: &'static Message,
+    class: &MsgClass,
+    limits: &DecodeLimits,
+) -> Result<(), ScanError> {
+    debug_assert!(message.fields.len() <= MAX_FIELDS);
+    let mut counts = [0usize; MAX_FIELDS];
+
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        match key & 7 {
+            0 => {
+                varint(&mut buf)?;
+            }
+            1 => skip(&mut buf, 8)?,
+            5 => skip(&mut buf, 4)?,
+            2 => {
+                let len = usize::try_from(varint(&mut buf)?).map_err(|_| ScanError::Malformed)?;
+                let data = buf.get(..len).ok_or(ScanError::Malformed)?;
+                buf = &buf[len..];
+
+                let Some((i, field)) = message.field(key >> 3) else {
+                    continue;
+                };
+                counts[i] += 1;
+                match field.kind {
+                    Kind::Varint | Kind::Fixed64 | Kind::Bytes => {}
+                    Kind::Str => limits.check_string(field.name, len)?,
+                    Kind::Blob => limits.check_blob(class, len)?,
+                    Kind::AuthContext => limits.check_auth_context(len)?,
+                    Kind::Msg(inner) => scan(data, inner, class, limits)?,
+                    Kind::Payload(payload_class, inner) => {
+                        scan(data, inner, &payload_class.msg_class(), limits)?
+                    }
+                    Kind::RepeatedStr => {
+                        limits.check_repeated(field.name, counts[i])?;
+                        limits.check_string(field.name, len)?;
+                    }
+                    Kind::RepeatedMsg(inner) => {
+                        limits.check_repeated(field.name, counts[i])?;
+                        scan(data, inner, class, limits)?;
+                    }
+                    Kind::Map => {
+                        limits.check_map(field.name, counts[i])?;
+                        scan_map_entry(data, field.name, limits)?;
+                    }
+                }
+            }
+            _ => return Err(ScanError::Malformed),
+        }
+    }
+    Ok(())
+}
+
+/// Map entries are `{ string key = 1; string value = 2; }`; both count
+/// against the string limit under the map's own name.
+fn scan_map_entry(
+    mut buf: &[u8],
+    name: &'static str,
+    limits: &DecodeLimits,
+) -> Result<(), ScanError> {
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        match key & 7 {
+            0 => {
+                varint(&mut buf)?;
+            }
+            1 => skip(&mut buf, 8)?,
+            5 => skip(&mut buf, 4)?,
+            2 => {
+                let len = usize::try_from(varint(&mut buf)?).map_err(|_| ScanError::Malformed)?;
+                limits.check_string(name, len)?;
+                skip(&mut buf, len)?;
+            }
+            _ => return Err(ScanError::Malformed),
+        }
+    }
+    Ok(())
+}
+
+fn varint(buf: &mut &[u8]) -> Result<u64, ScanError> {
+    read_varint(buf).ok_or(ScanError::Malformed)
+}
+
+fn skip(buf: &mut &[u8], n: usize) -> Result<(), ScanError> {
+    *buf = buf.get(n..).ok_or(ScanError::Malformed)?;
+    Ok(())
+}
@@ -3,14 +3,54 @@ use prost::Message;
 use crate::convert::v1::{envelope_core_to_proto, envelope_proto_to_core};
 use crate::error::WireError;
 use crate::proto::v1 as proto;
+use crate::wire::limits::{ScanError, scan_envelope};
+use crate::wire::strict::{StrictError, check_canonical, check_envelope, check_header_enums};
 use hmf_core::envelope::Envelope;
+use hmf_core::envelope::limits::DecodeLimits;
 
 pub fn envelope_encode(env: &Envelope) -> Result<Vec<u8>, WireError> {
     let p: proto::Envelope = envelope_core_to_proto(env);
     Ok(p.encode_to_vec())
 }
 
+/// Decodes an envelope under the default [`DecodeLimits`].
 pub fn envelope_decode(bytes: &[u8]) -> Result<Envelope, WireError> {
+    envelope_decode_with(bytes, &DecodeLimits::default())
+}
+
+/// Decodes an envelope, refusing oversized fields before any are materialised.
+pub fn envelope_decode_with(bytes: &[u8], limits: &DecodeLimits) -> Result<Envelope, WireError> {
+    if let Err(ScanError::Limit(e)) = scan_envelope(bytes, limits) {
+        return Err(e.into());
+    }
+    // Malformed input is left to prost so its error is reported unchanged.
     let p = proto::Envelope::decode(bytes)?;
     envelope_proto_to_core(p)
 }
+
+/// Strict decoding under the default [`DecodeLimits`].
+pub fn envelope_decode_strict(bytes: &[u8]) -> Result<Envelope, WireError> {
+    envelope_decode_strict_with(bytes, &DecodeLimits::default())
+}
+
+/// Decodes an envelope only if `bytes` is its canonical encoding.
+///
+/// Unlike [`envelope_decode_with`], nothing is silently dropped: unknown
+/// fields, non-canonical encodings and unknown `msg_class`, `sig_alg` or
+/// `delivery_profile` values are refused with [`WireError::Convert`].
+pub fn envelope_decode_strict_with(
+    bytes: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Envelope, WireError> {
+    if let Err(ScanError::Limit(e)) = scan_envelope(bytes, limits) {
+        return Err(e.into());
+    }
+    if let Err(StrictError::Reject(reason)) = check_envelope(bytes) {
+        return Err(WireError::Convert(reason));
+    }
+    let p = proto::Envelope::decode(bytes)?;
+    check_canonical(bytes, &p.encode_to_vec())?;
+    let env = envelope_proto_to_core(p)?;
+    check_header_enums(&env)?;
+    Ok(env)
+}
@@ -0,0 +1,305 @@
+//! Field tables for the v1 messages, for walks over encoded bytes that must
+//! not wait for prost to materialise anything.
+//!
+//! Must list every field in `proto/`; the strict decoder treats anything
+//! missing here as unknown.
+
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    /// `uint32`, `uint64`, `int64`, `bool` and enums.
+    Varint,
+    /// `double`.
+    Fixed64,
+    Str,
+    /// `bytes` with no size limit of its own, such as `signature`.
+    Bytes,
+    /// `bytes` counted against the blob limit of the payload's message class.
+    Blob,
+    AuthContext,
+    Msg(&'static Message),
+    /// A payload oneof member; sets the message class that governs blob
+    /// limits below it.
+    Payload(Class, &'static Message),
+    RepeatedStr,
+    RepeatedMsg(&'static Message),
+    /// `map<string, string>`.
+    Map,
+}
+
+impl Kind {
+    pub(crate) fn wire_type(self) -> u64 {
+        match self {
+            Kind::Varint => 0,
+            Kind::Fixed64 => 1,
+            _ => 2,
+        }
+    }
+
+    pub(crate) fn is_repeated(self) -> bool {
+        matches!(self, Kind::RepeatedStr | Kind::RepeatedMsg(_) | Kind::Map)
+    }
+}
+
+/// `Copy` stand-in for the payload's [`hmf_core::envelope::MsgClass`].
+#[derive(Clone, Copy)]
+pub(crate) enum Class {
+    Telemetry,
+    Command,
+    Config,
+    Engineering,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Field {
+    pub(crate) number: u64,
+    pub(crate) name: &'static str,
+    pub(crate) kind: Kind,
+}
+
+pub(crate) struct Message {
+    pub(crate) name: &'static str,
+    pub(crate) fields: &'static [Field],
+}
+
+impl Message {
+    pub(crate) fn field(&self, number: u64) -> Option<(usize, Field)> {
+        self.fields
+            .iter()
+            .position(|f| f.number == number)
+            .map(|i| (i, self.fields[i]))
+    }
+}
+
+/// Upper bound on fields per message, for fixed-size per-field counters.
+pub(crate) const MAX_FIELDS: usize = 24;
+
+const fn f(number: u64, name: &'static str, kind: Kind) -> Field {
+    Field { number, name, kind }
+}
+
+pub(crate) static MAP_ENTRY: Message = Message {
+    name: "MapEntry",
+    fields: &[f(1, "key", Kind::Str), f(2, "value", Kind::Str)],
+};
+
+static ACK: Message = Message {
+    name: "Ack",
+    fields: &[
+        f(1, "status", Kind::Varint),
+        f(2, "detail", Kind::Str),
+        f(3, "reason", Kind::Varint),
+    ],
+};
+static RESULT: Message = Message {
+    name: "Result",
+    fields: &[f(1, "status", Kind::Varint), f(2, "detail", Kind::Str)],
+};
+
+static BIRTH: Message = Message {
+    name: "LifecycleBirth",
+    fields: &[
+        f(1, "vendor", Kind::Str),
+        f(2, "model", Kind::Str),
+        f(3, "serial", Kind::Str),
+        f(4, "hw_rev", Kind::Str),
+        f(5, "fw_rev", Kind::Str),
+        f(6, "uptime_ms", Kind::Varint),
+        f(7, "capabilities", Kind::RepeatedStr),
+    ],
+};
+static HEARTBEAT: Message = Message {
+    name: "LifecycleHeartbeat",
+    fields: &[
+        f(1, "uptime_ms", Kind::Varint),
+        f(2, "health", Kind::Varint),
+    ],
+};
+static DEATH: Message = Message {
+    name: "LifecycleDeath",
+    fields: &[
+        f(1, "reason", Kind::Varint),
+        f(2, "uptime_ms", Kind::Varint),
+        f(3, "detail", Kind::Str),
+    ],
+};
+static STATE_ITEM: Message = Message {
+    name: "StateItem",
+    fields: &[
+        f(1, "key", Kind::Str),
+        f(2, "unit", Kind::Str),
+        f(3, "quality", Kind::Varint),
+        f(10, "f64", Kind::Fixed64),
+        f(11, "i64", Kind::Varint),
+        f(12, "u64", Kind::Varint),
+        f(13, "b", Kind::Varint),
+        f(14, "s", Kind::Str),
+        f(15, "blob", Kind::Blob),
+    ],
+};
+static STATE: Message = Message {
+    name: "StateUpdate",
+    fields: &[
+        f(1, "local_seq", Kind::Varint),
+        f(2, "items", Kind::RepeatedMsg(&STATE_ITEM)),
+    ],
+};
+static ALARM: Message = Message {
+    name: "Alarm",
+    fields: &[
+        f(1, "severity", Kind::Varint),
+        f(2, "code", Kind::Str),
+        f(3, "summary", Kind::Str),
+        f(4, "detail", Kind::Str),
+        f(5, "related_key", Kind::Str),
+        f(6, "recommended_action", Kind::Str),
+    ],
+};
+static SUBJECT: Message = Message {
+    name: "Subject",
+    fields: &[f(1, "subject_id", Kind::Str)],
+};
+static OBSERVATION: Message = Message {
+    name: "Observation",
+    fields: &[
+        f(1, "subject", Kind::Msg(&SUBJECT)),
+        f(2, "type", Kind::Varint),
+        f(3, "confidence_pct", Kind::Varint),
+        f(4, "summary", Kind::Str),
+        f(5, "detail", Kind::Str),
+    ],
+};
+static TELEMETRY: Message = Message {
+    name: "Telemetry",
+    fields: &[
+        f(1, "birth", Kind::Msg(&BIRTH)),
+        f(2, "heartbeat", Kind::Msg(&HEARTBEAT)),
+        f(3, "death", Kind::Msg(&DEATH)),
+        f(4, "state", Kind::Msg(&STATE)),
+        f(5, "alarm", Kind::Msg(&ALARM)),
+        f(6, "observation", Kind::Msg(&OBSERVATION)),
+    ],
+};
+
+static COMMAND_REQUEST: Message = Message {
+    name: "CommandRequest",
+    fields: &[
+        f(1, "request_id", Kind::Str),
+        f(2, "command", Kind::Str),
+        f(3, "target", Kind::Str),
+        f(4, "params", Kind::Map),
+        f(5, "blob", Kind::Blob),
+        f(6, "requires_confirmation", Kind::Varint),
+    ],
+};
+static COMMAND: Message = Message {
+    name: "Command",
+    fields: &[
+        f(1, "request", Kind::Msg(&COMMAND_REQUEST)),
+        f(2, "ack", Kind::Msg(&ACK)),
+        f(3, "result", Kind::Msg(&RESULT)),
+    ],
+};
+
+static CONFIG_QUERY: Message = Message {
+    name: "ConfigQuery",
+    fields: &[f(1, "keys", Kind::RepeatedStr)],
+};
+static CONFIG_SNAPSHOT: Message = Message {
+    name: "ConfigSnapshot",
+    fields: &[
+        f(1, "config_version", Kind::Varint),
+        f(2, "params", Kind::Map),
+    ],
+};
+static CONFIG_UPDATE: Message = Message {
+    name: "ConfigUpdate",
+    fields: &[
+        f(1, "update_id", Kind::Str),
+        f(2, "strict", Kind::Varint),
+        f(3, "params", Kind::Map),
+        f(4, "expected_version", Kind::Varint),
+    ],
+};
+static CONFIG: Message = Message {
+    name: "Config",
+    fields: &[
+        f(1, "query", Kind::Msg(&CONFIG_QUERY)),
+        f(2, "snapshot", Kind::Msg(&CONFIG_SNAPSHOT)),
+        f(3, "update", Kind::Msg(&CONFIG_UPDATE)),
+        f(4, "ack", Kind::Msg(&ACK)),
+        f(5, "result", Kind::Msg(&RESULT)),
+    ],
+};
+
+static ENGINEERING_REQUEST: Message = Message {
+    name: "EngineeringRequest",
+    fields: &[
+        f(1, "request_id", Kind::Str),
+        f(2, "action", Kind::Str),
+        f(3, "target", Kind::Str),
+        f(4, "params", Kind::Map),
+        f(5, "blob", Kind::Blob),
+        f(6, "requires_confirmation", Kind::Varint),
+    ],
+};
+static ENGINEERING_RESULT: Message = Message {
+    name: "EngineeringResult",
+    fields: &[
+        f(1, "status", Kind::Varint),
+        f(2, "detail", Kind::Str),
+        f(3, "outputs", Kind::Map),
+        f(4, "blob", Kind::Blob),
+    ],
+};
+static ENGINEERING: Message = Message {
+    name: "Engineering",
+    fields: &[
+        f(1, "request", Kind::Msg(&ENGINEERING_REQUEST)),
+        f(2, "ack", Kind::Msg(&ACK)),
+        f(3, "result", Kind::Msg(&ENGINEERING_RESULT)),
+    ],
+};
+
+pub(crate) static ENVELOPE: Message = Message {
+    name: "Envelope",
+    fields: &[
+        f(1, "proto_ver", Kind::Varint),
+        f(2, "msg_class", Kind::Varint),
+        f(3, "sender_id", Kind::Str),
+        f(4, "sender_instance", Kind::Str),
+        f(5, "counter", Kind::Varint),
+        f(6, "ttl_ms", Kind::Varint),
+        f(7, "transaction_id", Kind::Str),
+        f(8, "idempotency_key", Kind::Str),
+        f(9, "delivery_profile", Kind::Varint),
+        f(10, "topic", Kind::Str),
+        f(11, "target", Kind::Str),
+        f(12, "scope", Kind::Str),
+        f(20, "telemetry", Kind::Payload(Class::Telemetry, &TELEMETRY)),
+        f(21, "command", Kind::Payload(Class::Command, &COMMAND)),
+        f(22, "config", Kind::Payload(Class::Config, &CONFIG)),
+        f(
+            23,
+            "engineering",
+            Kind::Payload(Class::Engineering, &ENGINEERING),
+        ),
+        f(30, "sig_alg", Kind::Varint),
+        f(31, "signature", Kind::Bytes),
+        f(32, "key_id", Kind::Str),
+        f(33, "auth_context", Kind::AuthContext),
+    ],
+};
+
+/// One protobuf varint; `None` if truncated or longer than ten bytes.
+pub(crate) fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first()?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
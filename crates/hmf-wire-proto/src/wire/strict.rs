@@ -0,0 +1,141 @@
+//! Strict decoding: the encoded bytes must be exactly what this
+//! implementation would have produced for the decoded envelope.
+//!
+//! Anything prost would otherwise drop or normalise—unknown fields, repeated
+//! scalars, padded varints, explicit defaults, unsorted map entries—is data
+//! the signature does not cover, so it is refused rather than ignored.
+
+use hmf_core::envelope::{DeliveryProfile, Envelope, MsgClass, SigAlg};
+
+use crate::error::WireError;
+use crate::wire::schema::{ENVELOPE, Kind, MAP_ENTRY, Message};
+
+pub(crate) enum StrictError {
+    Reject(String),
+    /// Not valid protobuf; left for the decoder to report.
+    Malformed,
+}
+
+/// Walks an encoded `Envelope`, refusing unknown fields, unexpected wire
+/// types, out-of-order or duplicated fields and non-minimal varints.
+pub(crate) fn check_envelope(bytes: &[u8]) -> Result<(), StrictError> {
+    check(bytes, &ENVELOPE)
+}
+
+fn check(mut buf: &[u8], message: &'static Message) -> Result<(), StrictError> {
+    let mut last: Option<u64> = None;
+
+    while !buf.is_empty() {
+        let key = varint(&mut buf, message, "field key")?;
+        let number = key >> 3;
+        let Some((_, field)) = message.field(number) else {
+            return Err(reject(format!(
+                "unknown field {number} in {}",
+                message.name
+            )));
+        };
+        let path = || format!("{}.{}", message.name, field.name);
+
+        if key & 7 != field.kind.wire_type() {
+            return Err(reject(format!(
+                "{}: wire type {}, expected {}",
+                path(),
+                key & 7,
+                field.kind.wire_type()
+            )));
+        }
+        match last {
+            Some(prev) if number < prev => {
+                return Err(reject(format!("{} out of field order", path())));
+            }
+            Some(prev) if number == prev && !field.kind.is_repeated() => {
+                return Err(reject(format!("{} appears more than once", path())));
+            }
+            _ => {}
+        }
+        last = Some(number);
+
+        match field.kind {
+            Kind::Varint => {
+                varint(&mut buf, message, field.name)?;
+            }
+            Kind::Fixed64 => {
+                buf = buf.get(8..).ok_or(StrictError::Malformed)?;
+            }
+            Kind::Str | Kind::Bytes | Kind::Blob | Kind::AuthContext | Kind::RepeatedStr => {
+                length_delimited(&mut buf, message, field.name)?;
+            }
+            Kind::Msg(inner) | Kind::Payload(_, inner) | Kind::RepeatedMsg(inner) => {
+                check(length_delimited(&mut buf, message, field.name)?, inner)?;
+            }
+            Kind::Map => {
+                check(length_delimited(&mut buf, message, field.name)?, &MAP_ENTRY)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn length_delimited<'a>(
+    buf: &mut &'a [u8],
+    message: &Message,
+    field: &str,
+) -> Result<&'a [u8], StrictError> {
+    let len = varint(buf, message, field)?;
+    let len = usize::try_from(len).map_err(|_| StrictError::Malformed)?;
+    let data = buf.get(..len).ok_or(StrictError::Malformed)?;
+    *buf = &buf[len..];
+    Ok(data)
+}
+
+/// Reads a varint, refusing padded encodings such as `0x80 0x00` for zero.
+fn varint(buf: &mut &[u8], message: &Message, field: &str) -> Result<u64, StrictError> {
+    let start = *buf;
+    let value = crate::wire::schema::read_varint(buf).ok_or(StrictError::Malformed)?;
+    let used = start.len() - buf.len();
+    if used > 1 && start[used - 1] == 0 {
+        return Err(reject(format!(
+            "non-canonical varint in {}.{field}",
+            message.name
+        )));
+    }
+    Ok(value)
+}
+
+/// Compares `bytes` with the canonical re-encoding of what prost decoded.
+pub(crate) fn check_canonical(bytes: &[u8], reencoded: &[u8]) -> Result<(), WireError> {
+    if bytes == reencoded {
+        return Ok(());
+    }
+    let at = bytes
+        .iter()
+        .zip(reencoded)
+        .position(|(a, b)| a != b)
+        .unwrap_or(bytes.len().min(reencoded.len()));
+    Err(WireError::Convert(format!(
+        "non-canonical encoding: differs from canonical form at byte {at}"
+    )))
+}
+
+/// Refuses header enum values this implementation does not know.
+///
+/// Unknown values in these fields would change how the envelope is routed,
+/// verified or delivered, so they cannot be carried through as `Unknown`.
+pub(crate) fn check_header_enums(env: &Envelope) -> Result<(), WireError> {
+    if let MsgClass::Unknown(v) = env.msg_class {
+        return Err(WireError::Convert(format!("unknown msg_class value {v}")));
+    }
+    if let SigAlg::Unknown(v) = env.sig_alg {
+        return Err(WireError::Convert(format!("unknown sig_alg value {v}")));
+    }
+    if let DeliveryProfile::Unknown(v) = env.delivery_profile {
+        return Err(WireError::Convert(format!(
+            "unknown delivery_profile value {v}"
+        )));
+    }
+    Ok(())
+}
+
+fn reject(reason: String) -> StrictError {
+    StrictError::Reject(reason)
+}
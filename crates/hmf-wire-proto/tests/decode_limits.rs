@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use hmf_core::Envelope;
+use hmf_core::envelope::limits::DecodeLimits;
+use hmf_core::envelope::{
+    Command, CommandPayload, CommandRequest, DeliveryProfile, EnvelopeBuilder, MsgClass, Payload,
+    Quality, SigAlg, StateItem, StateUpdate, StateValue, Telemetry, TelemetryPayload,
+};
+use hmf_core::envelope::{envelope_validate, envelope_validate_with};
+use hmf_core::error::{LimitError, ValidateError};
+use hmf_wire_proto::error::WireError;
+use hmf_wire_proto::wire::protobuf::{envelope_decode, envelope_decode_with, envelope_encode};
+
+fn envelope(payload: Payload, auth_context: Vec<u8>) -> Envelope {
+    EnvelopeBuilder::new()
+        .with_sender_id("device-1")
+        .with_sender_instance("boot-1")
+        .with_counter(1)
+        .with_ttl_ms(5_000)
+        .with_delivery_profile(DeliveryProfile::BestEffort)
+        .with_payload(payload)
+        .with_security(
+            SigAlg::Ed25519,
+            "device-1:ed25519:v1",
+            auth_context,
+            vec![0; 64],
+        )
+        .build()
+}
+
+fn state(items: usize, blob: usize) -> Payload {
+    let item = StateItem {
+        key: "temp".to_string(),
+        value: Some(StateValue::Blob(vec![0; blob])),
+        unit: String::new(),
+        quality: Quality::Good,
+    };
+    Payload::Telemetry(Telemetry {
+        payload: Some(TelemetryPayload::State(StateUpdate {
+            local_seq: 1,
+            items: vec![item; items],
+        })),
+    })
+}
+
+fn command(params: usize, command: &str, blob: usize) -> Payload {
+    Payload::Command(Command {
+        payload: Some(CommandPayload::Request(CommandRequest {
+            request_id: "req-1".to_string(),
+            command: command.to_string(),
+            target: "valve-1".to_string(),
+            params: (0..params)
+                .map(|i| (format!("k{i}"), "v".to_string()))
+                .collect::<BTreeMap<_, _>>(),
+            blob: vec![0; blob],
+            requires_confirmation: false,
+        })),
+    })
+}
+
+fn decode(env: &Envelope, limits: &DecodeLimits) -> Result<Envelope, WireError> {
+    envelope_decode_with(&envelope_encode(env).unwrap(), limits)
+}
+
+#[test]
+fn envelopes_within_limits_round_trip() {
+    let env = envelope(command(4, "open", 16), vec![0; 32]);
+    assert_eq!(
+        envelope_decode(&envelope_encode(&env).unwrap()).unwrap(),
+        env
+    );
+    envelope_validate(&env).unwrap();
+}
+
+#[test]
+fn oversized_auth_context_is_refused_on_decode() {
+    let limits = DecodeLimits::default().with_max_auth_context(64);
+    let env = envelope(state(1, 0), vec![0; 65]);
+
+    assert!(matches!(
+        decode(&env, &limits),
+        Err(WireError::Limit(LimitError::AuthContextTooLarge {
+            len: 65,
+            max: 64
+        }))
+    ));
+    assert!(matches!(
+        envelope_validate_with(&env, &limits),
+        Err(ValidateError::Limit(LimitError::AuthContextTooLarge { .. }))
+    ));
+}
+
+#[test]
+fn repeated_items_and_map_entries_are_counted() {
+    let limits = DecodeLimits::default()
+        .with_max_repeated(8)
+        .with_max_map_entries(2);
+
+    decode(&envelope(state(8, 0), Vec::new()), &limits).unwrap();
+    assert!(matches!(
+        decode(&envelope(state(9, 0), Vec::new()), &limits),
+        Err(WireError::Limit(LimitError::TooManyItems { .. }))
+    ));
+
+    decode(&envelope(command(2, "open", 0), Vec::new()), &limits).unwrap();
+    assert!(matches!(
+        decode(&envelope(command(3, "open", 0), Vec::new()), &limits),
+        Err(WireError::Limit(LimitError::TooManyEntries { .. }))
+    ));
+}
+
+#[test]
+fn long_strings_are_refused() {
+    let limits = DecodeLimits::default().with_max_string_len(32);
+    let env = envelope(command(0, &"x".repeat(33), 0), Vec::new());
+
+    assert!(matches!(
+        decode(&env, &limits),
+        Err(WireError::Limit(LimitError::StringTooLong {
+            field: "command",
+            ..
+        }))
+    ));
+    assert!(matches!(
+        envelope_validate_with(&env, &limits),
+        Err(ValidateError::Limit(LimitError::StringTooLong { .. }))
+    ));
+}
+
+#[test]
+fn blob_limits_follow_the_message_class() {
+    let limits = DecodeLimits::default()
+        .with_max_blob(MsgClass::Telemetry, 16)
+        .with_max_blob(MsgClass::Command, 32);
+
+    decode(&envelope(state(1, 16), Vec::new()), &limits).unwrap();
+    assert!(matches!(
+        decode(&envelope(state(1, 17), Vec::new()), &limits),
+        Err(WireError::Limit(LimitError::BlobTooLarge {
+            msg_class: MsgClass::Telemetry,
+            len: 17,
+            max: 16,
+        }))
+    ));
+
+    decode(&envelope(command(0, "open", 32), Vec::new()), &limits).unwrap();
+    assert!(matches!(
+        decode(&envelope(command(0, "open", 33), Vec::new()), &limits),
+        Err(WireError::Limit(LimitError::BlobTooLarge {
+            msg_class: MsgClass::Command,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn malformed_input_is_reported_by_the_decoder() {
+    let mut bytes = envelope_encode(&envelope(state(1, 0), Vec::new())).unwrap();
+    bytes.truncate(bytes.len() - 1);
+    assert!(matches!(envelope_decode(&bytes), Err(WireError::Decode(_))));
+}
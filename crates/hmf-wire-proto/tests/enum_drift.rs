@@ -0,0 +1,57 @@
+//! Fails whenever a core enum and its generated prost enum disagree on which
+//! numbers are defined or what they mean.
+
+use hmf_core::envelope as core;
+use hmf_wire_proto::proto::v1 as proto;
+
+/// Wider than any enum in the schema, so additions on either side are caught.
+const PROBE: std::ops::RangeInclusive<i32> = -1..=64;
+
+/// `LinkDown` means `LINK_DOWN`; proto names may carry an enum prefix such as
+/// `HEALTH_` or `ALARM_`.
+fn same_meaning(core_name: &str, proto_name: &str) -> bool {
+    let proto_name = proto_name.replace('_', "");
+    proto_name.ends_with(&core_name.to_ascii_uppercase())
+}
+
+macro_rules! drift_test {
+    ($test:ident, $core:ident) => {
+        #[test]
+        fn $test() {
+            for v in PROBE {
+                let core = core::$core::from_i32(v);
+                assert_eq!(
+                    core.to_i32(),
+                    v,
+                    "{} {v} does not round-trip",
+                    stringify!($core)
+                );
+                match proto::$core::try_from(v) {
+                    Ok(p) => assert!(
+                        same_meaning(&format!("{core:?}"), p.as_str_name()),
+                        "{} {v}: core {core:?}, proto {}",
+                        stringify!($core),
+                        p.as_str_name(),
+                    ),
+                    Err(_) => assert_eq!(
+                        core,
+                        core::$core::Unknown(v),
+                        "{} {v} is defined in core but not in the proto",
+                        stringify!($core),
+                    ),
+                }
+            }
+        }
+    };
+}
+
+drift_test!(msg_class, MsgClass);
+drift_test!(delivery_profile, DeliveryProfile);
+drift_test!(sig_alg, SigAlg);
+drift_test!(ack_status, AckStatus);
+drift_test!(result_status, ResultStatus);
+drift_test!(death_reason, DeathReason);
+drift_test!(health, Health);
+drift_test!(quality, Quality);
+drift_test!(alarm_severity, AlarmSeverity);
+drift_test!(observation_type, ObservationType);
@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use hmf_core::Envelope;
+use hmf_core::envelope::{
+    Ack, AckStatus, Alarm, AlarmSeverity, Command, CommandPayload, CommandRequest, Config,
+    ConfigPayload, ConfigQuery, ConfigSnapshot, ConfigUpdate, DeathReason, DeliveryProfile,
+    Engineering, EngineeringPayload, EngineeringRequest, EngineeringResult, EnvelopeBuilder,
+    Health, LifecycleBirth, LifecycleDeath, LifecycleHeartbeat, Observation, ObservationType,
+    OpResult, Payload, Quality, RejectReason, ResultStatus, SigAlg, StateItem, StateUpdate,
+    StateValue, Subject, Telemetry, TelemetryPayload,
+};
+use hmf_core::ids::{IdempotencyKey, TransactionId};
+use hmf_wire_proto::error::WireError;
+use hmf_wire_proto::wire::protobuf::{envelope_decode, envelope_decode_strict, envelope_encode};
+use prost::encoding::{decode_key, decode_varint, encode_key, encode_varint};
+
+fn params() -> BTreeMap<String, String> {
+    [("mode", "auto"), ("setpoint", "42"), ("alarm", "off")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn envelope() -> Envelope {
+    with_payload(Payload::Command(Command {
+        payload: Some(CommandPayload::Request(CommandRequest {
+            request_id: "req-1".to_string(),
+            command: "set".to_string(),
+            target: "valve-1".to_string(),
+            params: params(),
+            blob: Vec::new(),
+            requires_confirmation: false,
+        })),
+    }))
+}
+
+fn with_payload(payload: Payload) -> Envelope {
+    EnvelopeBuilder::new()
+        .with_sender_id("device-1")
+        .with_sender_instance("boot-1")
+        .with_counter(7)
+        .with_ttl_ms(5_000)
+        .with_delivery_profile(DeliveryProfile::OrderedReliable)
+        .with_payload(payload)
+        .with_security(
+            SigAlg::Ed25519,
+            "device-1:ed25519:v1",
+            vec![1; 8],
+            vec![0; 64],
+        )
+        .build()
+}
+
+/// Top-level fields as `(number, raw bytes including the key)`.
+fn fields(mut buf: &[u8]) -> Vec<(u32, Vec<u8>)> {
+    let mut out = Vec::new();
+    while !buf.is_empty() {
+        let start = buf;
+        let (number, wire_type) = decode_key(&mut buf).unwrap();
+        match wire_type as u8 {
+            0 => {
+                decode_varint(&mut buf).unwrap();
+            }
+            2 => {
+                let len = decode_varint(&mut buf).unwrap() as usize;
+                buf = &buf[len..];
+            }
+            other => panic!("unexpected wire type {other}"),
+        }
+        out.push((number, start[..start.len() - buf.len()].to_vec()));
+    }
+    out
+}
+
+fn join(fields: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    fields.iter().flat_map(|(_, raw)| raw.clone()).collect()
+}
+
+fn varint_field(number: u32, value: u64) -> (u32, Vec<u8>) {
+    let mut raw = Vec::new();
+    encode_key(number, prost::encoding::WireType::Varint, &mut raw);
+    encode_varint(value, &mut raw);
+    (number, raw)
+}
+
+fn position(fields: &[(u32, Vec<u8>)], number: u32) -> usize {
+    fields.iter().position(|(n, _)| *n == number).unwrap()
+}
+
+fn strict_reason(bytes: &[u8]) -> String {
+    match envelope_decode_strict(bytes) {
+        Err(WireError::Convert(reason)) => reason,
+        other => panic!("expected a conversion error, got {other:?}"),
+    }
+}
+
+#[test]
+fn canonical_encoding_is_accepted() {
+    let env = envelope();
+    let bytes = envelope_encode(&env).unwrap();
+    assert_eq!(envelope_decode_strict(&bytes).unwrap(), env);
+}
+
+/// Every field of every schema message populated, so a field missing from the
+/// strict schema shows up as a rejected canonical encoding.
+#[test]
+fn every_payload_field_is_known() {
+    let telemetry = |payload| {
+        Payload::Telemetry(Telemetry {
+            payload: Some(payload),
+        })
+    };
+    let command = |payload| {
+        Payload::Command(Command {
+            payload: Some(payload),
+        })
+    };
+    let config = |payload| {
+        Payload::Config(Config {
+            payload: Some(payload),
+        })
+    };
+    let engineering = |payload| {
+        Payload::Engineering(Engineering {
+            payload: Some(payload),
+        })
+    };
+    let ack = || Ack {
+        status: AckStatus::Rejected,
+        detail: "d".to_string(),
+        reason: RejectReason::Authorization,
+    };
+    let result = || OpResult {
+        status: ResultStatus::Failed,
+        detail: "d".to_string(),
+    };
+    let item = |value| StateItem {
+        key: "k".to_string(),
+        value: Some(value),
+        unit: "u".to_string(),
+        quality: Quality::Stale,
+    };
+    let payloads = [
+        telemetry(TelemetryPayload::Birth(LifecycleBirth {
+            vendor: "v".to_string(),
+            model: "m".to_string(),
+            serial: "s".to_string(),
+            hw_rev: "h".to_string(),
+            fw_rev: "f".to_string(),
+            uptime_ms: 1,
+            capabilities: vec!["a".to_string(), "b".to_string()],
+        })),
+        telemetry(TelemetryPayload::Heartbeat(LifecycleHeartbeat {
+            uptime_ms: 1,
+            health: Health::Degraded,
+        })),
+        telemetry(TelemetryPayload::Death(LifecycleDeath {
+            reason: DeathReason::Reboot,
+            uptime_ms: 1,
+            detail: "d".to_string(),
+        })),
+        telemetry(TelemetryPayload::State(StateUpdate {
+            local_seq: 1,
+            items: vec![
+                item(StateValue::F64(1.5)),
+                item(StateValue::I64(-1)),
+                item(StateValue::U64(1)),
+                item(StateValue::B(true)),
+                item(StateValue::S("s".to_string())),
+                item(StateValue::Blob(vec![1])),
+            ],
+        })),
+        telemetry(TelemetryPayload::Alarm(Alarm {
+            severity: AlarmSeverity::Error,
+            code: "c".to_string(),
+            summary: "s".to_string(),
+            detail: "d".to_string(),
+            related_key: "k".to_string(),
+            recommended_action: "a".to_string(),
+        })),
+        telemetry(TelemetryPayload::Observation(Observation {
+            subject: Some(Subject {
+                subject_id: "device-2".to_string(),
+            }),
+            r#type: ObservationType::LinkDown,
+            confidence_pct: 90,
+            summary: "s".to_string(),
+            detail: "d".to_string(),
+        })),
+        command(CommandPayload::Request(CommandRequest {
+            request_id: "r".to_string(),
+            command: "c".to_string(),
+            target: "t".to_string(),
+            params: params(),
+            blob: vec![1],
+            requires_confirmation: true,
+        })),
+        command(CommandPayload::Ack(ack())),
+        command(CommandPayload::Result(result())),
+        config(ConfigPayload::Query(ConfigQuery {
+            keys: vec!["a".to_string(), "b".to_string()],
+        })),
+        config(ConfigPayload::Snapshot(ConfigSnapshot {
+            config_version: 3,
+            params: params(),
+        })),
+        config(ConfigPayload::Update(ConfigUpdate {
+            update_id: "u".to_string(),
+            strict: true,
+            params: params(),
+            expected_version: Some(3),
+        })),
+        config(ConfigPayload::Ack(ack())),
+        config(ConfigPayload::Result(result())),
+        engineering(EngineeringPayload::Request(EngineeringRequest {
+            request_id: "r".to_string(),
+            action: "a".to_string(),
+            target: "t".to_string(),
+            params: params(),
+            blob: vec![1],
+            requires_confirmation: true,
+        })),
+        engineering(EngineeringPayload::Ack(ack())),
+        engineering(EngineeringPayload::Result(EngineeringResult {
+            status: ResultStatus::Completed,
+            detail: "d".to_string(),
+            outputs: params(),
+            blob: vec![1],
+        })),
+    ];
+
+    for payload in payloads {
+        let mut env = with_payload(payload);
+        env.transaction_id = TransactionId::new("txn-1");
+        env.idempotency_key = IdempotencyKey::new("idem-1");
+        env.topic = "topic".to_string();
+        env.target = "target".to_string();
+        env.scope = "scope".to_string();
+        let bytes = envelope_encode(&env).unwrap();
+        assert_eq!(envelope_decode_strict(&bytes).unwrap(), env);
+    }
+}
+
+#[test]
+fn unknown_fields_are_refused() {
+    let env = envelope();
+    let mut f = fields(&envelope_encode(&env).unwrap());
+    f.push(varint_field(99, 1));
+    let bytes = join(&f);
+
+    // The lenient decoder silently drops the field.
+    assert_eq!(envelope_decode(&bytes).unwrap(), env);
+    assert_eq!(strict_reason(&bytes), "unknown field 99 in Envelope");
+}
+
+#[test]
+fn out_of_order_and_repeated_fields_are_refused() {
+    let canonical = fields(&envelope_encode(&envelope()).unwrap());
+
+    let mut f = canonical.clone();
+    let counter = position(&f, 5);
+    f.swap(counter, counter + 1);
+    assert_eq!(
+        strict_reason(&join(&f)),
+        "Envelope.counter out of field order"
+    );
+
+    let mut f = canonical;
+    let key_id = position(&f, 32);
+    f.insert(key_id, f[key_id].clone());
+    assert_eq!(
+        strict_reason(&join(&f)),
+        "Envelope.key_id appears more than once"
+    );
+}
+
+#[test]
+fn padded_varints_and_explicit_defaults_are_refused() {
+    let canonical = fields(&envelope_encode(&envelope()).unwrap());
+
+    let mut f = canonical.clone();
+    let counter = position(&f, 5);
+    f[counter].1 = vec![0x28, 0x87, 0x00];
+    assert_eq!(
+        strict_reason(&join(&f)),
+        "non-canonical varint in Envelope.counter"
+    );
+
+    // An empty `scope`, which a canonical encoder omits.
+    let mut f = canonical;
+    let payload = position(&f, 21);
+    f.insert(payload, (12, vec![0x62, 0x00]));
+    assert!(strict_reason(&join(&f)).starts_with("non-canonical encoding"));
+}
+
+#[test]
+fn unknown_header_enum_values_are_refused() {
+    let canonical = fields(&envelope_encode(&envelope()).unwrap());
+
+    for (number, name) in [(2, "msg_class"), (9, "delivery_profile"), (30, "sig_alg")] {
+        let mut f = canonical.clone();
+        let at = position(&f, number);
+        f[at] = varint_field(number, 77);
+        assert_eq!(strict_reason(&join(&f)), format!("unknown {name} value 77"));
+    }
+}